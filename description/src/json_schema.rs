@@ -0,0 +1,402 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::anyhow;
+use scale_info::{form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
+use serde_json::{json, Map, Value};
+
+use crate::transformer::Transformer;
+
+type SchemaTransformer<'a> = Transformer<'a, Value, SchemaState>;
+
+/// The JSON Schema dialect of the generated schemas.
+const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+#[derive(Default)]
+struct SchemaState {
+    /// Types that were referenced while their schema was still being computed.
+    recursive: RefCell<BTreeSet<u32>>,
+    /// Schemas of recursive types, keyed by type id. They end up in the `$defs` of the root schema.
+    defs: RefCell<BTreeMap<String, Value>>,
+}
+
+/// Generates a JSON Schema (draft 2020-12) for the type that is registered under the given `type_id`.
+///
+/// The schema describes the JSON representation of `scale_value::Value`s of this type:
+/// - Composites with named fields are objects, composites with unnamed fields are arrays.
+/// - Variants are objects with a `name` tag and the `values` of the variant, combined via `oneOf`.
+/// - Sequences and arrays are arrays, 256 bit integers are arrays of their 32 bytes.
+/// - Bit sequences are objects with a `__bitvec__values__` array of booleans.
+///
+/// Compact types are described by the schema of their inner type, with `"format": "compact"` as an annotation.
+/// Recursive types are placed in the `$defs` of the root schema and referenced via `$ref`.
+pub fn json_schema(type_id: u32, type_registry: &PortableRegistry) -> anyhow::Result<Value> {
+    fn reference_on_recurse(
+        type_id: u32,
+        _ty: &Type<PortableForm>,
+        transformer: &SchemaTransformer,
+    ) -> Option<anyhow::Result<Value>> {
+        transformer.state().recursive.borrow_mut().insert(type_id);
+        Some(Ok(def_ref(type_id)))
+    }
+
+    fn return_cached(
+        _type_id: u32,
+        _ty: &Type<PortableForm>,
+        cached: &Value,
+        _transformer: &SchemaTransformer,
+    ) -> Option<anyhow::Result<Value>> {
+        Some(Ok(cached.clone()))
+    }
+
+    let transformer = SchemaTransformer::new(
+        ty_schema,
+        reference_on_recurse,
+        return_cached,
+        SchemaState::default(),
+        type_registry,
+    );
+    let root = transformer.resolve(type_id)?;
+
+    let mut schema = Map::new();
+    schema.insert("$schema".into(), SCHEMA_DIALECT.into());
+    match root {
+        Value::Object(root) => schema.extend(root),
+        // Enums without variants are the only types whose schema is `false`, as they cannot be instantiated.
+        _ => {
+            schema.insert("not".into(), json!({}));
+        }
+    }
+    let defs = transformer.state().defs.take();
+    if !defs.is_empty() {
+        schema.insert("$defs".into(), Value::Object(defs.into_iter().collect()));
+    }
+    Ok(Value::Object(schema))
+}
+
+fn def_ref(type_id: u32) -> Value {
+    json!({ "$ref": format!("#/$defs/{type_id}") })
+}
+
+fn ty_schema(
+    type_id: u32,
+    ty: &Type<PortableForm>,
+    transformer: &SchemaTransformer,
+) -> anyhow::Result<Value> {
+    let mut schema = type_def_schema(&ty.type_def, transformer)?;
+    if let Value::Object(object) = &mut schema {
+        if let Some(ident) = ty.path.ident() {
+            object.insert("title".into(), ident.into());
+        }
+        if !ty.docs.is_empty() {
+            object.insert("description".into(), ty.docs.join("\n").trim().into());
+        }
+    }
+
+    // If the type referenced itself, the references need to point to its definition.
+    let state = transformer.state();
+    if state.recursive.borrow().contains(&type_id) {
+        state.defs.borrow_mut().insert(type_id.to_string(), schema);
+        return Ok(def_ref(type_id));
+    }
+    Ok(schema)
+}
+
+fn type_def_schema(
+    type_def: &TypeDef<PortableForm>,
+    transformer: &SchemaTransformer,
+) -> anyhow::Result<Value> {
+    match type_def {
+        TypeDef::Composite(composite) => fields_schema(&composite.fields, transformer),
+        TypeDef::Variant(variant) => {
+            if variant.variants.is_empty() {
+                return Ok(Value::Bool(false));
+            }
+            let variants = variant
+                .variants
+                .iter()
+                .map(|variant| {
+                    Ok(json!({
+                        "type": "object",
+                        "properties": {
+                            "name": { "const": variant.name },
+                            "values": fields_schema(&variant.fields, transformer)?,
+                        },
+                        "required": ["name", "values"],
+                        "additionalProperties": false,
+                    }))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(json!({ "oneOf": variants }))
+        }
+        TypeDef::Sequence(sequence) => Ok(json!({
+            "type": "array",
+            "items": transformer.resolve(sequence.type_param.id)?,
+        })),
+        TypeDef::Array(array) => Ok(json!({
+            "type": "array",
+            "items": transformer.resolve(array.type_param.id)?,
+            "minItems": array.len,
+            "maxItems": array.len,
+        })),
+        TypeDef::Tuple(tuple) => {
+            let elements = tuple
+                .fields
+                .iter()
+                .map(|ty| transformer.resolve(ty.id))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(tuple_schema(elements))
+        }
+        TypeDef::Primitive(primitive) => Ok(primitive_schema(primitive)),
+        TypeDef::Compact(compact) => {
+            let mut inner = transformer.resolve(compact.type_param.id)?;
+            if let Value::Object(object) = &mut inner {
+                object.insert("format".into(), "compact".into());
+            }
+            Ok(inner)
+        }
+        TypeDef::BitSequence(_) => Ok(json!({
+            "type": "object",
+            "properties": {
+                "__bitvec__values__": { "type": "array", "items": { "type": "boolean" } },
+            },
+            "required": ["__bitvec__values__"],
+            "additionalProperties": false,
+            "format": "bit-sequence",
+        })),
+    }
+}
+
+fn fields_schema(
+    fields: &[Field<PortableForm>],
+    transformer: &SchemaTransformer,
+) -> anyhow::Result<Value> {
+    let all_fields_named = fields.iter().all(|f| f.name.is_some());
+    let all_fields_unnamed = fields.iter().all(|f| f.name.is_none());
+    match (all_fields_named, all_fields_unnamed) {
+        (true, true) => Ok(tuple_schema(vec![])),
+        (true, false) => {
+            let mut properties = Map::new();
+            for field in fields {
+                let name = field.name.clone().unwrap();
+                properties.insert(name, transformer.resolve(field.ty.id)?);
+            }
+            let required = properties.keys().cloned().collect::<Vec<_>>();
+            Ok(json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            }))
+        }
+        (false, true) => {
+            let elements = fields
+                .iter()
+                .map(|field| transformer.resolve(field.ty.id))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(tuple_schema(elements))
+        }
+        (false, false) => Err(anyhow!(
+            "combination of named and unnamed fields in compound type"
+        )),
+    }
+}
+
+/// Unnamed composites and tuples are arrays with a fixed number of elements.
+fn tuple_schema(elements: Vec<Value>) -> Value {
+    if elements.is_empty() {
+        return json!({ "type": "array", "maxItems": 0 });
+    }
+    json!({
+        "type": "array",
+        "prefixItems": elements,
+        "items": false,
+        "minItems": elements.len(),
+    })
+}
+
+fn primitive_schema(primitive: &TypeDefPrimitive) -> Value {
+    match primitive {
+        TypeDefPrimitive::Bool => json!({ "type": "boolean" }),
+        TypeDefPrimitive::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        TypeDefPrimitive::Str => json!({ "type": "string" }),
+        TypeDefPrimitive::U8 => integer_schema(u8::MIN, u8::MAX),
+        TypeDefPrimitive::U16 => integer_schema(u16::MIN, u16::MAX),
+        TypeDefPrimitive::U32 => integer_schema(u32::MIN, u32::MAX),
+        TypeDefPrimitive::U64 => integer_schema(u64::MIN, u64::MAX),
+        // JSON numbers beyond 64 bits are not representable here, so only the sign is constrained.
+        TypeDefPrimitive::U128 => json!({ "type": "integer", "minimum": 0 }),
+        TypeDefPrimitive::I8 => integer_schema(i8::MIN, i8::MAX),
+        TypeDefPrimitive::I16 => integer_schema(i16::MIN, i16::MAX),
+        TypeDefPrimitive::I32 => integer_schema(i32::MIN, i32::MAX),
+        TypeDefPrimitive::I64 => integer_schema(i64::MIN, i64::MAX),
+        TypeDefPrimitive::I128 => json!({ "type": "integer" }),
+        // 256 bit integers are represented as their 32 little endian bytes.
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => json!({
+            "type": "array",
+            "items": integer_schema(u8::MIN, u8::MAX),
+            "minItems": 32,
+            "maxItems": 32,
+        }),
+    }
+}
+
+fn integer_schema(min: impl Into<Value>, max: impl Into<Value>) -> Value {
+    let (min, max) = (min.into(), max.into());
+    json!({ "type": "integer", "minimum": min, "maximum": max })
+}
+
+#[cfg(test)]
+mod tests {
+    use parity_scale_codec::Compact;
+    use pretty_assertions::assert_eq;
+    use scale_info::{PortableRegistry, TypeInfo};
+    use serde_json::json;
+
+    use super::json_schema;
+
+    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+        let mut registry = scale_info::Registry::new();
+        let m = scale_info::MetaType::new::<T>();
+        let ty = registry.register_type(&m);
+        (ty.id, registry.into())
+    }
+
+    #[test]
+    fn composites_and_variants() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Transfer {
+            dest: Dest,
+            value: Compact<u128>,
+            memo: Option<Vec<u8>>,
+        }
+
+        /// Where to send funds to.
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Dest {
+            Id([u8; 2]),
+            Index(u32, bool),
+        }
+
+        let (type_id, type_registry) = make_type::<Transfer>();
+        let byte = json!({ "type": "integer", "minimum": 0, "maximum": 255 });
+
+        assert_eq!(
+            json_schema(type_id, &type_registry).unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "Transfer",
+                "type": "object",
+                "properties": {
+                    "dest": {
+                        "title": "Dest",
+                        "description": "Where to send funds to.",
+                        "oneOf": [
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "const": "Id" },
+                                    "values": {
+                                        "type": "array",
+                                        "prefixItems": [
+                                            { "type": "array", "items": byte, "minItems": 2, "maxItems": 2 }
+                                        ],
+                                        "items": false,
+                                        "minItems": 1,
+                                    },
+                                },
+                                "required": ["name", "values"],
+                                "additionalProperties": false,
+                            },
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "const": "Index" },
+                                    "values": {
+                                        "type": "array",
+                                        "prefixItems": [
+                                            { "type": "integer", "minimum": 0, "maximum": 4294967295u32 },
+                                            { "type": "boolean" },
+                                        ],
+                                        "items": false,
+                                        "minItems": 2,
+                                    },
+                                },
+                                "required": ["name", "values"],
+                                "additionalProperties": false,
+                            },
+                        ],
+                    },
+                    "value": { "type": "integer", "minimum": 0, "format": "compact" },
+                    "memo": {
+                        "title": "Option",
+                        "oneOf": [
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "const": "None" },
+                                    "values": { "type": "array", "maxItems": 0 },
+                                },
+                                "required": ["name", "values"],
+                                "additionalProperties": false,
+                            },
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "name": { "const": "Some" },
+                                    "values": {
+                                        "type": "array",
+                                        "prefixItems": [{ "type": "array", "items": byte }],
+                                        "items": false,
+                                        "minItems": 1,
+                                    },
+                                },
+                                "required": ["name", "values"],
+                                "additionalProperties": false,
+                            },
+                        ],
+                    },
+                },
+                "required": ["dest", "memo", "value"],
+                "additionalProperties": false,
+            })
+        );
+    }
+
+    #[test]
+    fn recursive_types_are_referenced() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Tree {
+            value: bool,
+            children: Vec<Tree>,
+        }
+
+        let (type_id, type_registry) = make_type::<Tree>();
+
+        assert_eq!(
+            json_schema(type_id, &type_registry).unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$ref": format!("#/$defs/{type_id}"),
+                "$defs": {
+                    type_id.to_string(): {
+                        "title": "Tree",
+                        "type": "object",
+                        "properties": {
+                            "value": { "type": "boolean" },
+                            "children": {
+                                "type": "array",
+                                "items": { "$ref": format!("#/$defs/{type_id}") },
+                            },
+                        },
+                        "required": ["children", "value"],
+                        "additionalProperties": false,
+                    },
+                },
+            })
+        );
+    }
+}
@@ -17,9 +17,12 @@
 //! - A human readable description of the type via [`crate::type_description`].
 //! - An exemplary rust value of the type via [`crate::rust_value`].
 //! - An exemplary scale value of the type via [`crate::scale_value`].
+//! - A JSON Schema for the JSON representation of scale values of the type via [`crate::json_schema`].
 
 mod description;
 mod formatting;
+#[cfg(feature = "json-schema")]
+mod json_schema;
 pub mod transformer;
 
 #[cfg(feature = "type-example")]
@@ -31,12 +34,20 @@ pub mod type_example;
 
 #[cfg(feature = "type-example")]
 pub use type_example::{
-    rust_value::{example as rust_value, example_from_seed as rust_value_from_seed},
-    scale_value::{example as scale_value, example_from_seed as scale_value_from_seed},
+    rust_value::{
+        example as rust_value, example_from_seed as rust_value_from_seed,
+        exhaustive_examples as rust_value_exhaustive,
+    },
+    scale_value::{
+        example as scale_value, example_from_seed as scale_value_from_seed,
+        exhaustive_examples as scale_value_exhaustive,
+    },
 };
 
 pub use description::type_description;
 pub use formatting::format_type_description;
+#[cfg(feature = "json-schema")]
+pub use json_schema::json_schema;
 
 #[cfg(test)]
 mod tests {
@@ -49,7 +60,10 @@ mod tests {
     use scale_info::{PortableRegistry, TypeInfo};
     use scale_typegen::TypeGeneratorSettings;
 
-    use crate::{type_description, type_example::rust_value};
+    use crate::{
+        type_description,
+        type_example::{rust_value, strategy::ExampleStrategy},
+    };
 
     fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
         let mut registry = scale_info::Registry::new();
@@ -367,4 +381,170 @@ mod tests {
             quote! {types::scale_typegen_description::tests::T1( Compact(161u8), )}.to_string()
         );
     }
+
+    #[test]
+    fn rust_value_exhaustive() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Shape {
+            Invisible,
+            Circle(u16),
+            Polygon { corners: Vec<bool> },
+        }
+
+        use quote::quote;
+
+        let (type_id, type_registry) = make_type::<Shape>();
+        let settings = TypeGeneratorSettings::new();
+        let examples =
+            rust_value::exhaustive_examples(type_id, &type_registry, &settings, 100, None, None)
+                .map(|e| e.unwrap().to_string())
+                .collect::<Vec<_>>();
+
+        let shape = quote!(types::scale_typegen_description::tests::Shape);
+        assert_eq!(
+            examples,
+            [
+                quote!(#shape::Invisible),
+                quote!(#shape::Circle(0u16,)),
+                quote!(#shape::Polygon{ corners: vec![], }),
+                quote!(#shape::Invisible),
+                quote!(#shape::Circle(65535u16,)),
+                quote!(#shape::Polygon{ corners: vec![false], }),
+                quote!(#shape::Invisible),
+                quote!(#shape::Circle(0u16,)),
+                quote!(#shape::Polygon{ corners: vec![true, false, true], }),
+            ]
+            .map(|e| e.to_string())
+        );
+    }
+
+    #[test]
+    fn rust_value_strategy() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Message {
+            Ping,
+            Text {
+                lines: Vec<String>,
+                #[codec(compact)]
+                id: u16,
+                n: Compact<u16>,
+                flags: BitVec<u8, Lsb0>,
+            },
+        }
+
+        use bitvec::{order::Lsb0, vec::BitVec};
+        use quote::quote;
+
+        let (type_id, type_registry) = make_type::<Message>();
+        let settings = TypeGeneratorSettings::new();
+        let strategy = ExampleStrategy::new()
+            .strings(["hello"])
+            .integers(7..=7)
+            .sequence_len(1..=1)
+            .bit_sequence([true, true])
+            .variant_weight("Ping", 0)
+            .bit_sequence_path(quote!(Bits))
+            .compact_path(quote!(codec::Compact));
+        let example = rust_value::example_with_strategy(
+            type_id,
+            &type_registry,
+            &settings,
+            42,
+            strategy,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            example.to_string(),
+            quote! {
+                types::scale_typegen_description::tests::Message::Text{
+                    lines: vec!["hello".into()],
+                    id: 7u16,
+                    n: codec::Compact(7u16),
+                    flags: Bits::from_iter([true, true]),
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn rust_value_bounded_recursion() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Call {
+            Remark(Vec<u8>),
+            Batch { calls: Vec<Call> },
+        }
+
+        use quote::quote;
+
+        let (type_id, type_registry) = make_type::<Call>();
+        let settings = TypeGeneratorSettings::new();
+        let example = |strategy| {
+            rust_value::example_with_strategy(
+                type_id,
+                &type_registry,
+                &settings,
+                42,
+                strategy,
+                None,
+                None,
+            )
+        };
+
+        // Recursive types yield an error by default.
+        assert!(example(ExampleStrategy::new().variant_weight("Remark", 0)).is_err());
+
+        let strategy = ExampleStrategy::new()
+            .variant_weight("Remark", 0)
+            .max_recursion_depth(1);
+        let call = quote!(types::scale_typegen_description::tests::Call);
+        assert_eq!(
+            example(strategy).unwrap().to_string(),
+            quote! {
+                #call::Batch{
+                    calls: vec![#call::Remark(vec![],), #call::Remark(vec![],)],
+                }
+            }
+            .to_string()
+        );
+        // Boxed fields are wrapped in `Box::new`, like the generated `Box<Tree>` fields.
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Tree {
+            Leaf(bool),
+            Node(Box<Tree>, Box<Tree>),
+        }
+
+        let (type_id, type_registry) = make_type::<Tree>();
+        let strategy = ExampleStrategy::new()
+            .variant_weight("Leaf", 0)
+            .max_recursion_depth(1);
+        let example = rust_value::example_with_strategy(
+            type_id,
+            &type_registry,
+            &settings,
+            42,
+            strategy,
+            None,
+            None,
+        )
+        .unwrap();
+        let tree = quote!(types::scale_typegen_description::tests::Tree);
+        assert_eq!(
+            example.to_string(),
+            quote! {
+                #tree::Node(
+                    Box::new(#tree::Leaf(true,)),
+                    Box::new(#tree::Leaf(false,)),
+                )
+            }
+            .to_string()
+        );
+    }
 }
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use scale_info::TypeDefPrimitive;
+use scale_value::Primitive;
+
+/// The lengths of sequences and bit sequences that are cycled through: empty, one and many elements.
+pub(crate) const SEQUENCE_LENGTHS: [usize; 3] = [0, 1, 3];
+
+/// Keeps track of how often each type has been visited while generating exhaustive examples.
+///
+/// Every type with multiple cases (enum variants, sequence lengths, boundary values of primitives)
+/// cycles through its cases on each visit, such that each case is generated at least once
+/// if the type is visited often enough.
+#[derive(Debug, Default)]
+pub(crate) struct Coverage {
+    /// The number of visits and the number of cases for each type id.
+    visits: HashMap<u32, (usize, usize)>,
+}
+
+impl Coverage {
+    /// Returns the index of the case to generate for the type on this visit.
+    pub(crate) fn next_case(&mut self, type_id: u32, cases: usize) -> usize {
+        let (visits, _) = self.visits.entry(type_id).or_insert((0, cases));
+        let case = *visits % cases.max(1);
+        *visits += 1;
+        case
+    }
+
+    /// True if all cases of every visited type have been generated.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.visits.values().all(|(visits, cases)| visits >= cases)
+    }
+}
+
+/// Yields examples until all cases are covered, an error occurs, or the budget is spent.
+pub(crate) fn examples_until_complete<R>(
+    budget: usize,
+    mut next_example: impl FnMut() -> anyhow::Result<R>,
+    is_complete: impl Fn() -> bool,
+) -> impl Iterator<Item = anyhow::Result<R>> {
+    let mut done = false;
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let example = next_example();
+        done = example.is_err() || is_complete();
+        Some(example)
+    })
+    .take(budget)
+}
+
+/// The boundary values of a primitive type: zero, the minimum and maximum, or the empty string.
+pub(crate) fn boundary_primitives(def: &TypeDefPrimitive) -> Vec<Primitive> {
+    match def {
+        TypeDefPrimitive::Bool => vec![Primitive::Bool(false), Primitive::Bool(true)],
+        TypeDefPrimitive::Char => vec![Primitive::Char('\0'), Primitive::Char(char::MAX)],
+        TypeDefPrimitive::Str => vec![
+            Primitive::String("".into()),
+            Primitive::String("Foo".into()),
+        ],
+        TypeDefPrimitive::U8 => unsigned(u8::MAX as u128),
+        TypeDefPrimitive::U16 => unsigned(u16::MAX as u128),
+        TypeDefPrimitive::U32 => unsigned(u32::MAX as u128),
+        TypeDefPrimitive::U64 => unsigned(u64::MAX as u128),
+        TypeDefPrimitive::U128 => unsigned(u128::MAX),
+        // all bits unset and all bits set
+        TypeDefPrimitive::U256 => vec![Primitive::U256([0; 32]), Primitive::U256([u8::MAX; 32])],
+        TypeDefPrimitive::I8 => signed(i8::MIN as i128, i8::MAX as i128),
+        TypeDefPrimitive::I16 => signed(i16::MIN as i128, i16::MAX as i128),
+        TypeDefPrimitive::I32 => signed(i32::MIN as i128, i32::MAX as i128),
+        TypeDefPrimitive::I64 => signed(i64::MIN as i128, i64::MAX as i128),
+        TypeDefPrimitive::I128 => signed(i128::MIN, i128::MAX),
+        TypeDefPrimitive::I256 => vec![Primitive::I256([0; 32]), Primitive::I256([u8::MAX; 32])],
+    }
+}
+
+fn unsigned(max: u128) -> Vec<Primitive> {
+    vec![Primitive::U128(0), Primitive::U128(max)]
+}
+
+fn signed(min: i128, max: i128) -> Vec<Primitive> {
+    vec![
+        Primitive::I128(0),
+        Primitive::I128(min),
+        Primitive::I128(max),
+    ]
+}
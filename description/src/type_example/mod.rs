@@ -1,7 +1,13 @@
+/// Cycling through the cases of types for exhaustive examples
+mod exhaustive;
+/// Bounded nesting of recursive types in examples
+mod recursion;
 /// Generate an exemplary rust value of some type
 pub mod rust_value;
 /// Generate an exemplary scale value of some type
 pub mod scale_value;
+/// Configure the values chosen for examples
+pub mod strategy;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,122 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use anyhow::anyhow;
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, Variant};
+
+/// Allows recursive types to nest up to a depth. Below that depth, examples are steered towards termination:
+/// sequences are empty and the enum variant with the fewest fields that leads to a finite example is chosen.
+#[derive(Debug)]
+pub(crate) struct RecursionGuard {
+    max_recursion_depth: usize,
+    /// The types for which a finite example exists, when choosing empty sequences and the right variants,
+    /// together with the round of the fixpoint computation in which they became finite.
+    finite_rounds: HashMap<u32, usize>,
+    /// The types that are currently generated, and whether they must terminate.
+    stack: RefCell<Vec<(u32, bool)>>,
+}
+
+impl RecursionGuard {
+    pub(crate) fn new(max_recursion_depth: usize, types: &PortableRegistry) -> Self {
+        RecursionGuard {
+            max_recursion_depth,
+            finite_rounds: finite_rounds(types),
+            stack: RefCell::new(vec![]),
+        }
+    }
+
+    /// Marks the type as generated until [`RecursionGuard::exit`] is called. Errors if the type must terminate,
+    /// but every example of it is infinitely nested.
+    pub(crate) fn enter(&self, type_id: u32) -> anyhow::Result<()> {
+        let mut stack = self.stack.borrow_mut();
+        let parent_terminates = stack.last().is_some_and(|(_, terminates)| *terminates);
+        let recursion_depth = stack.iter().filter(|(id, _)| *id == type_id).count();
+        let terminates = parent_terminates || recursion_depth >= self.max_recursion_depth;
+        if terminates && !self.finite_rounds.contains_key(&type_id) {
+            return Err(anyhow!(
+                "Cannot generate a finite example for recursive type with id {type_id}"
+            ));
+        }
+        stack.push((type_id, terminates));
+        Ok(())
+    }
+
+    pub(crate) fn exit(&self) {
+        self.stack.borrow_mut().pop();
+    }
+
+    /// True if the type that is currently generated must terminate.
+    pub(crate) fn must_terminate(&self) -> bool {
+        self.stack
+            .borrow()
+            .last()
+            .is_some_and(|(_, terminates)| *terminates)
+    }
+
+    /// The variant of the enum type with the fewest fields that has a finite example. Only variants whose
+    /// fields all became finite in an earlier round than the enum itself are considered, because a variant
+    /// with a field that is only finite through the enum could lead straight back to it.
+    pub(crate) fn terminating_variant<'a>(
+        &self,
+        type_id: u32,
+        variants: &'a [Variant<PortableForm>],
+    ) -> Option<&'a Variant<PortableForm>> {
+        let round = self
+            .finite_rounds
+            .get(&type_id)
+            .copied()
+            .unwrap_or(usize::MAX);
+        variants
+            .iter()
+            .filter(|variant| {
+                variant.fields.iter().all(|field| {
+                    self.finite_rounds
+                        .get(&field.ty.id)
+                        .is_some_and(|field_round| *field_round < round)
+                })
+            })
+            .min_by_key(|variant| variant.fields.len())
+    }
+}
+
+/// Computes the types that have a finite example, when choosing empty sequences and the right variants,
+/// together with the round in which they became finite. A type like `struct Human { mom: Box<Human> }`
+/// has no finite example.
+fn finite_rounds(types: &PortableRegistry) -> HashMap<u32, usize> {
+    let type_defs: HashMap<u32, &TypeDef<PortableForm>> = types
+        .types
+        .iter()
+        .map(|ty| (ty.id, &ty.ty.type_def))
+        .collect();
+    let mut finite: HashMap<u32, usize> = HashMap::new();
+    for round in 0.. {
+        let is_finite = |id: &u32| finite.contains_key(id);
+        let newly_finite = type_defs
+            .iter()
+            .filter(|(id, _)| !finite.contains_key(id))
+            .filter(|(_, type_def)| match type_def {
+                TypeDef::Composite(composite) => composite
+                    .fields
+                    .iter()
+                    .map(|f| f.ty.id)
+                    .all(|id| is_finite(&id)),
+                TypeDef::Variant(variant) => variant.variants.iter().any(|variant| {
+                    variant
+                        .fields
+                        .iter()
+                        .map(|f| f.ty.id)
+                        .all(|id| is_finite(&id))
+                }),
+                TypeDef::Array(array) => array.len == 0 || is_finite(&array.type_param.id),
+                TypeDef::Tuple(tuple) => tuple.fields.iter().map(|f| f.id).all(|id| is_finite(&id)),
+                TypeDef::Compact(compact) => is_finite(&compact.type_param.id),
+                TypeDef::Sequence(_) | TypeDef::Primitive(_) | TypeDef::BitSequence(_) => true,
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        if newly_finite.is_empty() {
+            break;
+        }
+        finite.extend(newly_finite.into_iter().map(|id| (id, round)));
+    }
+    finite
+}
@@ -1,11 +1,17 @@
+use super::{
+    exhaustive::{boundary_primitives, examples_until_complete, Coverage, SEQUENCE_LENGTHS},
+    recursion::RecursionGuard,
+    strategy::ExampleStrategy,
+};
 use crate::transformer::Transformer;
 use anyhow::anyhow;
 use proc_macro2::{TokenStream, TokenTree};
 use quote::{format_ident, quote};
-use rand::{seq::SliceRandom, SeedableRng};
+use rand::SeedableRng;
 use scale_info::{form::PortableForm, Field, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
 use scale_typegen::{typegen::ir::ToTokensWithSettings, TypeGenerator, TypeGeneratorSettings};
-use std::cell::RefCell;
+use scale_value::Primitive;
+use std::{cell::RefCell, rc::Rc};
 
 /// A transformer capable of converting a [`scale_info::Type`] from a type registry into
 /// a rust expression.
@@ -21,6 +27,11 @@ pub struct CodeTransformerState<'a> {
     ty_middleware: Option<TyMiddleware>,
     /// `ty_path_middleware` can be used to convert a type path like `::std::vec::Vec<T>` into just `Vec<T>`.
     ty_path_middleware: Option<TyPathMiddleware>,
+    /// If set, the cases of each type are cycled through instead of being chosen randomly.
+    coverage: Option<RefCell<Coverage>>,
+    strategy: ExampleStrategy,
+    /// Set if recursive types are allowed up to the `max_recursion_depth` of the strategy.
+    recursion: Option<RecursionGuard>,
 }
 
 ///  Middleware for a `CodeTransformer` to return a different type when a certain type is encountered.
@@ -34,7 +45,7 @@ impl CodeTransformer<'_> {
     /// resolves a type path, removes the generic bits, e.g. `Foo<T, R>` becomes `Foo`,
     /// and, if the correct ty_path_middleware is set, prunes the resulting type path.
     fn resolve_type_path_omit_generics(&self, type_id: u32) -> anyhow::Result<TokenStream> {
-        let gen = self.state().type_generator;
+        let gen = &self.state().type_generator;
         let mut type_path = gen
             .resolve_type_path(type_id)
             .map_err(|e| anyhow!("{e}"))?
@@ -70,6 +81,15 @@ impl CodeTransformer<'_> {
         Ok(has_unused_type_params)
     }
 
+    /// True if the type that is currently generated is below the `max_recursion_depth`,
+    /// and its example should be as small as possible.
+    fn must_terminate(&self) -> bool {
+        self.state()
+            .recursion
+            .as_ref()
+            .is_some_and(|recursion| recursion.must_terminate())
+    }
+
     fn resolve_type(&self, type_id: u32) -> anyhow::Result<&Type<PortableForm>> {
         self.state()
             .type_generator
@@ -126,11 +146,91 @@ pub fn example_from_seed(
     ty_middleware: Option<TyMiddleware>,
     ty_path_middleware: Option<TyPathMiddleware>,
 ) -> anyhow::Result<TokenStream> {
+    example_with_strategy(
+        type_id,
+        types,
+        settings_for_path_resolver,
+        seed,
+        ExampleStrategy::default(),
+        ty_middleware,
+        ty_path_middleware,
+    )
+}
+
+/// Generates a random rust value for a type from the registry, like [`example_from_seed`],
+/// with the values chosen according to the `strategy`.
+pub fn example_with_strategy(
+    type_id: u32,
+    types: &PortableRegistry,
+    settings_for_path_resolver: &TypeGeneratorSettings,
+    seed: u64,
+    strategy: ExampleStrategy,
+    ty_middleware: Option<TyMiddleware>,
+    ty_path_middleware: Option<TyPathMiddleware>,
+) -> anyhow::Result<TokenStream> {
+    let state = CodeTransformerState {
+        rng: RefCell::new(rand_chacha::ChaCha8Rng::seed_from_u64(seed)),
+        type_generator: TypeGenerator::new(types, settings_for_path_resolver),
+        ty_middleware,
+        ty_path_middleware,
+        coverage: None,
+        recursion: strategy
+            .max_recursion_depth
+            .map(|max_recursion_depth| RecursionGuard::new(max_recursion_depth, types)),
+        strategy,
+    };
+    code_transformer(state, types).resolve(type_id)
+}
+
+/// Generates rust values for a type from the registry that together cover every variant of each enum,
+/// empty, single and multi element sequences and the boundary values of primitives (zero, min, max, empty string).
+///
+/// The iterator stops once every case of all types encountered has been generated, or after `budget` examples.
+/// The middlewares are applied like in [`example_from_seed`]. Recursive types yield an error.
+pub fn exhaustive_examples<'a>(
+    type_id: u32,
+    types: &'a PortableRegistry,
+    settings_for_path_resolver: &'a TypeGeneratorSettings,
+    budget: usize,
+    ty_middleware: Option<TyMiddleware>,
+    ty_path_middleware: Option<TyPathMiddleware>,
+) -> impl Iterator<Item = anyhow::Result<TokenStream>> + 'a {
+    let state = CodeTransformerState {
+        rng: RefCell::new(rand_chacha::ChaCha8Rng::seed_from_u64(0)),
+        type_generator: TypeGenerator::new(types, settings_for_path_resolver),
+        ty_middleware,
+        ty_path_middleware,
+        coverage: Some(RefCell::new(Coverage::default())),
+        strategy: ExampleStrategy::default(),
+        recursion: None,
+    };
+    let transformer = Rc::new(code_transformer(state, types));
+    let is_complete = {
+        let transformer = transformer.clone();
+        move || {
+            transformer
+                .state()
+                .coverage
+                .as_ref()
+                .is_some_and(|coverage| coverage.borrow().is_complete())
+        }
+    };
+    examples_until_complete(budget, move || transformer.resolve(type_id), is_complete)
+}
+
+fn code_transformer<'a>(
+    state: CodeTransformerState<'a>,
+    types: &'a PortableRegistry,
+) -> CodeTransformer<'a> {
+    /// Note: with a recursion guard, the transformer continues, because the guard ensures termination.
     fn error_on_recurse(
         _type_id: u32,
         ty: &Type<PortableForm>,
-        _transformer: &CodeTransformer,
+        transformer: &CodeTransformer,
     ) -> Option<anyhow::Result<TokenStream>> {
+        if transformer.state().recursion.is_some() {
+            return None;
+        }
         Some(Err(anyhow!(
             "Cannot generate rust type example for recursive type: {ty:?}"
         )))
@@ -146,21 +246,13 @@ pub fn example_from_seed(
         None
     }
 
-    let state = CodeTransformerState {
-        rng: RefCell::new(rand_chacha::ChaCha8Rng::seed_from_u64(seed)),
-        type_generator: TypeGenerator::new(types, settings_for_path_resolver),
-        ty_middleware,
-        ty_path_middleware,
-    };
-
-    let transformer = CodeTransformer::new(
+    CodeTransformer::new(
         ty_example,
         error_on_recurse,
         compute_another_example,
         state,
         types,
-    );
-    transformer.resolve(type_id)
+    )
 }
 
 fn ty_example(
@@ -175,6 +267,20 @@ fn ty_example(
         }
     }
 
+    let Some(recursion) = &transformer.state().recursion else {
+        return ty_def_example(type_id, ty, transformer);
+    };
+    recursion.enter(type_id)?;
+    let example = ty_def_example(type_id, ty, transformer);
+    recursion.exit();
+    example
+}
+
+fn ty_def_example(
+    type_id: u32,
+    ty: &Type<PortableForm>,
+    transformer: &CodeTransformer,
+) -> anyhow::Result<TokenStream> {
     //  general handling of type definitions
     match &ty.type_def {
         scale_info::TypeDef::Composite(composite) => {
@@ -187,10 +293,26 @@ fn ty_example(
         }
         scale_info::TypeDef::Variant(variant) => {
             let enum_path = transformer.resolve_type_path_omit_generics(type_id)?;
-            let random_variant = variant
-                .variants
-                .choose(&mut *transformer.state().rng.borrow_mut())
-                .ok_or_else(|| anyhow!("Variant type should have at least one variant"))?;
+            let state = transformer.state();
+            let random_variant = match &state.coverage {
+                _ if transformer.must_terminate() => state
+                    .recursion
+                    .as_ref()
+                    .and_then(|recursion| recursion.terminating_variant(type_id, &variant.variants))
+                    .ok_or_else(|| anyhow!("Variant type should have at least one variant"))?,
+                Some(coverage) => {
+                    let case = coverage
+                        .borrow_mut()
+                        .next_case(type_id, variant.variants.len());
+                    variant
+                        .variants
+                        .get(case)
+                        .ok_or_else(|| anyhow!("Variant type should have at least one variant"))?
+                }
+                None => state
+                    .strategy
+                    .choose_variant(&variant.variants, &mut *state.rng.borrow_mut())?,
+            };
             let variant_ident = format_ident!("{}", &random_variant.name);
             // Never needs phantom data, because phantom data is generated as a separate variant.
             let fields = fields_example(&random_variant.fields, false, transformer)?;
@@ -203,11 +325,24 @@ fn ty_example(
             Ok(example)
         }
         scale_info::TypeDef::Sequence(def) => {
-            // Return a Vec with 2 random example elements:
+            let state = transformer.state();
+            let len = match &state.coverage {
+                _ if transformer.must_terminate() => 0,
+                Some(coverage) => {
+                    let case = coverage
+                        .borrow_mut()
+                        .next_case(type_id, SEQUENCE_LENGTHS.len());
+                    SEQUENCE_LENGTHS[case]
+                }
+                None => state
+                    .strategy
+                    .sequence_len_sample(&mut *state.rng.borrow_mut()),
+            };
             let inner_ty = transformer.resolve_type(def.type_param.id)?;
-            let item1 = ty_example(def.type_param.id, inner_ty, transformer)?;
-            let item2 = ty_example(def.type_param.id, inner_ty, transformer)?;
-            let vec_code = quote!(vec![#item1, #item2]);
+            let items = (0..len)
+                .map(|_| ty_example(def.type_param.id, inner_ty, transformer))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let vec_code = quote!(vec![#(#items),*]);
             Ok(vec_code)
         }
         scale_info::TypeDef::Array(def) => {
@@ -233,18 +368,37 @@ fn ty_example(
             }
             Ok(quote!(( #(#fields),* )))
         }
-        scale_info::TypeDef::Primitive(def) => Ok(primitive_example(
-            def,
-            &mut *transformer.state().rng.borrow_mut(),
-        )),
+        scale_info::TypeDef::Primitive(def) => match &transformer.state().coverage {
+            Some(coverage) => {
+                let mut boundaries = boundary_primitives(def);
+                let case = coverage.borrow_mut().next_case(type_id, boundaries.len());
+                primitive_value_example(def, &boundaries.swap_remove(case))
+            }
+            None => {
+                let state = transformer.state();
+                let primitive = state
+                    .strategy
+                    .primitive(def, &mut *state.rng.borrow_mut())?;
+                primitive_value_example(def, &primitive)
+            }
+        },
         scale_info::TypeDef::Compact(def) => {
             let code = transformer.resolve(def.type_param.id)?;
             Ok(code)
         }
         scale_info::TypeDef::BitSequence(_def) => {
-            Ok(quote!(subxt::utils::bits::DecodedBits::from_iter([
-                true, false, false
-            ])))
+            let state = transformer.state();
+            let bits: Vec<bool> = match &state.coverage {
+                Some(coverage) => {
+                    let case = coverage
+                        .borrow_mut()
+                        .next_case(type_id, SEQUENCE_LENGTHS.len());
+                    (0..SEQUENCE_LENGTHS[case]).map(|i| i % 2 == 0).collect()
+                }
+                None => state.strategy.bits(&mut *state.rng.borrow_mut()),
+            };
+            let bit_sequence_path = &state.strategy.bit_sequence_path;
+            Ok(quote!(#bit_sequence_path::from_iter([#(#bits),*])))
         }
     }
 }
@@ -265,6 +419,26 @@ fn fields_example(
             .unwrap_or(false)
     }
 
+    /// Field is a `Box<T>` in the generated code, see `CompositeFieldIR::is_boxed` in `scale_typegen`.
+    fn field_is_boxed(f: &Field<PortableForm>) -> bool {
+        f.type_name
+            .as_ref()
+            .map(|e| e.contains("Box<"))
+            .unwrap_or_default()
+    }
+
+    let compact_path = &transformer.state().strategy.compact_path;
+    let field_value = |f: &Field<PortableForm>| -> anyhow::Result<TokenStream> {
+        let mut value_code = transformer.resolve(f.ty.id)?;
+        if field_is_explicit_compact(f) {
+            value_code = quote!(#compact_path(#value_code))
+        }
+        if field_is_boxed(f) {
+            value_code = quote!(Box::new(#value_code))
+        }
+        Ok(value_code)
+    };
+
     match (all_named, all_unnamed) {
         (true, false) => {
             // all fields named
@@ -272,10 +446,7 @@ fn fields_example(
             for f in fields {
                 let name = f.name.as_ref().expect("safe because of check above; qed");
                 let ident = format_ident!("{name}");
-                let mut value_code = transformer.resolve(f.ty.id)?;
-                if field_is_explicit_compact(f) {
-                    value_code = quote!(Compact(#value_code))
-                }
+                let value_code = field_value(f)?;
                 field_idents_and_values.push(quote!(#ident : #value_code));
             }
             // maybe add phantom data to struct / named composite enum
@@ -290,11 +461,7 @@ fn fields_example(
             // all fields unnamed
             let mut field_values: Vec<TokenStream> = vec![];
             for f in fields {
-                let mut value_code = transformer.resolve(f.ty.id)?;
-                if field_is_explicit_compact(f) {
-                    value_code = quote!(Compact(#value_code))
-                }
-                field_values.push(value_code);
+                field_values.push(field_value(f)?);
             }
             // maybe add phantom data to struct / named composite enum
             let maybe_phantom = if needs_phantom_data {
@@ -315,67 +482,55 @@ fn fields_example(
     }
 }
 
-fn primitive_example(def: &TypeDefPrimitive, rng: &mut impl rand::Rng) -> TokenStream {
-    match def {
-        TypeDefPrimitive::Bool => {
-            let b: bool = rng.gen();
-            quote!(#b)
-        }
-        TypeDefPrimitive::Char => {
-            let c = *['a', 'b', 'c', 'd', 'e', 'f', 'g'].choose(rng).unwrap();
-            quote!(#c)
-        }
-        TypeDefPrimitive::Str => {
-            let str = *["Foo", "Bar", "Fizz", "Buzz"].choose(rng).unwrap();
-            quote!(#str.into())
-        }
-        TypeDefPrimitive::U8 => {
-            let n = rng.gen::<u8>();
-            quote!(#n)
-        }
-        TypeDefPrimitive::U16 => {
-            let _n = rng.gen::<u16>();
-            quote!(n)
-        }
-        TypeDefPrimitive::U32 => {
-            let n = rng.gen::<u32>();
+/// Converts a scale value primitive into a rust expression of the primitive type `def`.
+fn primitive_value_example(
+    def: &TypeDefPrimitive,
+    primitive: &Primitive,
+) -> anyhow::Result<TokenStream> {
+    let code = match (def, primitive) {
+        (TypeDefPrimitive::Bool, Primitive::Bool(b)) => quote!(#b),
+        (TypeDefPrimitive::Char, Primitive::Char(c)) => quote!(#c),
+        (TypeDefPrimitive::Str, Primitive::String(str)) => quote!(#str.into()),
+        (TypeDefPrimitive::U8, Primitive::U128(n)) => {
+            let n = u8::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::U64 => {
-            let n = rng.gen::<u64>();
+        (TypeDefPrimitive::U16, Primitive::U128(n)) => {
+            let n = u16::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::U128 => {
-            let n = rng.gen::<u128>();
+        (TypeDefPrimitive::U32, Primitive::U128(n)) => {
+            let n = u32::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::U256 => {
-            let n = rng.gen::<[u8; 32]>().into_iter();
-            quote!([#(#n),*])
-        }
-        TypeDefPrimitive::I8 => {
-            let n = rng.gen::<i8>();
+        (TypeDefPrimitive::U64, Primitive::U128(n)) => {
+            let n = u64::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::I16 => {
-            let n = rng.gen::<i16>();
+        (TypeDefPrimitive::U128, Primitive::U128(n)) => quote!(#n),
+        (TypeDefPrimitive::I8, Primitive::I128(n)) => {
+            let n = i8::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::I32 => {
-            let n = rng.gen::<i32>();
+        (TypeDefPrimitive::I16, Primitive::I128(n)) => {
+            let n = i16::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::I64 => {
-            let n = rng.gen::<i64>();
+        (TypeDefPrimitive::I32, Primitive::I128(n)) => {
+            let n = i32::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::I128 => {
-            let n = rng.gen::<i128>();
+        (TypeDefPrimitive::I64, Primitive::I128(n)) => {
+            let n = i64::try_from(*n)?;
             quote!(#n)
         }
-        TypeDefPrimitive::I256 => {
-            let n = rng.gen::<[u8; 32]>().into_iter();
+        (TypeDefPrimitive::I128, Primitive::I128(n)) => quote!(#n),
+        (TypeDefPrimitive::U256, Primitive::U256(bytes))
+        | (TypeDefPrimitive::I256, Primitive::I256(bytes)) => {
+            let n = bytes.iter();
             quote!([#(#n),*])
         }
-    }
+        _ => return Err(anyhow!("{primitive:?} is not a value of {def:?}")),
+    };
+    Ok(code)
 }
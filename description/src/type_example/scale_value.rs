@@ -1,15 +1,143 @@
-use std::cell::RefCell;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use anyhow::anyhow;
 use rand::SeedableRng;
-use rand::{seq::SliceRandom, Rng};
 
-use scale_info::{form::PortableForm, PortableRegistry, Type, TypeDef, TypeDefPrimitive};
-use scale_value::{BitSequence, Composite, Primitive, Value, ValueDef, Variant};
+use scale_info::{form::PortableForm, PortableRegistry, Type, TypeDef};
+use scale_value::{BitSequence, Composite, Value, ValueDef, Variant};
 
 use crate::transformer::Transformer;
 
-type ValueTransformer<'a> = Transformer<'a, Value, RefCell<rand_chacha::ChaCha8Rng>>;
+use super::{
+    exhaustive::{boundary_primitives, examples_until_complete, Coverage, SEQUENCE_LENGTHS},
+    recursion::RecursionGuard,
+    strategy::ExampleStrategy,
+};
+
+type ValueTransformer<'a> = Transformer<'a, Value, ValueExampleState>;
+
+struct ValueExampleState {
+    rng: RefCell<rand_chacha::ChaCha8Rng>,
+    /// If set, the cases of each type are cycled through instead of being chosen randomly.
+    coverage: Option<RefCell<Coverage>>,
+    options: ExampleOptions,
+    /// The nesting depth of the type that is currently generated. The outermost type has depth 1.
+    depth: Cell<usize>,
+    /// The number of bytes left until the `target_encoded_size` is reached.
+    remaining_size: Cell<usize>,
+    middlewares: Vec<ValueMiddleware>,
+    /// The type name of the field whose value is resolved next, handed to the middlewares.
+    field_type_name: RefCell<Option<String>>,
+    /// Set if recursive types are allowed up to the `max_recursion_depth` of the strategy.
+    recursion: Option<RecursionGuard>,
+}
+
+impl ValueExampleState {
+    fn new(
+        seed: u64,
+        coverage: Option<Coverage>,
+        options: ExampleOptions,
+        middlewares: Vec<ValueMiddleware>,
+        types: &PortableRegistry,
+    ) -> Self {
+        ValueExampleState {
+            recursion: options
+                .strategy
+                .max_recursion_depth
+                .map(|max_recursion_depth| RecursionGuard::new(max_recursion_depth, types)),
+            rng: RefCell::new(rand_chacha::ChaCha8Rng::seed_from_u64(seed)),
+            coverage: coverage.map(RefCell::new),
+            remaining_size: Cell::new(options.target_encoded_size.unwrap_or(0)),
+            options,
+            depth: Cell::new(0),
+            middlewares,
+            field_type_name: RefCell::new(None),
+        }
+    }
+}
+
+/// Middleware for scale value examples to return a different value when a certain type is encountered.
+/// For example, a middleware could return the account id of a well known dev account instead of random bytes.
+pub type ValueMiddleware = Box<dyn Fn(&MiddlewareContext) -> Option<anyhow::Result<Value>>>;
+
+/// The type a [`ValueMiddleware`] is asked for a value of.
+#[derive(Debug, Clone, Copy)]
+pub struct MiddlewareContext<'a> {
+    /// The id of the type in the registry.
+    pub type_id: u32,
+    /// The type.
+    pub ty: &'a Type<PortableForm>,
+    /// The type name of the field the value is for, as written in the source, e.g. `T::Balance`.
+    /// None if the value is not for a field, e.g. for elements of sequences.
+    pub field_type_name: Option<&'a str>,
+    /// The registry the type is from.
+    pub types: &'a PortableRegistry,
+}
+
+/// Creates a middleware that returns a value for all types with the given path, e.g. `sp_core::crypto::AccountId32`.
+pub fn path_middleware(path: impl Into<String>, value: Value) -> ValueMiddleware {
+    let path = path.into();
+    Box::new(move |cx| (cx.ty.path.to_string() == path).then(|| Ok(value.clone())))
+}
+
+/// Creates a middleware that returns values for all types that satisfy the `predicate`,
+/// e.g. fields with a type name ending in `Balance`.
+pub fn predicate_middleware(
+    predicate: impl Fn(&MiddlewareContext) -> bool + 'static,
+    value: impl Fn(&MiddlewareContext) -> anyhow::Result<Value> + 'static,
+) -> ValueMiddleware {
+    Box::new(move |cx| predicate(cx).then(|| value(cx)))
+}
+
+/// Options to constrain the size and configure the values of generated scale value examples, see [`example_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct ExampleOptions {
+    /// The maximum number of elements in sequences and bit sequences.
+    pub max_sequence_len: Option<usize>,
+    /// The maximum nesting depth, where the outermost type has depth 1. Below it, sequences are empty
+    /// and the variant with the fewest fields is chosen for enums. Structs and tuples are still generated in full.
+    pub max_depth: Option<usize>,
+    /// The approximate size of the SCALE encoded example in bytes. Sequences are filled with elements
+    /// until the target is reached, so the example can exceed it by the size of one element and of the
+    /// fields after the sequence.
+    pub target_encoded_size: Option<usize>,
+    /// The values chosen for primitives, sequence lengths and enum variants.
+    pub strategy: ExampleStrategy,
+}
+
+impl ExampleOptions {
+    /// Creates new options without any constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `max_sequence_len` field.
+    pub fn max_sequence_len(mut self, max_sequence_len: usize) -> Self {
+        self.max_sequence_len = Some(max_sequence_len);
+        self
+    }
+
+    /// Sets the `max_depth` field.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the `target_encoded_size` field.
+    pub fn target_encoded_size(mut self, target_encoded_size: usize) -> Self {
+        self.target_encoded_size = Some(target_encoded_size);
+        self
+    }
+
+    /// Sets the `strategy` field.
+    pub fn strategy(mut self, strategy: ExampleStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+}
 
 /// Generates a random scale value for a type from the registry.
 pub fn example(id: u32, types: &PortableRegistry) -> anyhow::Result<Value> {
@@ -19,11 +147,81 @@ pub fn example(id: u32, types: &PortableRegistry) -> anyhow::Result<Value> {
 
 /// Generates a random scale value for a type from the registry. You can specify the seed to get reproducable results.
 pub fn example_from_seed(id: u32, types: &PortableRegistry, seed: u64) -> anyhow::Result<Value> {
+    example_with_options(id, types, seed, ExampleOptions::default())
+}
+
+/// Generates a random scale value for a type from the registry, with its size constrained by the `options`.
+/// Use [`encoded_size`] to check how close the example is to the `target_encoded_size`.
+pub fn example_with_options(
+    id: u32,
+    types: &PortableRegistry,
+    seed: u64,
+    options: ExampleOptions,
+) -> anyhow::Result<Value> {
+    example_with_middlewares(id, types, seed, options, vec![])
+}
+
+/// Generates a random scale value for a type from the registry, like [`example_with_options`].
+/// For each type, the `middlewares` are asked for a value first, and the first value returned is used.
+pub fn example_with_middlewares(
+    id: u32,
+    types: &PortableRegistry,
+    seed: u64,
+    options: ExampleOptions,
+    middlewares: Vec<ValueMiddleware>,
+) -> anyhow::Result<Value> {
+    let state = ValueExampleState::new(seed, None, options, middlewares, types);
+    value_transformer(state, types).resolve(id)
+}
+
+/// The size in bytes of the SCALE encoding of a value as the type with the given id.
+pub fn encoded_size(value: &Value, id: u32, types: &PortableRegistry) -> anyhow::Result<usize> {
+    let mut bytes = vec![];
+    scale_value::scale::encode_as_type(value, id, types, &mut bytes)?;
+    Ok(bytes.len())
+}
+
+/// Generates scale values for a type from the registry that together cover every variant of each enum,
+/// empty, single and multi element sequences and the boundary values of primitives (zero, min, max, empty string).
+///
+/// The iterator stops once every case of all types encountered has been generated, or after `budget` examples.
+/// Recursive types yield an error, like in [`example`].
+pub fn exhaustive_examples(
+    id: u32,
+    types: &PortableRegistry,
+    budget: usize,
+) -> impl Iterator<Item = anyhow::Result<Value>> + '_ {
+    let state = ValueExampleState::new(
+        0,
+        Some(Coverage::default()),
+        ExampleOptions::default(),
+        vec![],
+        types,
+    );
+    let transformer = Rc::new(value_transformer(state, types));
+    let is_complete = {
+        let transformer = transformer.clone();
+        move || {
+            transformer
+                .state()
+                .coverage
+                .as_ref()
+                .is_some_and(|coverage| coverage.borrow().is_complete())
+        }
+    };
+    examples_until_complete(budget, move || transformer.resolve(id), is_complete)
+}
+
+fn value_transformer(state: ValueExampleState, types: &PortableRegistry) -> ValueTransformer<'_> {
+    /// Note: with a recursion guard, the transformer continues, because the guard ensures termination.
     fn error_on_recurse(
         _type_id: u32,
         ty: &Type<PortableForm>,
-        _transformer: &ValueTransformer,
+        transformer: &ValueTransformer,
     ) -> Option<anyhow::Result<Value>> {
+        if transformer.state().recursion.is_some() {
+            return None;
+        }
         Some(Err(anyhow!(
             "Cannot generate scale value example for recursive type: {ty:?}"
         )))
@@ -39,25 +237,143 @@ pub fn example_from_seed(id: u32, types: &PortableRegistry, seed: u64) -> anyhow
         None
     }
 
-    let state = RefCell::new(rand_chacha::ChaCha8Rng::seed_from_u64(seed));
-    let transformer = ValueTransformer::new(
+    ValueTransformer::new(
         ty_example,
         error_on_recurse,
         compute_another_example,
         state,
         types,
-    );
-    transformer.resolve(id)
+    )
+}
+
+impl ValueTransformer<'_> {
+    /// True if the type that is currently generated is below the `max_depth`
+    /// or the `max_recursion_depth`, and its example should be as small as possible.
+    fn must_terminate(&self) -> bool {
+        let state = self.state();
+        let is_too_deep = state
+            .options
+            .max_depth
+            .is_some_and(|max_depth| state.depth.get() > max_depth);
+        is_too_deep
+            || state
+                .recursion
+                .as_ref()
+                .is_some_and(|recursion| recursion.must_terminate())
+    }
+
+    /// The variant with the fewest fields, that also leads to a finite example if recursion is allowed.
+    fn terminating_variant<'a>(
+        &self,
+        type_id: u32,
+        variants: &'a [scale_info::Variant<PortableForm>],
+    ) -> Option<&'a scale_info::Variant<PortableForm>> {
+        match &self.state().recursion {
+            Some(recursion) => recursion.terminating_variant(type_id, variants),
+            None => variants.iter().min_by_key(|variant| variant.fields.len()),
+        }
+    }
+
+    /// The maximum number of elements for a sequence of the type that is currently generated.
+    fn max_sequence_len(&self) -> usize {
+        if self.must_terminate() {
+            return 0;
+        }
+        self.state().options.max_sequence_len.unwrap_or(usize::MAX)
+    }
+
+    /// Counts the bytes towards the `target_encoded_size`.
+    fn consume_size(&self, size: usize) {
+        let remaining = &self.state().remaining_size;
+        remaining.set(remaining.get().saturating_sub(size));
+    }
+
+    /// Counts the encoded size of a value without nested types, like a primitive, towards the `target_encoded_size`.
+    fn consume_encoded_size(&self, value: &Value, type_id: u32) -> anyhow::Result<()> {
+        if self.state().options.target_encoded_size.is_some() {
+            self.consume_size(encoded_size(value, type_id, self.types())?);
+        }
+        Ok(())
+    }
+
+    /// Resolves the value of a field, making its type name available to the middlewares.
+    fn resolve_field(&self, type_id: u32, type_name: Option<&str>) -> anyhow::Result<Value> {
+        *self.state().field_type_name.borrow_mut() = type_name.map(Into::into);
+        self.resolve(type_id)
+    }
+
+    /// Returns the value of the first middleware that has one for the type.
+    fn middleware_example(
+        &self,
+        type_id: u32,
+        ty: &Type<PortableForm>,
+        field_type_name: Option<&str>,
+    ) -> Option<anyhow::Result<Value>> {
+        let cx = MiddlewareContext {
+            type_id,
+            ty,
+            field_type_name,
+            types: self.types(),
+        };
+        self.state()
+            .middlewares
+            .iter()
+            .find_map(|middleware| middleware(&cx))
+    }
+
+    /// Resolves elements until the `target_encoded_size` or `max_len` is reached.
+    fn fill_sequence(&self, type_id: u32, max_len: usize) -> anyhow::Result<Vec<Value>> {
+        let remaining = &self.state().remaining_size;
+        let mut elements = vec![];
+        while elements.len() < max_len && remaining.get() > 0 {
+            let before = remaining.get();
+            elements.push(self.resolve(type_id)?);
+            // Elements without any encoded bytes, like `()`, would never reach the target.
+            if remaining.get() == before {
+                break;
+            }
+        }
+        Ok(elements)
+    }
 }
 
 fn ty_example(
-    _type_id: u32,
+    type_id: u32,
+    ty: &Type<PortableForm>,
+    transformer: &ValueTransformer,
+) -> anyhow::Result<Value> {
+    // The type name only belongs to the field itself, not to any types nested in it.
+    let field_type_name = transformer.state().field_type_name.take();
+    if let Some(value) = transformer.middleware_example(type_id, ty, field_type_name.as_deref()) {
+        let value = value?;
+        transformer.consume_encoded_size(&value, type_id)?;
+        return Ok(value);
+    }
+
+    let state = transformer.state();
+    if let Some(recursion) = &state.recursion {
+        recursion.enter(type_id)?;
+    }
+    state.depth.set(state.depth.get() + 1);
+    let example = ty_def_example(type_id, ty, transformer);
+    state.depth.set(state.depth.get() - 1);
+    if let Some(recursion) = &state.recursion {
+        recursion.exit();
+    }
+    example
+}
+
+fn ty_def_example(
+    type_id: u32,
     ty: &Type<PortableForm>,
     transformer: &ValueTransformer,
 ) -> anyhow::Result<Value> {
     match &ty.type_def {
         TypeDef::Composite(composite) => {
-            let fields = composite.fields.iter().map(|e| (e.name.as_ref(), e.ty.id));
+            let fields = composite
+                .fields
+                .iter()
+                .map(|e| (e.name.as_ref(), e.ty.id, e.type_name.as_deref()));
             let composite = fields_type_example(fields, transformer)?;
             Ok(Value {
                 value: ValueDef::Composite(composite),
@@ -65,14 +381,29 @@ fn ty_example(
             })
         }
         TypeDef::Variant(variant) => {
-            let random_variant = variant
-                .variants
-                .choose(&mut *transformer.state().borrow_mut())
-                .ok_or_else(|| anyhow!("Variant type should have at least one variant"))?;
+            let no_variants = || anyhow!("Variant type should have at least one variant");
+            let state = transformer.state();
+            let random_variant = match &state.coverage {
+                _ if transformer.must_terminate() => transformer
+                    .terminating_variant(type_id, &variant.variants)
+                    .ok_or_else(no_variants)?,
+                Some(coverage) => {
+                    let case = coverage
+                        .borrow_mut()
+                        .next_case(type_id, variant.variants.len());
+                    variant.variants.get(case).ok_or_else(no_variants)?
+                }
+                None => state
+                    .options
+                    .strategy
+                    .choose_variant(&variant.variants, &mut *state.rng.borrow_mut())?,
+            };
+            // the variant index
+            transformer.consume_size(1);
             let fields = random_variant
                 .fields
                 .iter()
-                .map(|e| (e.name.as_ref(), e.ty.id));
+                .map(|e| (e.name.as_ref(), e.ty.id, e.type_name.as_deref()));
             let composite = fields_type_example(fields, transformer)?;
             Ok(Value {
                 value: ValueDef::Variant(Variant {
@@ -83,10 +414,32 @@ fn ty_example(
             })
         }
         TypeDef::Sequence(sequence) => {
-            // sequence with 2 elements should be enough:
-            let example1 = transformer.resolve(sequence.type_param.id)?;
-            let example2 = transformer.resolve(sequence.type_param.id)?;
-            Ok(Value::unnamed_composite([example1, example2]))
+            let state = transformer.state();
+            let max_len = transformer.max_sequence_len();
+            let elements = match &state.coverage {
+                None if state.options.target_encoded_size.is_some() => {
+                    transformer.fill_sequence(sequence.type_param.id, max_len)?
+                }
+                coverage => {
+                    let len = match coverage {
+                        Some(coverage) => {
+                            let case = coverage
+                                .borrow_mut()
+                                .next_case(type_id, SEQUENCE_LENGTHS.len());
+                            SEQUENCE_LENGTHS[case]
+                        }
+                        None => state
+                            .options
+                            .strategy
+                            .sequence_len_sample(&mut *state.rng.borrow_mut()),
+                    };
+                    (0..len.min(max_len))
+                        .map(|_| transformer.resolve(sequence.type_param.id))
+                        .collect::<anyhow::Result<Vec<_>>>()?
+                }
+            };
+            transformer.consume_size(compact_len_size(elements.len()));
+            Ok(Value::unnamed_composite(elements))
         }
         TypeDef::Array(array) => {
             let elements: Vec<_> = (0..array.len)
@@ -95,56 +448,71 @@ fn ty_example(
             Ok(Value::unnamed_composite(elements))
         }
         TypeDef::Tuple(tuple) => {
-            let fields = tuple.fields.iter().map(|e| (None::<&str>, e.id));
+            let fields = tuple.fields.iter().map(|e| (None::<&str>, e.id, None));
             let composite = fields_type_example(fields, transformer)?;
             Ok(Value {
                 value: ValueDef::Composite(composite),
                 context: (),
             })
         }
-        TypeDef::Primitive(primitive) => Ok(primitive_type_def_example(
-            primitive,
-            &mut *transformer.state().borrow_mut(),
-        )),
+        TypeDef::Primitive(primitive) => match &transformer.state().coverage {
+            Some(coverage) => {
+                let mut boundaries = boundary_primitives(primitive);
+                let case = coverage.borrow_mut().next_case(type_id, boundaries.len());
+                Ok(Value::primitive(boundaries.swap_remove(case)))
+            }
+            None => {
+                let state = transformer.state();
+                let value = Value::primitive(
+                    state
+                        .options
+                        .strategy
+                        .primitive(primitive, &mut *state.rng.borrow_mut())?,
+                );
+                transformer.consume_encoded_size(&value, type_id)?;
+                Ok(value)
+            }
+        },
         TypeDef::Compact(compact) => transformer.resolve(compact.type_param.id),
         TypeDef::BitSequence(_) => {
             let mut bit_sequence = BitSequence::new();
-            let rng = &mut *transformer.state().borrow_mut();
-            for _ in 0..rng.gen_range(3..7) {
-                bit_sequence.push(rng.gen());
+            match &transformer.state().coverage {
+                Some(coverage) => {
+                    let case = coverage
+                        .borrow_mut()
+                        .next_case(type_id, SEQUENCE_LENGTHS.len());
+                    for i in 0..SEQUENCE_LENGTHS[case] {
+                        bit_sequence.push(i % 2 == 0);
+                    }
+                }
+                None => {
+                    let state = transformer.state();
+                    let bits = state.options.strategy.bits(&mut *state.rng.borrow_mut());
+                    let len = bits.len().min(transformer.max_sequence_len());
+                    for bit in bits.into_iter().take(len) {
+                        bit_sequence.push(bit);
+                    }
+                }
             }
-            Ok(Value::bit_sequence(bit_sequence))
+            let value = Value::bit_sequence(bit_sequence);
+            transformer.consume_encoded_size(&value, type_id)?;
+            Ok(value)
         }
     }
 }
 
-fn primitive_type_def_example(primitive: &TypeDefPrimitive, rng: &mut impl rand::Rng) -> Value {
-    let primitive: Primitive = match primitive {
-        TypeDefPrimitive::Bool => Primitive::Bool(rng.gen()),
-        TypeDefPrimitive::Char => {
-            Primitive::Char(*['a', 'b', 'c', 'd', 'e', 'f', 'g'].choose(rng).unwrap())
-        }
-        TypeDefPrimitive::Str => {
-            Primitive::String((*["Foo", "Bar", "Fizz", "Buzz"].choose(rng).unwrap()).into())
-        }
-        TypeDefPrimitive::U8 => Primitive::U128(rng.gen::<u8>() as u128),
-        TypeDefPrimitive::U16 => Primitive::U128(rng.gen::<u16>() as u128),
-        TypeDefPrimitive::U32 => Primitive::U128(rng.gen::<u32>() as u128),
-        TypeDefPrimitive::U64 => Primitive::U128(rng.gen::<u64>() as u128),
-        TypeDefPrimitive::U128 => Primitive::U128(rng.gen()),
-        TypeDefPrimitive::U256 => Primitive::U256(rng.gen()),
-        TypeDefPrimitive::I8 => Primitive::I128(rng.gen::<i8>() as i128),
-        TypeDefPrimitive::I16 => Primitive::I128(rng.gen::<i16>() as i128),
-        TypeDefPrimitive::I32 => Primitive::I128(rng.gen::<i32>() as i128),
-        TypeDefPrimitive::I64 => Primitive::I128(rng.gen::<i64>() as i128),
-        TypeDefPrimitive::I128 => Primitive::I128(rng.gen()),
-        TypeDefPrimitive::I256 => Primitive::I256(rng.gen()),
-    };
-    Value::primitive(primitive)
+/// The size of the compact encoded length prefix of a sequence.
+fn compact_len_size(len: usize) -> usize {
+    match len {
+        0..=0x3f => 1,
+        0x40..=0x3fff => 2,
+        0x4000..=0x3fff_ffff => 4,
+        _ => 5,
+    }
 }
 
-fn fields_type_example(
-    fields: impl Iterator<Item = (Option<impl AsRef<str>>, u32)> + Clone,
+fn fields_type_example<'a>(
+    fields: impl Iterator<Item = (Option<impl AsRef<str>>, u32, Option<&'a str>)> + Clone,
     transformer: &ValueTransformer,
 ) -> anyhow::Result<Composite<()>> {
     let all_fields_named = fields.clone().all(|e| e.0.is_some());
@@ -153,8 +521,8 @@ fn fields_type_example(
         (true, true) => Ok(Composite::Unnamed(vec![])),
         (true, false) => {
             let mut elements: Vec<(String, Value)> = vec![];
-            for (name, id) in fields {
-                let field_value = transformer.resolve(id)?;
+            for (name, id, type_name) in fields {
+                let field_value = transformer.resolve_field(id, type_name)?;
                 let name = name.unwrap().as_ref().into();
                 elements.push((name, field_value));
             }
@@ -162,8 +530,8 @@ fn fields_type_example(
         }
         (false, true) => {
             let mut elements = vec![];
-            for (_, id) in fields {
-                let field_value = transformer.resolve(id)?;
+            for (_, id, type_name) in fields {
+                let field_value = transformer.resolve_field(id, type_name)?;
                 elements.push(field_value);
             }
             Ok(Composite::unnamed(elements))
@@ -184,6 +552,11 @@ mod tests {
 
     use super::example;
     use super::example_from_seed;
+    use super::exhaustive_examples;
+    use super::{encoded_size, example_with_options, ExampleOptions};
+    use super::{example_with_middlewares, path_middleware, predicate_middleware};
+    use crate::type_example::strategy::ExampleStrategy;
+    use scale_value::{At, Value};
 
     fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
         let mut registry = scale_info::Registry::new();
@@ -206,6 +579,51 @@ mod tests {
         let (id, types) = make_type::<Human>();
         // Make sure recursion does not panic. An error should be yielded instead.
         assert!(example(id, &types).is_err());
+        let examples = exhaustive_examples(id, &types, 10).collect::<Vec<_>>();
+        assert_eq!(examples.len(), 1);
+        assert!(examples[0].is_err());
+    }
+
+    #[test]
+    fn exhaustive_examples_cover_all_cases() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Human {
+            eye_color: Color,
+            scores: Vec<u8>,
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Color {
+            Black,
+            White,
+            Green(i32),
+        }
+
+        let (id, types) = make_type::<Human>();
+        let examples = exhaustive_examples(id, &types, 100)
+            .map(|e| e.unwrap().to_string())
+            .collect::<Vec<_>>();
+
+        // `Green` is generated every third example, so the three boundary values of `i32` take 9 examples.
+        assert_eq!(
+            examples,
+            [
+                "{ eye_color: Black (), scores: () }",
+                "{ eye_color: White (), scores: (0) }",
+                "{ eye_color: Green (0), scores: (255, 0, 255) }",
+                "{ eye_color: Black (), scores: () }",
+                "{ eye_color: White (), scores: (0) }",
+                "{ eye_color: Green (-2147483648), scores: (255, 0, 255) }",
+                "{ eye_color: Black (), scores: () }",
+                "{ eye_color: White (), scores: (0) }",
+                "{ eye_color: Green (2147483647), scores: (255, 0, 255) }",
+            ]
+        );
+
+        let examples = exhaustive_examples(id, &types, 4).collect::<Vec<_>>();
+        assert_eq!(examples.len(), 4);
     }
 
     #[test]
@@ -247,4 +665,217 @@ mod tests {
         assert_eq!(b1, b2);
         assert_ne!(a1, b1);
     }
+
+    #[test]
+    fn size_constrained_examples() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Block {
+            number: u32,
+            extrinsics: Vec<Vec<u8>>,
+            digest: Vec<Digest>,
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Digest {
+            Empty,
+            Seal(u64, Vec<u8>),
+        }
+
+        let (id, types) = make_type::<Block>();
+
+        let options = ExampleOptions::new().target_encoded_size(10_000);
+        let value = example_with_options(id, &types, 2, options).unwrap();
+        let size = encoded_size(&value, id, &types).unwrap();
+        assert!((10_000..10_100).contains(&size), "size: {size}");
+
+        let options = ExampleOptions::new()
+            .target_encoded_size(10_000)
+            .max_sequence_len(3);
+        let value = example_with_options(id, &types, 2, options).unwrap();
+        let size = encoded_size(&value, id, &types).unwrap();
+        assert!(size < 100, "size: {size}");
+
+        // Below depth 1, the sequences are empty.
+        let options = ExampleOptions::new().max_depth(1);
+        let value = example_with_options(id, &types, 2, options).unwrap();
+        let size = encoded_size(&value, id, &types).unwrap();
+        assert_eq!(size, 4 + 1 + 1);
+
+        // Below depth 2, `Vec<u8>` elements are empty and `Digest` is always `Empty`.
+        let options = ExampleOptions::new().max_depth(2);
+        let value = example_with_options(id, &types, 2, options).unwrap();
+        assert_eq!(
+            value.to_string(),
+            format!(
+                "{{ number: {}, extrinsics: ((), ()), digest: (Empty (), Empty ()) }}",
+                value.at("number").unwrap().as_u128().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn strategy_controls_values() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Human {
+            name: String,
+            age: i8,
+            friends: Vec<u64>,
+            eye_color: Color,
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Color {
+            Black,
+            White,
+            Green(i32),
+        }
+
+        let (id, types) = make_type::<Human>();
+        let strategy = ExampleStrategy::new()
+            .strings(["Alice"])
+            .integers(-1000..=3)
+            .sequence_len(3..=3)
+            .variant_weight("Black", 0)
+            .variant_weight("White", 0);
+        let options = ExampleOptions::new().strategy(strategy);
+
+        for seed in 0..10 {
+            let value = example_with_options(id, &types, seed, options.clone()).unwrap();
+            assert_eq!(value.at("name").unwrap().as_str(), Some("Alice"));
+            let age = value.at("age").unwrap().as_i128().unwrap();
+            assert!((-128..=3).contains(&age));
+            let friends = value.at("friends").unwrap();
+            assert_eq!(friends.at(2).unwrap().as_u128().map(|n| n <= 3), Some(true));
+            assert!(friends.at(3).is_none());
+            assert!(value.to_string().contains("eye_color: Green ("));
+        }
+    }
+
+    #[test]
+    fn middlewares() {
+        mod sp_core {
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct AccountId32(pub [u8; 32]);
+        }
+        use sp_core::AccountId32;
+
+        type Balance = u128;
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Transfer {
+            from: AccountId32,
+            to: AccountId32,
+            amount: Balance,
+            tip: u128,
+            others: Vec<AccountId32>,
+        }
+
+        let (id, types) = make_type::<Transfer>();
+        let alice = Value::from_bytes([1; 32]);
+        let middlewares = vec![
+            path_middleware(
+                "scale_typegen_description::type_example::scale_value::tests::sp_core::AccountId32",
+                Value::unnamed_composite([alice.clone()]),
+            ),
+            predicate_middleware(
+                |cx| cx.field_type_name == Some("Balance"),
+                |_| Ok(Value::u128(1_000_000_000_000)),
+            ),
+        ];
+        let value =
+            example_with_middlewares(id, &types, 2, ExampleOptions::new(), middlewares).unwrap();
+
+        let alice_account = Value::unnamed_composite([alice]);
+        assert_eq!(value.at("from"), Some(&alice_account));
+        assert_eq!(value.at("to"), Some(&alice_account));
+        assert_eq!(value.at("others").unwrap().at(1), Some(&alice_account));
+        assert_eq!(
+            value.at("amount").unwrap().as_u128(),
+            Some(1_000_000_000_000)
+        );
+        assert_ne!(value.at("tip").unwrap().as_u128(), Some(1_000_000_000_000));
+    }
+
+    #[test]
+    fn bounded_recursion() {
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum Tree {
+            Node(Box<Tree>, Box<Tree>),
+            Leaf(u8),
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Human {
+            name: String,
+            children: Vec<Human>,
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct Infinite {
+            next: Box<Infinite>,
+        }
+
+        let strategy = ExampleStrategy::new()
+            .max_recursion_depth(1)
+            .variant_weight("Leaf", 0)
+            .strings(["Bob"]);
+        let options = ExampleOptions::new().strategy(strategy.clone());
+
+        let (id, types) = make_type::<Tree>();
+        let value = example_with_options(id, &types, 2, options.clone()).unwrap();
+        assert_eq!(
+            value.to_string().replace(char::is_numeric, ""),
+            "Node (Leaf (), Leaf ())"
+        );
+        let deeper_options =
+            ExampleOptions::new().strategy(strategy.clone().max_recursion_depth(2));
+        let value = example_with_options(id, &types, 2, deeper_options).unwrap();
+        assert_eq!(
+            value.to_string().replace(char::is_numeric, ""),
+            "Node (Node (Leaf (), Leaf ()), Node (Leaf (), Leaf ()))"
+        );
+
+        let (id, types) = make_type::<Human>();
+        let value = example_with_options(id, &types, 2, options.clone()).unwrap();
+        assert_eq!(
+            value.to_string(),
+            "{ name: \"Bob\", children: ({ name: \"Bob\", children: () }, { name: \"Bob\", children: () }) }"
+        );
+
+        // Types without a finite example still yield an error.
+        let (id, types) = make_type::<Infinite>();
+        assert!(example_with_options(id, &types, 2, options).is_err());
+
+        let options = ExampleOptions::new().strategy(strategy.max_recursion_depth(0));
+        let (id, types) = make_type::<Tree>();
+        let value = example_with_options(id, &types, 2, options.clone()).unwrap();
+        assert!(value.to_string().starts_with("Leaf ("));
+
+        // `Node` has fewer fields, but `U` is only finite through `T`, so choosing it would never terminate.
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        enum T {
+            Leaf(u8, u8),
+            Node(Box<U>),
+        }
+
+        #[allow(unused)]
+        #[derive(TypeInfo)]
+        struct U {
+            t: T,
+        }
+
+        let (id, types) = make_type::<T>();
+        let value = example_with_options(id, &types, 2, options).unwrap();
+        assert!(value.to_string().starts_with("Leaf ("));
+    }
 }
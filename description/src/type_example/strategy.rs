@@ -0,0 +1,244 @@
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use anyhow::anyhow;
+use proc_macro2::TokenStream;
+use quote::quote;
+use rand::{seq::SliceRandom, Rng};
+use scale_info::{form::PortableForm, TypeDefPrimitive, Variant};
+use scale_value::Primitive;
+
+/// Controls the values chosen for rust value and scale value examples.
+///
+/// The default strategy generates the same examples as [`crate::rust_value::example_from_seed`]
+/// and [`crate::scale_value::example_from_seed`].
+#[derive(Debug, Clone)]
+pub struct ExampleStrategy {
+    /// The strings that are chosen from for `str` primitives.
+    pub strings: Vec<String>,
+    /// The characters that are chosen from for `char` primitives.
+    pub chars: Vec<char>,
+    /// If set, integers are sampled from this range, clamped to the bounds of the integer type.
+    /// `u256` and `i256` are not affected.
+    pub integers: Option<RangeInclusive<i128>>,
+    /// The number of elements in sequences.
+    pub sequence_len: RangeInclusive<usize>,
+    /// The number of bits in bit sequences with random contents.
+    pub bit_sequence_len: RangeInclusive<usize>,
+    /// If set, bit sequences always have these contents.
+    pub bit_sequence: Option<Vec<bool>>,
+    /// Relative weights of enum variants by their name, applied to all enums. Variants without a weight
+    /// have a weight of 1, variants with a weight of 0 are never chosen.
+    pub variant_weights: HashMap<String, u32>,
+    /// The type used to construct bit sequences in rust value examples, via its `from_iter` function.
+    pub bit_sequence_path: TokenStream,
+    /// The type used to wrap the values of explicitly compact fields in rust value examples.
+    pub compact_path: TokenStream,
+    /// If set, recursive types are nested up to this depth, instead of yielding an error. Below it, sequences
+    /// are empty and the enum variant with the fewest fields that does not recurse infinitely is chosen.
+    /// With a depth of 0, no type is nested within itself.
+    pub max_recursion_depth: Option<usize>,
+}
+
+impl Default for ExampleStrategy {
+    fn default() -> Self {
+        Self {
+            strings: ["Foo", "Bar", "Fizz", "Buzz"].map(String::from).into(),
+            chars: vec!['a', 'b', 'c', 'd', 'e', 'f', 'g'],
+            integers: None,
+            sequence_len: 2..=2,
+            bit_sequence_len: 3..=6,
+            bit_sequence: None,
+            variant_weights: HashMap::new(),
+            bit_sequence_path: quote!(subxt::utils::bits::DecodedBits),
+            compact_path: quote!(Compact),
+            max_recursion_depth: None,
+        }
+    }
+}
+
+impl ExampleStrategy {
+    /// Creates a new default strategy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `strings` field.
+    pub fn strings(mut self, strings: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.strings = strings.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the `chars` field.
+    pub fn chars(mut self, chars: impl IntoIterator<Item = char>) -> Self {
+        self.chars = chars.into_iter().collect();
+        self
+    }
+
+    /// Sets the `integers` field.
+    pub fn integers(mut self, integers: RangeInclusive<i128>) -> Self {
+        self.integers = Some(integers);
+        self
+    }
+
+    /// Sets the `sequence_len` field.
+    pub fn sequence_len(mut self, sequence_len: RangeInclusive<usize>) -> Self {
+        self.sequence_len = sequence_len;
+        self
+    }
+
+    /// Sets the `bit_sequence_len` field.
+    pub fn bit_sequence_len(mut self, bit_sequence_len: RangeInclusive<usize>) -> Self {
+        self.bit_sequence_len = bit_sequence_len;
+        self
+    }
+
+    /// Sets the `bit_sequence` field.
+    pub fn bit_sequence(mut self, bits: impl IntoIterator<Item = bool>) -> Self {
+        self.bit_sequence = Some(bits.into_iter().collect());
+        self
+    }
+
+    /// Sets the weight of all variants with this name.
+    pub fn variant_weight(mut self, name: impl Into<String>, weight: u32) -> Self {
+        self.variant_weights.insert(name.into(), weight);
+        self
+    }
+
+    /// Sets the `bit_sequence_path` field.
+    pub fn bit_sequence_path(mut self, path: TokenStream) -> Self {
+        self.bit_sequence_path = path;
+        self
+    }
+
+    /// Sets the `compact_path` field.
+    pub fn compact_path(mut self, path: TokenStream) -> Self {
+        self.compact_path = path;
+        self
+    }
+
+    /// Sets the `max_recursion_depth` field.
+    pub fn max_recursion_depth(mut self, max_recursion_depth: usize) -> Self {
+        self.max_recursion_depth = Some(max_recursion_depth);
+        self
+    }
+
+    pub(crate) fn sequence_len_sample(&self, rng: &mut impl Rng) -> usize {
+        sample_len(&self.sequence_len, rng)
+    }
+
+    pub(crate) fn bits(&self, rng: &mut impl Rng) -> Vec<bool> {
+        match &self.bit_sequence {
+            Some(bits) => bits.clone(),
+            None => (0..sample_len(&self.bit_sequence_len, rng))
+                .map(|_| rng.gen())
+                .collect(),
+        }
+    }
+
+    pub(crate) fn choose_variant<'a>(
+        &self,
+        variants: &'a [Variant<PortableForm>],
+        rng: &mut impl Rng,
+    ) -> anyhow::Result<&'a Variant<PortableForm>> {
+        if self.variant_weights.is_empty() {
+            return variants
+                .choose(rng)
+                .ok_or_else(|| anyhow!("Variant type should have at least one variant"));
+        }
+        variants
+            .choose_weighted(rng, |variant| {
+                self.variant_weights
+                    .get(&variant.name)
+                    .copied()
+                    .unwrap_or(1)
+            })
+            .map_err(|e| anyhow!("Cannot choose a variant: {e}"))
+    }
+
+    pub(crate) fn primitive(
+        &self,
+        def: &TypeDefPrimitive,
+        rng: &mut impl Rng,
+    ) -> anyhow::Result<Primitive> {
+        let primitive = match def {
+            TypeDefPrimitive::Bool => Primitive::Bool(rng.gen()),
+            TypeDefPrimitive::Char => Primitive::Char(
+                *self
+                    .chars
+                    .choose(rng)
+                    .ok_or_else(|| anyhow!("The example strategy has no chars"))?,
+            ),
+            TypeDefPrimitive::Str => Primitive::String(
+                self.strings
+                    .choose(rng)
+                    .ok_or_else(|| anyhow!("The example strategy has no strings"))?
+                    .clone(),
+            ),
+            TypeDefPrimitive::U8 => Primitive::U128(
+                self.integer(0, u8::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<u8>().into()) as u128,
+            ),
+            TypeDefPrimitive::U16 => Primitive::U128(
+                self.integer(0, u16::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<u16>().into()) as u128,
+            ),
+            TypeDefPrimitive::U32 => Primitive::U128(
+                self.integer(0, u32::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<u32>().into()) as u128,
+            ),
+            TypeDefPrimitive::U64 => Primitive::U128(
+                self.integer(0, u64::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<u64>().into()) as u128,
+            ),
+            TypeDefPrimitive::U128 => match self.integer(0, i128::MAX, rng) {
+                Some(n) => Primitive::U128(n as u128),
+                None => Primitive::U128(rng.gen()),
+            },
+            TypeDefPrimitive::U256 => Primitive::U256(rng.gen()),
+            TypeDefPrimitive::I8 => Primitive::I128(
+                self.integer(i8::MIN.into(), i8::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<i8>().into()),
+            ),
+            TypeDefPrimitive::I16 => Primitive::I128(
+                self.integer(i16::MIN.into(), i16::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<i16>().into()),
+            ),
+            TypeDefPrimitive::I32 => Primitive::I128(
+                self.integer(i32::MIN.into(), i32::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<i32>().into()),
+            ),
+            TypeDefPrimitive::I64 => Primitive::I128(
+                self.integer(i64::MIN.into(), i64::MAX.into(), rng)
+                    .unwrap_or_else(|| rng.gen::<i64>().into()),
+            ),
+            TypeDefPrimitive::I128 => Primitive::I128(
+                self.integer(i128::MIN, i128::MAX, rng)
+                    .unwrap_or_else(|| rng.gen()),
+            ),
+            TypeDefPrimitive::I256 => Primitive::I256(rng.gen()),
+        };
+        Ok(primitive)
+    }
+
+    /// Samples an integer from the `integers` range, clamped to `min..=max`. None if no range is set.
+    fn integer(&self, min: i128, max: i128, rng: &mut impl Rng) -> Option<i128> {
+        let range = self.integers.as_ref()?;
+        let (start, end) = ((*range.start()).max(min), (*range.end()).min(max));
+        let n = if start <= end {
+            rng.gen_range(start..=end)
+        } else if *range.start() > max {
+            max
+        } else {
+            min
+        };
+        Some(n)
+    }
+}
+
+/// Samples a length from the range. Fixed lengths do not advance the rng.
+fn sample_len(range: &RangeInclusive<usize>, rng: &mut impl Rng) -> usize {
+    if range.start() >= range.end() {
+        return *range.start();
+    }
+    rng.gen_range(range.clone())
+}
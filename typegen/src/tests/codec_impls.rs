@@ -0,0 +1,822 @@
+use parity_scale_codec::{Decode, Encode, EncodeLike};
+use pretty_assertions::assert_eq;
+use proc_macro2::TokenStream;
+use scale_decode::DecodeAsType;
+use scale_encode::EncodeAsType;
+use scale_info::{meta_type, PortableRegistry, Registry};
+use syn::parse_quote;
+
+use crate::{typegen::ir::ToTokensWithSettings, TypeGenerator, TypeGeneratorSettings};
+
+mod source {
+    use parity_scale_codec::{Decode, Encode, HasCompact};
+    use scale_info::TypeInfo;
+
+    #[derive(TypeInfo, Encode, Decode)]
+    pub struct S {
+        pub a: u8,
+        #[codec(compact)]
+        pub b: u64,
+        pub e: E,
+        pub g: G<u32>,
+        pub p: P<NoCodec>,
+    }
+
+    #[derive(TypeInfo, Encode, Decode)]
+    pub enum E {
+        #[codec(index = 3)]
+        A,
+        B(bool, #[codec(compact)] u128),
+        C {
+            x: u16,
+        },
+    }
+
+    #[derive(TypeInfo, Encode, Decode)]
+    pub struct G<T: HasCompact> {
+        #[codec(compact)]
+        pub c: T,
+    }
+
+    #[derive(TypeInfo, Encode, Decode)]
+    pub struct P<T> {
+        pub a: u8,
+        pub _marker: ::core::marker::PhantomData<T>,
+    }
+
+    #[derive(TypeInfo)]
+    pub struct NoCodec;
+}
+
+/// Emits the given generated code, so it is compiled, and keeps it as a string to compare it with the generator
+/// output.
+macro_rules! compiled {
+    ($generated:item) => {
+        #[allow(unused_imports, dead_code)]
+        $generated
+
+        const COMPILED: &str = stringify!($generated);
+    };
+}
+
+fn settings() -> TypeGeneratorSettings {
+    TypeGeneratorSettings::new()
+        .compact_type_path(parse_quote!(::parity_scale_codec::Compact))
+        .explicit_codec_impls(parse_quote!(::parity_scale_codec))
+        .explicit_encode_as_type_impls(parse_quote!(::scale_encode))
+        .explicit_decode_as_type_impls(parse_quote!(::scale_decode))
+        .substitute(
+            parse_quote!(scale_typegen::tests::codec_impls::source::NoCodec),
+            parse_quote!(crate::tests::codec_impls::source::NoCodec),
+        )
+}
+
+fn registry() -> (PortableRegistry, u32) {
+    let mut registry = Registry::new();
+    let id = registry.register_type(&meta_type::<source::S>()).id;
+    (registry.into(), id)
+}
+
+compiled! {
+    pub mod types {
+        use super::types;
+        pub mod scale_typegen {
+            use super::types;
+            pub mod tests {
+                use super::types;
+                pub mod codec_impls {
+                    use super::types;
+                    pub mod source {
+                        use super::types;
+                        pub enum E {
+                            A,
+                            B(::core::primitive::bool, ::core::primitive::u128),
+                            C { x: ::core::primitive::u16 },
+                        }
+                        impl ::parity_scale_codec::Encode for E {
+                            fn encode_to<
+                                __CodecOutput: ::parity_scale_codec::Output
+                                    + ?::core::marker::Sized,
+                            >(&self, __codec_dest: &mut __CodecOutput) {
+                                match self {
+                                    Self::A => {
+                                        ::parity_scale_codec::Output::push_byte(__codec_dest, 3u8);
+                                    }
+                                    Self::B(__binding_0, __binding_1) => {
+                                        ::parity_scale_codec::Output::push_byte(__codec_dest, 1u8);
+                                        ::parity_scale_codec::Encode::encode_to(
+                                            __binding_0,
+                                            __codec_dest,
+                                        );
+                                        ::parity_scale_codec::Encode::encode_to(
+                                            &<<::core::primitive::u128 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::EncodeAsRef<
+                                                '_,
+                                                ::core::primitive::u128,
+                                            >>::RefType::from(__binding_1),
+                                            __codec_dest,
+                                        );
+                                    }
+                                    Self::C { x } => {
+                                        ::parity_scale_codec::Output::push_byte(__codec_dest, 2u8);
+                                        ::parity_scale_codec::Encode::encode_to(x, __codec_dest);
+                                    }
+                                }
+                            }
+                        }
+                        impl ::parity_scale_codec::EncodeLike for E {}
+                        impl ::parity_scale_codec::Decode for E {
+                            fn decode<__CodecInput: ::parity_scale_codec::Input>(
+                                __codec_input: &mut __CodecInput,
+                            ) -> ::core::result::Result<Self, ::parity_scale_codec::Error> {
+                                match ::parity_scale_codec::Input::read_byte(__codec_input)
+                                    .map_err(|e| {
+                                        e.chain("Could not decode `E`, failed to read variant byte")
+                                    })?
+                                {
+                                    3u8 => ::core::result::Result::Ok(Self::A),
+                                    1u8 => {
+                                        ::core::result::Result::Ok(
+                                            Self::B(
+                                                <::core::primitive::bool as ::parity_scale_codec::Decode>::decode(
+                                                        __codec_input,
+                                                    )
+                                                    .map_err(|e| e.chain("Could not decode `E::B.0`"))?,
+                                                <<::core::primitive::u128 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::Decode>::decode(
+                                                        __codec_input,
+                                                    )
+                                                    .map_err(|e| e.chain("Could not decode `E::B.1`"))?
+                                                    .into(),
+                                            ),
+                                        )
+                                    }
+                                    2u8 => {
+                                        ::core::result::Result::Ok(Self::C {
+                                            x: <::core::primitive::u16 as ::parity_scale_codec::Decode>::decode(
+                                                    __codec_input,
+                                                )
+                                                .map_err(|e| e.chain("Could not decode `E::C::x`"))?,
+                                        })
+                                    }
+                                    _ => {
+                                        ::core::result::Result::Err(
+                                            "Could not decode `E`, variant doesn't exist".into(),
+                                        )
+                                    }
+                                }
+                            }
+                        }
+                        impl ::scale_encode::EncodeAsType for E {
+                            fn encode_as_type_to<
+                                __EncodeAsTypeResolver: ::scale_encode::TypeResolver,
+                            >(
+                                &self,
+                                __encode_as_type_id: __EncodeAsTypeResolver::TypeId,
+                                __encode_as_type_types: &__EncodeAsTypeResolver,
+                                __encode_as_type_out: &mut ::scale_encode::Vec<
+                                    ::core::primitive::u8,
+                                >,
+                            ) -> ::core::result::Result<(), ::scale_encode::Error> {
+                                match self {
+                                    Self::A => {
+                                        ::scale_encode::Variant {
+                                            name: "A",
+                                            fields: ::scale_encode::Composite::new([].into_iter()),
+                                        }
+                                            .encode_variant_as_type_to(
+                                                __encode_as_type_id,
+                                                __encode_as_type_types,
+                                                __encode_as_type_out,
+                                            )
+                                    }
+                                    Self::B(__binding_0, __binding_1) => {
+                                        ::scale_encode::Variant {
+                                            name: "B",
+                                            fields: ::scale_encode::Composite::new(
+                                                [
+                                                    (
+                                                        ::core::option::Option::None,
+                                                        ::scale_encode::CompositeField::new(__binding_0),
+                                                    ),
+                                                    (
+                                                        ::core::option::Option::None,
+                                                        ::scale_encode::CompositeField::new(__binding_1),
+                                                    ),
+                                                ]
+                                                    .into_iter(),
+                                            ),
+                                        }
+                                            .encode_variant_as_type_to(
+                                                __encode_as_type_id,
+                                                __encode_as_type_types,
+                                                __encode_as_type_out,
+                                            )
+                                    }
+                                    Self::C { x } => {
+                                        ::scale_encode::Variant {
+                                            name: "C",
+                                            fields: ::scale_encode::Composite::new(
+                                                [
+                                                    (
+                                                        ::core::option::Option::Some("x"),
+                                                        ::scale_encode::CompositeField::new(x),
+                                                    ),
+                                                ]
+                                                    .into_iter(),
+                                            ),
+                                        }
+                                            .encode_variant_as_type_to(
+                                                __encode_as_type_id,
+                                                __encode_as_type_types,
+                                                __encode_as_type_out,
+                                            )
+                                    }
+                                }
+                            }
+                        }
+                        const _: () = {
+                            pub struct __DecodeAsTypeVisitor<__DecodeAsTypeResolver>(
+                                ::core::marker::PhantomData<(__DecodeAsTypeResolver,)>,
+                            );
+                            impl<
+                                __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                            > ::scale_decode::Visitor
+                            for __DecodeAsTypeVisitor<__DecodeAsTypeResolver> {
+                                type Value<'scale, 'resolver> = E;
+                                type Error = ::scale_decode::Error;
+                                type TypeResolver = __DecodeAsTypeResolver;
+                                fn visit_variant<'scale, 'resolver>(
+                                    self,
+                                    value: &mut ::scale_decode::visitor::types::Variant<
+                                        'scale,
+                                        'resolver,
+                                        Self::TypeResolver,
+                                    >,
+                                    _type_id: ::scale_decode::visitor::TypeIdFor<Self>,
+                                ) -> ::core::result::Result<
+                                    Self::Value<'scale, 'resolver>,
+                                    Self::Error,
+                                > {
+                                    let __variant_name = value.name();
+                                    let __fields = value
+                                        .fields()
+                                        .collect::<
+                                            ::core::result::Result<::scale_decode::vec::Vec<_>, _>,
+                                        >()?;
+                                    match __variant_name {
+                                        "A" => ::core::result::Result::Ok(E::A),
+                                        "B" => {
+                                            if __fields.len() != 2usize {
+                                                return ::core::result::Result::Err(
+                                                    ::scale_decode::Error::new(::scale_decode::error::ErrorKind::WrongLength {
+                                                            actual_len: __fields.len(),
+                                                            expected_len: 2usize,
+                                                        })
+                                                        .at_variant("B"),
+                                                );
+                                            }
+                                            ::core::result::Result::Ok(
+                                                E::B(
+                                                    __fields[0usize]
+                                                        .decode_as_type()
+                                                        .map_err(|e| e.at_idx(0usize).at_variant("B"))?,
+                                                    __fields[1usize]
+                                                        .decode_as_type()
+                                                        .map_err(|e| e.at_idx(1usize).at_variant("B"))?,
+                                                ),
+                                            )
+                                        }
+                                        "C" => {
+                                            ::core::result::Result::Ok(E::C {
+                                                x: __fields
+                                                    .iter()
+                                                    .find(|field| {
+                                                        field.name() == ::core::option::Option::Some("x")
+                                                    })
+                                                    .ok_or_else(|| {
+                                                        ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                                name: ::scale_decode::ToString::to_string("x"),
+                                                            })
+                                                            .at_variant("C")
+                                                    })?
+                                                    .decode_as_type()
+                                                    .map_err(|e| e.at_field("x").at_variant("C"))?,
+                                            })
+                                        }
+                                        __other => {
+                                            ::core::result::Result::Err(
+                                                ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindVariant {
+                                                    got: ::scale_decode::ToString::to_string(__other),
+                                                    expected: ::scale_decode::vec!["A", "B", "C"],
+                                                }),
+                                            )
+                                        }
+                                    }
+                                }
+                            }
+                            impl ::scale_decode::IntoVisitor for E {
+                                type AnyVisitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                > = __DecodeAsTypeVisitor<__DecodeAsTypeResolver>;
+                                fn into_visitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                >() -> Self::AnyVisitor<__DecodeAsTypeResolver> {
+                                    __DecodeAsTypeVisitor(::core::marker::PhantomData)
+                                }
+                            }
+                        };
+                        pub struct G<_0> {
+                            pub c: _0,
+                        }
+                        impl<_0> ::parity_scale_codec::Encode for G<_0>
+                        where
+                            _0: ::parity_scale_codec::HasCompact,
+                        {
+                            fn encode_to<
+                                __CodecOutput: ::parity_scale_codec::Output
+                                    + ?::core::marker::Sized,
+                            >(&self, __codec_dest: &mut __CodecOutput) {
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &<<_0 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::EncodeAsRef<
+                                        '_,
+                                        _0,
+                                    >>::RefType::from(&self.c),
+                                    __codec_dest,
+                                );
+                            }
+                        }
+                        impl<_0> ::parity_scale_codec::EncodeLike for G<_0>
+                        where
+                            _0: ::parity_scale_codec::HasCompact,
+                        {}
+                        impl<_0> ::parity_scale_codec::Decode for G<_0>
+                        where
+                            _0: ::parity_scale_codec::HasCompact,
+                        {
+                            fn decode<__CodecInput: ::parity_scale_codec::Input>(
+                                __codec_input: &mut __CodecInput,
+                            ) -> ::core::result::Result<Self, ::parity_scale_codec::Error> {
+                                ::core::result::Result::Ok(Self {
+                                    c: <<_0 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::Decode>::decode(
+                                            __codec_input,
+                                        )
+                                        .map_err(|e| e.chain("Could not decode `G::c`"))?
+                                        .into(),
+                                })
+                            }
+                        }
+                        impl<_0> ::scale_encode::EncodeAsType for G<_0>
+                        where
+                            _0: ::scale_encode::EncodeAsType,
+                        {
+                            fn encode_as_type_to<
+                                __EncodeAsTypeResolver: ::scale_encode::TypeResolver,
+                            >(
+                                &self,
+                                __encode_as_type_id: __EncodeAsTypeResolver::TypeId,
+                                __encode_as_type_types: &__EncodeAsTypeResolver,
+                                __encode_as_type_out: &mut ::scale_encode::Vec<
+                                    ::core::primitive::u8,
+                                >,
+                            ) -> ::core::result::Result<(), ::scale_encode::Error> {
+                                ::scale_encode::Composite::new(
+                                        [
+                                            (
+                                                ::core::option::Option::Some("c"),
+                                                ::scale_encode::CompositeField::new(&self.c),
+                                            ),
+                                        ]
+                                            .into_iter(),
+                                    )
+                                    .encode_composite_as_type_to(
+                                        __encode_as_type_id,
+                                        __encode_as_type_types,
+                                        __encode_as_type_out,
+                                    )
+                            }
+                        }
+                        const _: () = {
+                            pub struct __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0>(
+                                ::core::marker::PhantomData<(__DecodeAsTypeResolver, _0)>,
+                            );
+                            impl<
+                                __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                _0,
+                            > ::scale_decode::Visitor
+                            for __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0>
+                            where
+                                _0: ::scale_decode::IntoVisitor,
+                            {
+                                type Value<'scale, 'resolver> = G<_0>;
+                                type Error = ::scale_decode::Error;
+                                type TypeResolver = __DecodeAsTypeResolver;
+                                fn visit_composite<'scale, 'resolver>(
+                                    self,
+                                    value: &mut ::scale_decode::visitor::types::Composite<
+                                        'scale,
+                                        'resolver,
+                                        Self::TypeResolver,
+                                    >,
+                                    _type_id: ::scale_decode::visitor::TypeIdFor<Self>,
+                                ) -> ::core::result::Result<
+                                    Self::Value<'scale, 'resolver>,
+                                    Self::Error,
+                                > {
+                                    let __fields = value
+                                        .collect::<
+                                            ::core::result::Result<::scale_decode::vec::Vec<_>, _>,
+                                        >()?;
+                                    ::core::result::Result::Ok(G {
+                                        c: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("c")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("c"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("c"))?,
+                                    })
+                                }
+                            }
+                            impl<_0> ::scale_decode::IntoVisitor for G<_0>
+                            where
+                                _0: ::scale_decode::IntoVisitor,
+                            {
+                                type AnyVisitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                > = __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0>;
+                                fn into_visitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                >() -> Self::AnyVisitor<__DecodeAsTypeResolver> {
+                                    __DecodeAsTypeVisitor(::core::marker::PhantomData)
+                                }
+                            }
+                        };
+                        pub struct P<_0> {
+                            pub a: ::core::primitive::u8,
+                            pub __ignore: ::core::marker::PhantomData<_0>,
+                        }
+                        impl<_0> ::parity_scale_codec::Encode for P<_0> {
+                            fn encode_to<
+                                __CodecOutput: ::parity_scale_codec::Output
+                                    + ?::core::marker::Sized,
+                            >(&self, __codec_dest: &mut __CodecOutput) {
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &self.a,
+                                    __codec_dest,
+                                );
+                            }
+                        }
+                        impl<_0> ::parity_scale_codec::EncodeLike for P<_0> {}
+                        impl<_0> ::parity_scale_codec::Decode for P<_0> {
+                            fn decode<__CodecInput: ::parity_scale_codec::Input>(
+                                __codec_input: &mut __CodecInput,
+                            ) -> ::core::result::Result<Self, ::parity_scale_codec::Error> {
+                                ::core::result::Result::Ok(Self {
+                                    a: <::core::primitive::u8 as ::parity_scale_codec::Decode>::decode(
+                                            __codec_input,
+                                        )
+                                        .map_err(|e| e.chain("Could not decode `P::a`"))?,
+                                    __ignore: ::core::marker::PhantomData,
+                                })
+                            }
+                        }
+                        impl<_0> ::scale_encode::EncodeAsType for P<_0> {
+                            fn encode_as_type_to<
+                                __EncodeAsTypeResolver: ::scale_encode::TypeResolver,
+                            >(
+                                &self,
+                                __encode_as_type_id: __EncodeAsTypeResolver::TypeId,
+                                __encode_as_type_types: &__EncodeAsTypeResolver,
+                                __encode_as_type_out: &mut ::scale_encode::Vec<
+                                    ::core::primitive::u8,
+                                >,
+                            ) -> ::core::result::Result<(), ::scale_encode::Error> {
+                                ::scale_encode::Composite::new(
+                                        [
+                                            (
+                                                ::core::option::Option::Some("a"),
+                                                ::scale_encode::CompositeField::new(&self.a),
+                                            ),
+                                        ]
+                                            .into_iter(),
+                                    )
+                                    .encode_composite_as_type_to(
+                                        __encode_as_type_id,
+                                        __encode_as_type_types,
+                                        __encode_as_type_out,
+                                    )
+                            }
+                        }
+                        const _: () = {
+                            pub struct __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0>(
+                                ::core::marker::PhantomData<(__DecodeAsTypeResolver, _0)>,
+                            );
+                            impl<
+                                __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                _0,
+                            > ::scale_decode::Visitor
+                            for __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0> {
+                                type Value<'scale, 'resolver> = P<_0>;
+                                type Error = ::scale_decode::Error;
+                                type TypeResolver = __DecodeAsTypeResolver;
+                                fn visit_composite<'scale, 'resolver>(
+                                    self,
+                                    value: &mut ::scale_decode::visitor::types::Composite<
+                                        'scale,
+                                        'resolver,
+                                        Self::TypeResolver,
+                                    >,
+                                    _type_id: ::scale_decode::visitor::TypeIdFor<Self>,
+                                ) -> ::core::result::Result<
+                                    Self::Value<'scale, 'resolver>,
+                                    Self::Error,
+                                > {
+                                    let __fields = value
+                                        .collect::<
+                                            ::core::result::Result<::scale_decode::vec::Vec<_>, _>,
+                                        >()?;
+                                    ::core::result::Result::Ok(P {
+                                        a: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("a")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("a"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("a"))?,
+                                        __ignore: ::core::marker::PhantomData,
+                                    })
+                                }
+                            }
+                            impl<_0> ::scale_decode::IntoVisitor for P<_0> {
+                                type AnyVisitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                > = __DecodeAsTypeVisitor<__DecodeAsTypeResolver, _0>;
+                                fn into_visitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                >() -> Self::AnyVisitor<__DecodeAsTypeResolver> {
+                                    __DecodeAsTypeVisitor(::core::marker::PhantomData)
+                                }
+                            }
+                        };
+                        pub struct S {
+                            pub a: ::core::primitive::u8,
+                            pub b: ::core::primitive::u64,
+                            pub e: types::scale_typegen::tests::codec_impls::source::E,
+                            pub g: types::scale_typegen::tests::codec_impls::source::G<
+                                ::core::primitive::u32,
+                            >,
+                            pub p: types::scale_typegen::tests::codec_impls::source::P<
+                                crate::tests::codec_impls::source::NoCodec,
+                            >,
+                        }
+                        impl ::parity_scale_codec::Encode for S {
+                            fn encode_to<
+                                __CodecOutput: ::parity_scale_codec::Output
+                                    + ?::core::marker::Sized,
+                            >(&self, __codec_dest: &mut __CodecOutput) {
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &self.a,
+                                    __codec_dest,
+                                );
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &<<::core::primitive::u64 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::EncodeAsRef<
+                                        '_,
+                                        ::core::primitive::u64,
+                                    >>::RefType::from(&self.b),
+                                    __codec_dest,
+                                );
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &self.e,
+                                    __codec_dest,
+                                );
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &self.g,
+                                    __codec_dest,
+                                );
+                                ::parity_scale_codec::Encode::encode_to(
+                                    &self.p,
+                                    __codec_dest,
+                                );
+                            }
+                        }
+                        impl ::parity_scale_codec::EncodeLike for S {}
+                        impl ::parity_scale_codec::Decode for S {
+                            fn decode<__CodecInput: ::parity_scale_codec::Input>(
+                                __codec_input: &mut __CodecInput,
+                            ) -> ::core::result::Result<Self, ::parity_scale_codec::Error> {
+                                ::core::result::Result::Ok(Self {
+                                    a: <::core::primitive::u8 as ::parity_scale_codec::Decode>::decode(
+                                            __codec_input,
+                                        )
+                                        .map_err(|e| e.chain("Could not decode `S::a`"))?,
+                                    b: <<::core::primitive::u64 as ::parity_scale_codec::HasCompact>::Type as ::parity_scale_codec::Decode>::decode(
+                                            __codec_input,
+                                        )
+                                        .map_err(|e| e.chain("Could not decode `S::b`"))?
+                                        .into(),
+                                    e: <types::scale_typegen::tests::codec_impls::source::E as ::parity_scale_codec::Decode>::decode(
+                                            __codec_input,
+                                        )
+                                        .map_err(|e| e.chain("Could not decode `S::e`"))?,
+                                    g: <types::scale_typegen::tests::codec_impls::source::G<
+                                        ::core::primitive::u32,
+                                    > as ::parity_scale_codec::Decode>::decode(__codec_input)
+                                        .map_err(|e| e.chain("Could not decode `S::g`"))?,
+                                    p: <types::scale_typegen::tests::codec_impls::source::P<
+                                        crate::tests::codec_impls::source::NoCodec,
+                                    > as ::parity_scale_codec::Decode>::decode(__codec_input)
+                                        .map_err(|e| e.chain("Could not decode `S::p`"))?,
+                                })
+                            }
+                        }
+                        impl ::scale_encode::EncodeAsType for S {
+                            fn encode_as_type_to<
+                                __EncodeAsTypeResolver: ::scale_encode::TypeResolver,
+                            >(
+                                &self,
+                                __encode_as_type_id: __EncodeAsTypeResolver::TypeId,
+                                __encode_as_type_types: &__EncodeAsTypeResolver,
+                                __encode_as_type_out: &mut ::scale_encode::Vec<
+                                    ::core::primitive::u8,
+                                >,
+                            ) -> ::core::result::Result<(), ::scale_encode::Error> {
+                                ::scale_encode::Composite::new(
+                                        [
+                                            (
+                                                ::core::option::Option::Some("a"),
+                                                ::scale_encode::CompositeField::new(&self.a),
+                                            ),
+                                            (
+                                                ::core::option::Option::Some("b"),
+                                                ::scale_encode::CompositeField::new(&self.b),
+                                            ),
+                                            (
+                                                ::core::option::Option::Some("e"),
+                                                ::scale_encode::CompositeField::new(&self.e),
+                                            ),
+                                            (
+                                                ::core::option::Option::Some("g"),
+                                                ::scale_encode::CompositeField::new(&self.g),
+                                            ),
+                                            (
+                                                ::core::option::Option::Some("p"),
+                                                ::scale_encode::CompositeField::new(&self.p),
+                                            ),
+                                        ]
+                                            .into_iter(),
+                                    )
+                                    .encode_composite_as_type_to(
+                                        __encode_as_type_id,
+                                        __encode_as_type_types,
+                                        __encode_as_type_out,
+                                    )
+                            }
+                        }
+                        const _: () = {
+                            pub struct __DecodeAsTypeVisitor<__DecodeAsTypeResolver>(
+                                ::core::marker::PhantomData<(__DecodeAsTypeResolver,)>,
+                            );
+                            impl<
+                                __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                            > ::scale_decode::Visitor
+                            for __DecodeAsTypeVisitor<__DecodeAsTypeResolver> {
+                                type Value<'scale, 'resolver> = S;
+                                type Error = ::scale_decode::Error;
+                                type TypeResolver = __DecodeAsTypeResolver;
+                                fn visit_composite<'scale, 'resolver>(
+                                    self,
+                                    value: &mut ::scale_decode::visitor::types::Composite<
+                                        'scale,
+                                        'resolver,
+                                        Self::TypeResolver,
+                                    >,
+                                    _type_id: ::scale_decode::visitor::TypeIdFor<Self>,
+                                ) -> ::core::result::Result<
+                                    Self::Value<'scale, 'resolver>,
+                                    Self::Error,
+                                > {
+                                    let __fields = value
+                                        .collect::<
+                                            ::core::result::Result<::scale_decode::vec::Vec<_>, _>,
+                                        >()?;
+                                    ::core::result::Result::Ok(S {
+                                        a: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("a")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("a"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("a"))?,
+                                        b: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("b")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("b"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("b"))?,
+                                        e: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("e")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("e"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("e"))?,
+                                        g: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("g")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("g"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("g"))?,
+                                        p: __fields
+                                            .iter()
+                                            .find(|field| {
+                                                field.name() == ::core::option::Option::Some("p")
+                                            })
+                                            .ok_or_else(|| ::scale_decode::Error::new(::scale_decode::error::ErrorKind::CannotFindField {
+                                                name: ::scale_decode::ToString::to_string("p"),
+                                            }))?
+                                            .decode_as_type()
+                                            .map_err(|e| e.at_field("p"))?,
+                                    })
+                                }
+                            }
+                            impl ::scale_decode::IntoVisitor for S {
+                                type AnyVisitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                > = __DecodeAsTypeVisitor<__DecodeAsTypeResolver>;
+                                fn into_visitor<
+                                    __DecodeAsTypeResolver: ::scale_decode::TypeResolver,
+                                >() -> Self::AnyVisitor<__DecodeAsTypeResolver> {
+                                    __DecodeAsTypeVisitor(::core::marker::PhantomData)
+                                }
+                            }
+                        };
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn explicit_impls_compile_and_round_trip() {
+    let (registry, type_id) = registry();
+    let settings = settings();
+    let code = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod()
+        .unwrap()
+        .to_token_stream(&settings);
+    let compiled: TokenStream = COMPILED.parse().unwrap();
+    let pretty = |code| prettyplease::unparse(&syn::parse2(code).unwrap());
+    assert_eq!(pretty(code), pretty(compiled));
+
+    let value = |e| source::S {
+        a: 1,
+        b: u64::MAX,
+        e,
+        g: source::G { c: 42 },
+        p: source::P {
+            a: 2,
+            _marker: Default::default(),
+        },
+    };
+    let values = [
+        value(source::E::A),
+        value(source::E::B(true, u128::MAX)),
+        value(source::E::C { x: 7 }),
+    ];
+
+    type Generated = types::scale_typegen::tests::codec_impls::source::S;
+    // Like the `Encode` derive, the explicit impls can be used where `EncodeLike` is required.
+    fn assert_encode_like<T: EncodeLike>() {}
+    assert_encode_like::<Generated>();
+    assert_encode_like::<types::scale_typegen::tests::codec_impls::source::G<u32>>();
+
+    for value in values {
+        let encoded = value.encode();
+
+        let decoded = Generated::decode(&mut &encoded[..]).unwrap();
+        assert_eq!(decoded.encode(), encoded);
+
+        let decoded = Generated::decode_as_type(&mut &encoded[..], type_id, &registry).unwrap();
+        assert_eq!(decoded.encode_as_type(type_id, &registry).unwrap(), encoded);
+    }
+}
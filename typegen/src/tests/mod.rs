@@ -8,16 +8,24 @@ use syn::parse_quote;
 use crate::{
     tests::utils::{subxt_settings, Testgen},
     typegen::{
-        error::SettingsValidationError,
-        settings::{AllocCratePath, TypeGeneratorSettings},
+        derive_inference::DropReason,
+        error::{SettingsValidationError, TypeSubstitutionErrorKind},
+        settings::{
+            derives::{DerivableTrait, TypeSelector},
+            naming::{KeywordEscape, NameCase, NamingPolicy},
+            substitutes::absolute_path,
+            AllocCratePath, ModuleLayout, TypeGeneratorSettings,
+        },
         validation::{
             similar_type_paths_in_registry, validate_substitutes_and_derives_against_registry,
         },
     },
     utils::ensure_unique_type_paths,
-    DerivesRegistry, TypeSubstitutes, TypegenError,
+    DerivesRegistry, TypeGenerator, TypeSubstitutes, TypegenError,
 };
 
+mod codec_impls;
+mod serde_json_compat;
 mod utils;
 
 // // Dev note: It would be great to get this test passing, but the current type equality check
@@ -283,6 +291,80 @@ fn substitutes_with_generics() {
     }
 }
 
+#[test]
+fn pattern_substitutes() {
+    mod per_things {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Perbill(pub u32);
+
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Percent(pub u8);
+
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Permill(pub u32);
+    }
+
+    mod bounded_vec {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct BoundedVec<T>(pub Vec<T>);
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A {
+        perbill: per_things::Perbill,
+        percent: per_things::Percent,
+        permill: per_things::Permill,
+        names: bounded_vec::BoundedVec<u8>,
+    }
+
+    let settings = TypeGeneratorSettings::new()
+        .substitute_pattern("**::per_things::Per*", parse_quote!(::my_crate::PerThing))
+        // Later patterns take precedence over earlier ones:
+        .substitute_pattern(
+            "scale_typegen::**::Perc*",
+            parse_quote!(::my_crate::Percent),
+        )
+        // Exact paths take precedence over patterns:
+        .substitute(
+            parse_quote!(scale_typegen::tests::per_things::Permill),
+            parse_quote!(::my_crate::Permill),
+        )
+        .substitute_pattern(
+            "scale_typegen::*::bounded_vec::BoundedVec<T>",
+            parse_quote!(::std::vec::Vec<T>),
+        );
+    let code = Testgen::new().with::<A>().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+            pub struct A {
+                pub perbill: ::my_crate::PerThing,
+                pub percent: ::my_crate::Percent,
+                pub permill: ::my_crate::Permill,
+                pub names: ::std::vec::Vec<::core::primitive::u8>,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    let mut substitutes = TypeSubstitutes::new();
+    let err = substitutes
+        .insert_pattern(
+            "a::Per*<T::Item>",
+            absolute_path(parse_quote!(::b::C)).unwrap(),
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.kind,
+        TypeSubstitutionErrorKind::InvalidFromType
+    ));
+}
+
 #[test]
 fn substitutes_and_derives() {
     // set up settings
@@ -423,6 +505,154 @@ fn generate_struct_with_primitives() {
     assert_eq!(code.to_string(), expected_code.to_string());
 }
 
+#[test]
+fn generate_256_bit_primitives() {
+    struct U256;
+    impl TypeInfo for U256 {
+        type Identity = Self;
+        fn type_info() -> scale_info::Type {
+            scale_info::TypeDefPrimitive::U256.into()
+        }
+    }
+    struct I256;
+    impl TypeInfo for I256 {
+        type Identity = Self;
+        fn type_info() -> scale_info::Type {
+            scale_info::TypeDefPrimitive::I256.into()
+        }
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        a: U256,
+        b: I256,
+    }
+
+    // Without a type configured for U256, generation should fail with an error instead of panicking.
+    let err = Testgen::new()
+        .with::<S>()
+        .try_gen_tests_mod(TypeGeneratorSettings::new(), false)
+        .unwrap_err();
+    assert!(matches!(err, TypegenError::U256TypeNone));
+
+    let err = Testgen::new()
+        .with::<S>()
+        .try_gen_tests_mod(
+            TypeGeneratorSettings::new().u256_type(parse_quote!([::core::primitive::u8; 32])),
+            false,
+        )
+        .unwrap_err();
+    assert!(matches!(err, TypegenError::I256TypeNone));
+
+    let settings = TypeGeneratorSettings::new()
+        .u256_type(parse_quote!(::primitive_types::U256))
+        .i256_type(parse_quote!([::core::primitive::u8; 32]));
+    let code = Testgen::new().with::<S>().gen_tests_mod(settings);
+
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub struct S {
+                pub a: ::primitive_types::U256,
+                pub b: [::core::primitive::u8; 32],
+            }
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn render_256_bit_primitive_type_paths() {
+    use crate::typegen::{
+        ir::ToTokensWithSettings,
+        type_path::{TypePath, TypePathType},
+    };
+
+    let u256 = TypePath::from_type(TypePathType::Primitive {
+        def: scale_info::TypeDefPrimitive::U256,
+    });
+
+    // Constructing the type path by hand must not panic. There is no default type for it, so the generated code
+    // does not compile. The types from the settings are only used by the `MappedPrimitive`s of the generator.
+    let settings = TypeGeneratorSettings::new().u256_type(parse_quote!(::primitive_types::U256));
+    let code = u256.to_token_stream(&settings);
+    assert_eq!(
+        code.to_string(),
+        quote!(::core::compile_error!(
+            "`U256` is no rust primitive, resolve it to a `MappedPrimitive`"
+        ))
+        .to_string()
+    );
+}
+
+#[test]
+fn unknown_prelude_types() {
+    struct Duration;
+    impl TypeInfo for Duration {
+        type Identity = Self;
+        fn type_info() -> scale_info::Type {
+            scale_info::Type::builder()
+                .path(scale_info::Path::from_segments(["Duration"]).unwrap())
+                .composite(
+                    scale_info::build::Fields::named()
+                        .field(|f| f.ty::<u64>().name("secs").type_name("u64"))
+                        .field(|f| f.ty::<u32>().name("nanos").type_name("u32")),
+                )
+        }
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        timeout: Duration,
+        retries: Option<u8>,
+    }
+
+    // Single segment paths that are not known prelude types should be reported with their type id.
+    let err = Testgen::new()
+        .with::<S>()
+        .try_gen_tests_mod(TypeGeneratorSettings::new(), false)
+        .unwrap_err();
+    let TypegenError::UnknownPreludeType { type_id, name } = err else {
+        panic!("expected UnknownPreludeType error, got: {err}");
+    };
+    assert_eq!(type_id, Some(1));
+    assert_eq!(name, "Duration");
+
+    // Resolving a path on its own reports unknown prelude types as well.
+    let registry = scale_info::PortableRegistry::from(scale_info::Registry::new());
+    let settings = TypeGeneratorSettings::new();
+    let path = scale_info::Path::from_segments_unchecked(["Duration".to_string()]);
+    let err = TypeGenerator::new(&registry, &settings)
+        .type_path_maybe_with_substitutes(&path, &[])
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TypegenError::UnknownPreludeType { type_id: None, name } if name == "Duration"
+    ));
+
+    let settings = TypeGeneratorSettings::new()
+        .prelude_type("Duration", parse_quote!(::core::time::Duration))
+        .prelude_type("Option", parse_quote!(::my_crate::MyOption));
+    let code = Testgen::new().with::<S>().gen_tests_mod(settings);
+
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub struct S {
+                pub timeout: ::core::time::Duration,
+                pub retries: ::my_crate::MyOption<::core::primitive::u8>,
+            }
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
 #[test]
 fn generate_struct_with_a_struct_field() {
     #[allow(unused)]
@@ -1155,6 +1385,320 @@ fn modules() {
     assert_eq!(code.to_string(), expected_code.to_string());
 }
 
+#[test]
+fn modules_to_files() {
+    mod m {
+        pub mod a {
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct Foo;
+
+            pub mod b {
+                #[allow(unused)]
+                #[derive(scale_info::TypeInfo)]
+                pub struct Bar {
+                    a: super::Foo,
+                }
+            }
+        }
+    }
+
+    let registry = Testgen::new()
+        .with::<m::a::b::Bar>()
+        .into_portable_registry();
+    let settings = TypeGeneratorSettings::new();
+    let files = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod()
+        .unwrap()
+        .to_files(&settings)
+        .unwrap();
+
+    let paths = files
+        .keys()
+        .map(|p| p.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "types/mod.rs",
+            "types/scale_typegen/mod.rs",
+            "types/scale_typegen/tests/m/a/b/mod.rs",
+            "types/scale_typegen/tests/m/a/mod.rs",
+            "types/scale_typegen/tests/m/mod.rs",
+            "types/scale_typegen/tests/mod.rs",
+        ]
+    );
+
+    assert_eq!(
+        files[std::path::Path::new("types/mod.rs")],
+        "use super::types;\npub mod scale_typegen;\n"
+    );
+    assert_eq!(
+        files[std::path::Path::new("types/scale_typegen/tests/m/a/mod.rs")],
+        "use super::types;\npub mod b;\npub struct Foo;\n"
+    );
+    assert_eq!(
+        files[std::path::Path::new("types/scale_typegen/tests/m/a/b/mod.rs")],
+        "use super::types;\npub struct Bar {\n    pub a: types::scale_typegen::tests::m::a::Foo,\n}\n"
+    );
+}
+
+#[test]
+fn custom_type_emitter() {
+    use crate::typegen::ir::{
+        emitter::TypeEmitter,
+        module_ir::ModuleIR,
+        type_ir::{CompositeFieldIR, CompositeIR, EnumIR, TypeIR},
+        ToTokensWithSettings,
+    };
+    use proc_macro2::{Ident, TokenStream};
+
+    struct Emitter;
+    impl TypeEmitter for Emitter {
+        fn emit_module(&self, module: &ModuleIR, default: TokenStream) -> TokenStream {
+            let name = module.ident();
+            quote!(#[doc = stringify!(#name)] #default)
+        }
+
+        fn emit_struct(
+            &self,
+            _: &TypeIR,
+            composite: &CompositeIR,
+            default: TokenStream,
+        ) -> TokenStream {
+            let name = &composite.name;
+            quote!(#default impl #name {})
+        }
+
+        fn emit_enum(&self, _: &TypeIR, enum_ir: &EnumIR, default: TokenStream) -> TokenStream {
+            let name = &enum_ir.name;
+            quote!(#default impl #name {})
+        }
+
+        fn emit_variant(
+            &self,
+            _: &TypeIR,
+            index: u8,
+            _: &CompositeIR,
+            default: TokenStream,
+        ) -> TokenStream {
+            quote!(#[variant(#index)] #default)
+        }
+
+        fn emit_field(
+            &self,
+            _: &TypeIR,
+            variant: Option<&CompositeIR>,
+            name: Option<&Ident>,
+            _: &CompositeFieldIR,
+            default: TokenStream,
+        ) -> TokenStream {
+            let default = match variant {
+                Some(variant) => {
+                    let variant = &variant.name;
+                    quote!(#[in_variant(#variant)] #default)
+                }
+                None => default,
+            };
+            match name {
+                Some(name) => quote!(#[named(#name)] #default),
+                None => quote!(#[unnamed] #default),
+            }
+        }
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        a: u8,
+        e: E,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E {
+        A(bool),
+        B { c: u16 },
+    }
+
+    let registry = Testgen::new().with::<S>().into_portable_registry();
+    let settings = TypeGeneratorSettings::new();
+    let module = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod()
+        .unwrap();
+    let code = module
+        .children
+        .values()
+        .next()
+        .unwrap()
+        .to_token_stream_with_emitter(&settings, &Emitter);
+
+    let expected_code = quote! {
+        #[doc = stringify!(scale_typegen)]
+        pub mod scale_typegen {
+            use super::types;
+            #[doc = stringify!(tests)]
+            pub mod tests {
+                use super::types;
+                pub enum E {
+                    #[variant(0u8)]
+                    A(#[unnamed] #[in_variant(A)] ::core::primitive::bool,),
+                    #[variant(1u8)]
+                    B { #[named(c)] #[in_variant(B)] c: ::core::primitive::u16, },
+                }
+                impl E {}
+                pub struct S {
+                    #[named(a)] pub a: ::core::primitive::u8,
+                    #[named(e)] pub e: types::scale_typegen::tests::E,
+                }
+                impl S {}
+            }
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    // The default output is not affected by custom emitters:
+    let default_code = module
+        .children
+        .values()
+        .next()
+        .unwrap()
+        .to_token_stream(&settings);
+    assert!(!default_code.to_string().contains("impl"));
+}
+
+#[test]
+fn generate_typescript() {
+    use crate::typegen::typescript::TypeScriptSettings;
+
+    #[allow(unused, non_snake_case)]
+    #[derive(TypeInfo)]
+    struct S<T> {
+        r#type: Option<u8>,
+        e: E,
+        items: Vec<T>,
+        pair: (bool, String),
+        id: Id,
+        isValid: bool,
+    }
+
+    #[allow(unused, non_camel_case_types)]
+    #[derive(TypeInfo)]
+    enum E {
+        A,
+        B(u64, [u8; 4]),
+        C { c: u128 },
+        transfer_all,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Id([u8; 32]);
+
+    let registry = Testgen::new().with::<S<u32>>().into_portable_registry();
+    let settings = TypeGeneratorSettings::new().substitute(
+        parse_quote!(scale_typegen::tests::Id),
+        parse_quote!(::my_crate::AccountId),
+    );
+    let ts_settings =
+        TypeScriptSettings::new().external_type(parse_quote!(::my_crate::AccountId), "string");
+    let code = TypeGenerator::new(&registry, &settings)
+        .generate_typescript(&ts_settings)
+        .unwrap();
+
+    let expected_code = r#"export namespace types {
+    export namespace scale_typegen {
+        export namespace tests {
+            export type E =
+                | { name: "A"; values: [] }
+                | { name: "B"; values: [number, number[]] }
+                | { name: "C"; values: { c: number } }
+                | { name: "transfer_all"; values: [] };
+            export interface S<_0> {
+                type: Option<number>;
+                e: types.scale_typegen.tests.E;
+                items: _0[];
+                pair: [boolean, string];
+                id: string;
+                isValid: boolean;
+            }
+        }
+    }
+}
+"#;
+    let (prelude, namespaces) = code.split_once("\n\n").unwrap();
+    assert!(prelude.starts_with("export type Option<T> ="));
+    assert_eq!(namespaces, expected_code);
+
+    // The JSON representation uses the names from the type registry, regardless of the naming policy.
+    let settings = settings.naming(NamingPolicy {
+        keyword_escape: KeywordEscape::Suffix("_".into()),
+        field_case: NameCase::Snake,
+        variant_case: NameCase::UpperCamel,
+        serde_rename: false,
+    });
+    let renamed_code = TypeGenerator::new(&registry, &settings)
+        .generate_typescript(&ts_settings)
+        .unwrap();
+    assert_eq!(renamed_code, code);
+}
+
+#[test]
+fn generate_typescript_without_rust_paths() {
+    use crate::typegen::typescript::TypeScriptSettings;
+    use bitvec::{order::Lsb0, vec::BitVec};
+
+    struct U256;
+    impl TypeInfo for U256 {
+        type Identity = Self;
+        fn type_info() -> scale_info::Type {
+            scale_info::TypeDefPrimitive::U256.into()
+        }
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        #[codec(compact)]
+        amount: u64,
+        flags: BitVec<u8, Lsb0>,
+        balance: U256,
+    }
+
+    // The paths for compact types, bit sequences and 256 bit integers are only needed for rust code.
+    let registry = Testgen::new().with::<S>().into_portable_registry();
+    let settings = TypeGeneratorSettings::new();
+    let code = TypeGenerator::new(&registry, &settings)
+        .generate_typescript(&TypeScriptSettings::new())
+        .unwrap();
+
+    let expected_code = r#"export namespace types {
+    export namespace bitvec {
+        export namespace order {
+            export type Lsb0 = [];
+        }
+    }
+    export namespace scale_typegen {
+        export namespace tests {
+            export interface S {
+                amount: number;
+                flags: BitSequence;
+                balance: number[];
+            }
+        }
+    }
+}
+"#;
+    let (_, namespaces) = code.split_once("\n\n").unwrap();
+    assert_eq!(namespaces, expected_code);
+
+    let err = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod()
+        .unwrap_err();
+    assert!(matches!(err, TypegenError::CompactPathNone));
+}
+
 #[test]
 fn dont_force_struct_names_camel_case() {
     #[allow(unused)]
@@ -1443,19 +1987,390 @@ fn apply_derives() {
     assert_eq!(code.to_string(), expected_code.to_string());
 }
 
-/// By default a BTreeMap would be replaced by a KeyedVec.
-/// This test demonstrates that it does not happen if we opt out of default type substitutes.
 #[test]
-fn apply_custom_substitutes() {
-    use std::collections::BTreeMap;
+fn apply_derives_for_selected_types() {
+    mod pallet {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Event {
+            Deposited(Amount),
+        }
+
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Amount(u128);
+    }
 
     #[allow(unused)]
     #[derive(TypeInfo)]
-    struct S {
-        map: BTreeMap<u8, u8>,
+    struct A {
+        #[codec(compact)]
+        nonce: u32,
+        event: pallet::Event,
     }
 
-    let substitutes = TypeSubstitutes::new();
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct B;
+
+    let mut derives = DerivesRegistry::new();
+    derives.add_derives_for_all(vec![parse_quote!(Clone)]);
+    derives.add_derives_for_selected("**::pallet::Event", vec![parse_quote!(Hash)], true);
+    derives.add_derives_for_selected(TypeSelector::Structs, vec![parse_quote!(Default)], false);
+    derives.add_derives_for_selected(
+        TypeSelector::HasCompactField,
+        vec![parse_quote!(Copy)],
+        false,
+    );
+    derives.add_attributes_for_selected(
+        TypeSelector::Enums,
+        vec![parse_quote!(#[some_attribute])],
+        false,
+    );
+    // Derives for an exact path are merged with the derives from selectors:
+    derives.add_derives_for(
+        parse_quote!(scale_typegen::tests::B),
+        vec![parse_quote!(Eq)],
+        false,
+    );
+    // Also if they are added recursively. The exact path wins for an attribute with the same path:
+    derives.add_derives_for(
+        parse_quote!(scale_typegen::tests::pallet::Event),
+        vec![parse_quote!(PartialEq)],
+        true,
+    );
+    derives.add_attributes_for(
+        parse_quote!(scale_typegen::tests::pallet::Event),
+        vec![parse_quote!(#[some_attribute(exact)])],
+        true,
+    );
+
+    let settings = TypeGeneratorSettings {
+        derives,
+        ..subxt_settings()
+    };
+    let code = Testgen::new()
+        .with::<A>()
+        .with::<B>()
+        .gen_tests_mod(settings);
+
+    let expected_code = quote! {
+        pub mod tests {
+            use super::root;
+
+            pub mod pallet {
+                use super::root;
+
+                #[derive(::subxt_path::ext::codec::CompactAs, Clone, Default, Hash, PartialEq)]
+                #[some_attribute(exact)]
+                pub struct Amount(pub ::core::primitive::u128,);
+
+                #[derive(Clone, Hash, PartialEq)]
+                #[some_attribute(exact)]
+                pub enum Event {
+                    #[codec(index = 0)]
+                    Deposited(root::scale_typegen::tests::pallet::Amount,),
+                }
+            }
+
+            #[derive(Clone, Copy, Default)]
+            pub struct A {
+                #[codec(compact)]
+                pub nonce: ::core::primitive::u32,
+                pub event: root::scale_typegen::tests::pallet::Event,
+            }
+
+            #[derive(Clone, Default, Eq)]
+            pub struct B;
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn remove_derives() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A(B);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct B(C);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct C;
+
+    let mut derives = DerivesRegistry::new();
+    derives.add_derives_for_all(vec![
+        parse_quote!(Clone),
+        parse_quote!(Eq),
+        parse_quote!(Hash),
+    ]);
+    derives.add_derives_for(
+        parse_quote!(scale_typegen::tests::C),
+        vec![parse_quote!(Ord)],
+        false,
+    );
+
+    derives.remove_derives_for(
+        parse_quote!(scale_typegen::tests::A),
+        vec![parse_quote!(Hash)],
+        false,
+    );
+    derives.remove_derives_for(
+        parse_quote!(scale_typegen::tests::B),
+        vec![parse_quote!(Eq), parse_quote!(Ord)],
+        true,
+    );
+
+    let settings = TypeGeneratorSettings {
+        derives,
+        ..subxt_settings()
+    };
+    let code = Testgen::new().with::<A>().gen_tests_mod(settings);
+
+    let expected_code = quote! {
+        pub mod tests {
+            use super::root;
+
+            #[derive(Clone, Eq)]
+            pub struct A(pub root :: scale_typegen :: tests :: B,);
+
+            #[derive(Clone, Hash)]
+            pub struct B(pub root :: scale_typegen :: tests :: C,);
+
+            #[derive(Clone, Hash)]
+            pub struct C;
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn remove_derives_with_qualified_paths() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A;
+
+    let mut derives = DerivesRegistry::new();
+    derives.add_derives_for_all(vec![
+        parse_quote!(::core::clone::Clone),
+        parse_quote!(::core::cmp::Eq),
+        parse_quote!(Hash),
+    ]);
+    derives.remove_derives_for(
+        parse_quote!(scale_typegen::tests::A),
+        vec![parse_quote!(Eq), parse_quote!(::core::hash::Hash)],
+        false,
+    );
+
+    let settings = TypeGeneratorSettings {
+        derives,
+        ..subxt_settings()
+    };
+    let code = Testgen::new().with::<A>().gen_tests_mod(settings);
+
+    let expected_code = quote! {
+        pub mod tests {
+            use super::root;
+
+            #[derive(::core::clone::Clone)]
+            pub struct A;
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn infer_derives() {
+    mod ext {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct AccountId([u8; 32]);
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A {
+        id: u32,
+        b: B,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct B {
+        data: Vec<u8>,
+        who: ext::AccountId,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E {
+        X(Option<u32>),
+    }
+
+    let settings = TypeGeneratorSettings::new()
+        .add_derives_for_all([
+            parse_quote!(Clone),
+            parse_quote!(Copy),
+            parse_quote!(PartialEq),
+            parse_quote!(Eq),
+            parse_quote!(Hash),
+            parse_quote!(Default),
+        ])
+        .substitute(
+            parse_quote!(scale_typegen::tests::ext::AccountId),
+            parse_quote!(::ext::AccountId),
+        )
+        .external_type_traits(
+            parse_quote!(::ext::AccountId),
+            [DerivableTrait::Eq, DerivableTrait::Hash],
+        )
+        .infer_derives();
+
+    let code = Testgen::new()
+        .with::<A>()
+        .with::<E>()
+        .gen_tests_mod(settings.clone());
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            #[derive(Clone, Eq, Hash, PartialEq)]
+            pub struct A {
+                pub id: ::core::primitive::u32,
+                pub b: types::scale_typegen::tests::B,
+            }
+
+            #[derive(Clone, Eq, Hash, PartialEq)]
+            pub struct B {
+                pub data: ::std::vec::Vec<::core::primitive::u8>,
+                pub who: ::ext::AccountId,
+            }
+
+            #[derive(Clone, Copy, Eq, Hash, PartialEq)]
+            pub enum E {
+                X(::core::option::Option<::core::primitive::u32>,),
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    let registry = Testgen::new()
+        .with::<A>()
+        .with::<E>()
+        .into_portable_registry();
+    let (_, dropped) = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod_with_dropped_derives()
+        .unwrap();
+    let dropped = dropped
+        .iter()
+        .map(|d| {
+            let derive = &d.derive;
+            let reason = match &d.reason {
+                DropReason::Field(ty) => quote!(#ty).to_string(),
+                DropReason::Supertrait(t) => format!("{t:?}"),
+                DropReason::DefaultOnEnum => "enum".to_string(),
+            };
+            (d.type_path.to_string(), quote!(#derive).to_string(), reason)
+        })
+        .collect::<Vec<_>>();
+    let expected_dropped = [
+        (
+            "scale_typegen::tests::A",
+            "Copy",
+            "types :: scale_typegen :: tests :: B",
+        ),
+        (
+            "scale_typegen::tests::A",
+            "Default",
+            "types :: scale_typegen :: tests :: B",
+        ),
+        (
+            "scale_typegen::tests::B",
+            "Copy",
+            ":: std :: vec :: Vec < :: core :: primitive :: u8 >",
+        ),
+        ("scale_typegen::tests::B", "Default", ":: ext :: AccountId"),
+        ("scale_typegen::tests::E", "Default", "enum"),
+    ]
+    .map(|(a, b, c)| (a.to_string(), b.to_string(), c.to_string()));
+    assert_eq!(dropped, expected_dropped);
+}
+
+#[test]
+fn infer_derives_with_supertraits_and_mapped_primitives() {
+    struct U256;
+    impl TypeInfo for U256 {
+        type Identity = Self;
+        fn type_info() -> scale_info::Type {
+            scale_info::TypeDefPrimitive::U256.into()
+        }
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A {
+        a: U256,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct B(u8);
+
+    // `Copy` needs `Clone` and `Eq` needs `PartialEq`, `Ord` is dropped together with `Eq`.
+    let mut settings = TypeGeneratorSettings::new()
+        .u256_type(parse_quote!([::core::primitive::u8; 32]))
+        .add_derives_for_all([
+            parse_quote!(Copy),
+            parse_quote!(Eq),
+            parse_quote!(PartialOrd),
+            parse_quote!(Ord),
+            parse_quote!(Default),
+        ])
+        .infer_derives();
+    settings.derives.add_derives_for(
+        parse_quote!(scale_typegen::tests::A),
+        [parse_quote!(Clone), parse_quote!(PartialEq)],
+        false,
+    );
+    let code = Testgen::new()
+        .with::<A>()
+        .with::<B>()
+        .gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            #[derive(Clone, Copy, Default, Eq, Ord, PartialEq, PartialOrd)]
+            pub struct A {
+                pub a: [::core::primitive::u8; 32],
+            }
+
+            #[derive(Default)]
+            pub struct B(pub ::core::primitive::u8,);
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+/// By default a BTreeMap would be replaced by a KeyedVec.
+/// This test demonstrates that it does not happen if we opt out of default type substitutes.
+#[test]
+fn apply_custom_substitutes() {
+    use std::collections::BTreeMap;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        map: BTreeMap<u8, u8>,
+    }
+
+    let substitutes = TypeSubstitutes::new();
 
     let settings = TypeGeneratorSettings {
         substitutes,
@@ -1818,3 +2733,828 @@ fn alloc_crate_path_replacement() {
     assert_eq!(std_code.to_string(), expected_std_code.to_string());
     assert_eq!(no_std_code.to_string(), expected_no_std_code.to_string());
 }
+
+#[test]
+fn explicit_codec_impls() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S {
+        a: u8,
+        #[codec(compact)]
+        b: u64,
+        e: E,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E {
+        #[codec(index = 3)]
+        A,
+        B(bool),
+    }
+
+    let settings = TypeGeneratorSettings::new()
+        .compact_type_path(parse_quote!(::codec::Compact))
+        .insert_codec_attributes()
+        .explicit_codec_impls(parse_quote!(::codec));
+    let code = Testgen::new().with::<S>().gen(settings);
+
+    // No `#[codec(...)]` attributes are inserted, because they need the derive macros.
+    let expected_code = quote! {
+        pub mod types {
+            use super::types;
+            pub mod scale_typegen {
+                use super::types;
+                pub mod tests {
+                    use super::types;
+                    pub enum E {
+                        A,
+                        B(::core::primitive::bool,),
+                    }
+                    impl ::codec::Encode for E {
+                        fn encode_to<__CodecOutput: ::codec::Output + ?::core::marker::Sized>(
+                            &self,
+                            __codec_dest: &mut __CodecOutput,
+                        ) {
+                            match self {
+                                Self::A => {
+                                    ::codec::Output::push_byte(__codec_dest, 3u8);
+                                }
+                                Self::B(__binding_0,) => {
+                                    ::codec::Output::push_byte(__codec_dest, 1u8);
+                                    ::codec::Encode::encode_to(__binding_0, __codec_dest);
+                                }
+                            }
+                        }
+                    }
+                    impl ::codec::EncodeLike for E {}
+                    impl ::codec::Decode for E {
+                        fn decode<__CodecInput: ::codec::Input>(
+                            __codec_input: &mut __CodecInput,
+                        ) -> ::core::result::Result<Self, ::codec::Error> {
+                            match ::codec::Input::read_byte(__codec_input)
+                                .map_err(|e| e.chain("Could not decode `E`, failed to read variant byte"))?
+                            {
+                                3u8 => ::core::result::Result::Ok(Self::A),
+                                1u8 => ::core::result::Result::Ok(Self::B(
+                                    <::core::primitive::bool as ::codec::Decode>::decode(__codec_input)
+                                        .map_err(|e| e.chain("Could not decode `E::B.0`"))?,
+                                )),
+                                _ => ::core::result::Result::Err(
+                                    "Could not decode `E`, variant doesn't exist".into()
+                                ),
+                            }
+                        }
+                    }
+                    pub struct S {
+                        pub a: ::core::primitive::u8,
+                        pub b: ::core::primitive::u64,
+                        pub e: types::scale_typegen::tests::E,
+                    }
+                    impl ::codec::Encode for S {
+                        fn encode_to<__CodecOutput: ::codec::Output + ?::core::marker::Sized>(
+                            &self,
+                            __codec_dest: &mut __CodecOutput,
+                        ) {
+                            ::codec::Encode::encode_to(&self.a, __codec_dest);
+                            ::codec::Encode::encode_to(
+                                &<<::core::primitive::u64 as ::codec::HasCompact>::Type as ::codec::EncodeAsRef<
+                                    '_,
+                                    ::core::primitive::u64
+                                >>::RefType::from(&self.b),
+                                __codec_dest,
+                            );
+                            ::codec::Encode::encode_to(&self.e, __codec_dest);
+                        }
+                    }
+                    impl ::codec::EncodeLike for S {}
+                    impl ::codec::Decode for S {
+                        fn decode<__CodecInput: ::codec::Input>(
+                            __codec_input: &mut __CodecInput,
+                        ) -> ::core::result::Result<Self, ::codec::Error> {
+                            ::core::result::Result::Ok(Self {
+                                a: <::core::primitive::u8 as ::codec::Decode>::decode(__codec_input)
+                                    .map_err(|e| e.chain("Could not decode `S::a`"))?,
+                                b: <<::core::primitive::u64 as ::codec::HasCompact>::Type as ::codec::Decode>::decode(
+                                    __codec_input
+                                )
+                                .map_err(|e| e.chain("Could not decode `S::b`"))?
+                                .into(),
+                                e: <types::scale_typegen::tests::E as ::codec::Decode>::decode(__codec_input)
+                                    .map_err(|e| e.chain("Could not decode `S::e`"))?,
+                            })
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn generate_only_types_reachable_from_roots() {
+    mod pallet_a {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Call {
+            Transfer(Balance),
+        }
+
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Balance(u128);
+    }
+
+    mod pallet_b {
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub enum Call {
+            Remark(Remark),
+        }
+
+        #[allow(unused)]
+        #[derive(scale_info::TypeInfo)]
+        pub struct Remark(Vec<u8>);
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Unused;
+
+    let testgen = || {
+        Testgen::new()
+            .with::<pallet_a::Call>()
+            .with::<pallet_b::Call>()
+            .with::<Unused>()
+    };
+
+    // Roots by path pattern:
+    let settings = TypeGeneratorSettings::new().root_path("**::pallet_a::Call");
+    let code = testgen().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+            pub mod pallet_a {
+                use super::types;
+                pub struct Balance(pub ::core::primitive::u128,);
+                pub enum Call {
+                    Transfer(types::scale_typegen::tests::pallet_a::Balance,),
+                }
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    // Roots by type id:
+    let registry = testgen().into_portable_registry();
+    let remark_id = registry
+        .types
+        .iter()
+        .find(|ty| ty.ty.path.ident().as_deref() == Some("Remark"))
+        .unwrap()
+        .id;
+    let settings = TypeGeneratorSettings::new().root_type_id(remark_id);
+    let code = testgen().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+            pub mod pallet_b {
+                use super::types;
+                pub struct Remark(pub ::std::vec::Vec<::core::primitive::u8>,);
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    // Unknown type ids are an error:
+    let settings = TypeGeneratorSettings::new().root_type_id(1000);
+    let err = testgen().try_gen_tests_mod(settings, false).unwrap_err();
+    assert!(matches!(err, TypegenError::TypeNotFound(1000)));
+
+    // Path patterns that match no type are an error:
+    let settings = TypeGeneratorSettings::new().root_path("**::pallet_c::Call");
+    let err = testgen().try_gen_tests_mod(settings, false).unwrap_err();
+    assert!(matches!(err, TypegenError::RootNotFound(pattern) if pattern == "**::pallet_c::Call"));
+}
+
+#[test]
+fn reachable_types_stop_at_substitutes() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum Call {
+        Store(Wrapper<Item>),
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Wrapper<T> {
+        inner: Inner,
+        item: T,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Inner;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Item;
+
+    // `Inner` is only reachable through the fields of the substituted `Wrapper`, so it is not generated.
+    // `Item` is passed to the substitute as a type param, so it is still generated.
+    let settings = TypeGeneratorSettings::new()
+        .root_path("**::Call")
+        .substitute(
+            parse_quote!(scale_typegen::tests::Wrapper<T>),
+            parse_quote!(::std::boxed::Box<T>),
+        );
+    let code = Testgen::new().with::<Call>().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+            pub enum Call {
+                Store(::std::boxed::Box<types::scale_typegen::tests::Item>,),
+            }
+            pub struct Item;
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn serde_json_compat() {
+    use bitvec::{order::Lsb0, vec::BitVec};
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Unit;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Newtype(u32);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Bits(BitVec<u8, Lsb0>);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Phantom<T>(core::marker::PhantomData<T>);
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum E {
+        A,
+        B(u8),
+        C {
+            bits: BitVec<u8, Lsb0>,
+            p: Phantom<u8>,
+            o: Option<u8>,
+        },
+    }
+
+    let settings = TypeGeneratorSettings::new()
+        .decoded_bits_type_path(parse_quote!(::bits::DecodedBits))
+        .add_derives_for_all([parse_quote!(::serde::Serialize)])
+        .serde_json_compat(parse_quote!(::serde));
+    let code = Testgen::new()
+        .with::<Unit>()
+        .with::<Newtype>()
+        .with::<Bits>()
+        .with::<E>()
+        .gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            #[derive(::serde::Serialize)]
+            pub struct Bits(
+                #[serde(with = "types::__serde_json_compat::newtype_bits")]
+                pub ::bits::DecodedBits<::core::primitive::u8, types::bitvec::order::Lsb0>,
+            );
+
+            #[derive(::serde::Serialize)]
+            #[serde(tag = "name", content = "values")]
+            pub enum E {
+                #[serde(with = "types::__serde_json_compat::unit")]
+                A,
+                B(#[serde(with = "types::__serde_json_compat::newtype")] ::core::primitive::u8,),
+                C {
+                    #[serde(with = "types::__serde_json_compat::bits")]
+                    bits: ::bits::DecodedBits<::core::primitive::u8, types::bitvec::order::Lsb0>,
+                    p: types::scale_typegen::tests::Phantom<::core::primitive::u8>,
+                    o: types::__serde_json_compat::Option<::core::primitive::u8>,
+                },
+            }
+
+            #[derive(::serde::Serialize)]
+            pub struct Newtype(
+                #[serde(with = "types::__serde_json_compat::newtype")] pub ::core::primitive::u32,
+            );
+
+            #[derive(::serde::Serialize)]
+            pub struct Phantom<_0>(
+                #[serde(with = "types::__serde_json_compat::phantom")] pub ::core::marker::PhantomData<_0>
+            );
+
+            pub struct Unit;
+            impl ::serde::Serialize for Unit {
+                fn serialize<S: ::serde::Serializer>(
+                    &self,
+                    serializer: S
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    types::__serde_json_compat::unit::serialize(serializer)
+                }
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn naming_policy() {
+    #[allow(unused, non_snake_case)]
+    #[derive(TypeInfo)]
+    struct S {
+        r#type: u8,
+        fooBar: u8,
+    }
+
+    #[allow(unused, non_camel_case_types)]
+    #[derive(TypeInfo)]
+    enum E {
+        transfer_all { r#move: u8 },
+        Remark,
+    }
+
+    // Keywords are escaped with raw identifiers by default.
+    let code = Testgen::new()
+        .with::<S>()
+        .with::<E>()
+        .gen_tests_mod(Default::default());
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub enum E {
+                transfer_all { r#move: ::core::primitive::u8, },
+                Remark,
+            }
+
+            pub struct S {
+                pub r#type: ::core::primitive::u8,
+                pub fooBar: ::core::primitive::u8,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    let settings = TypeGeneratorSettings::new().naming(NamingPolicy {
+        keyword_escape: KeywordEscape::Suffix("_".into()),
+        field_case: NameCase::Snake,
+        variant_case: NameCase::UpperCamel,
+        serde_rename: true,
+    });
+    let code = Testgen::new()
+        .with::<S>()
+        .with::<E>()
+        .gen_tests_mod(settings.clone());
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub enum E {
+                #[serde(rename = "transfer_all")]
+                TransferAll {
+                    #[serde(rename = "r#move")]
+                    move_: ::core::primitive::u8,
+                },
+                Remark,
+            }
+
+            pub struct S {
+                #[serde(rename = "r#type")]
+                pub type_: ::core::primitive::u8,
+                #[serde(rename = "fooBar")]
+                pub foo_bar: ::core::primitive::u8,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    #[allow(unused, non_snake_case)]
+    #[derive(TypeInfo)]
+    struct Clash {
+        fooBar: u8,
+        foo_bar: u8,
+    }
+
+    let err = Testgen::new()
+        .with::<Clash>()
+        .try_gen_tests_mod(settings.clone(), true)
+        .unwrap_err();
+    assert!(matches!(err, TypegenError::DuplicateName { name, .. } if name == "foo_bar"));
+
+    // Raw identifiers are not renamed, so the `EncodeAsType` and `DecodeAsType` derives are kept.
+    let code = Testgen::new()
+        .with::<S>()
+        .gen_tests_mod(subxt_settings())
+        .to_string();
+    assert!(code.contains(&quote!(pub r#type: ::core::primitive::u8).to_string()));
+    assert!(code.contains(&quote!(::subxt_path::ext::scale_encode::EncodeAsType,).to_string()));
+    assert!(!code.contains("impl ::subxt_path::ext::scale_encode::EncodeAsType"));
+
+    // The derives would use the converted names, so they are replaced by explicit impls with the original names.
+    let code = Testgen::new()
+        .with::<S>()
+        .gen_tests_mod(subxt_settings().naming(settings.naming.clone()))
+        .to_string();
+    for (derive, attribute, explicit_impl) in [
+        (
+            quote!(::subxt_path::ext::scale_encode::EncodeAsType,),
+            "# [encode_as_type",
+            quote!(impl ::subxt_path::ext::scale_encode::EncodeAsType for S),
+        ),
+        (
+            quote!(::subxt_path::ext::scale_decode::DecodeAsType,),
+            "# [decode_as_type",
+            quote!(impl ::subxt_path::ext::scale_decode::IntoVisitor for S),
+        ),
+    ] {
+        assert!(!code.contains(&derive.to_string()));
+        assert!(!code.contains(attribute));
+        assert!(code.contains(&explicit_impl.to_string()));
+    }
+    assert!(code.contains("\"fooBar\""));
+
+    // Derives added for a type specifically are not replaced.
+    let mut settings = subxt_settings().naming(settings.naming);
+    settings.derives.add_derives_for(
+        parse_quote!(scale_typegen::tests::S),
+        [parse_quote!(scale_encode::EncodeAsType)],
+        false,
+    );
+    let err = Testgen::new()
+        .with::<S>()
+        .try_gen_tests_mod(settings, true)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        TypegenError::RenamedWithNameMatchingDerive { name, .. } if name == "r#type"
+    ));
+}
+
+#[test]
+fn rename_types() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct A {
+        b: B,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct B(u8);
+
+    let settings = TypeGeneratorSettings::new().rename_type(
+        parse_quote!(scale_typegen::tests::B),
+        parse_quote!(common::Byte),
+    );
+    let code = Testgen::new().with::<A>().gen(settings);
+    let expected_code = quote! {
+        pub mod types {
+            use super::types;
+
+            pub mod common {
+                use super::types;
+
+                pub struct Byte(pub ::core::primitive::u8,);
+            }
+
+            pub mod scale_typegen {
+                use super::types;
+
+                pub mod tests {
+                    use super::types;
+
+                    pub struct A {
+                        pub b: types::common::Byte,
+                    }
+                }
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    // Renaming a type next to another type with the same name is an error.
+    let settings = TypeGeneratorSettings::new().rename_type(
+        parse_quote!(scale_typegen::tests::B),
+        parse_quote!(scale_typegen::tests::A),
+    );
+    let err = Testgen::new()
+        .with::<A>()
+        .try_gen_tests_mod(settings, false)
+        .unwrap_err();
+    assert!(
+        matches!(err, TypegenError::DuplicateRenamedTypePath(path) if path == "scale_typegen::tests::A")
+    );
+
+    // Empty paths and segments that are no identifiers are rejected.
+    for to in [vec![], vec!["common".to_string(), "type".to_string()]] {
+        let mut settings = TypeGeneratorSettings::new();
+        settings.renames.insert(
+            vec!["scale_typegen".into(), "tests".into(), "B".into()],
+            to.clone(),
+        );
+        let err = Testgen::new()
+            .with::<A>()
+            .try_gen_tests_mod(settings, false)
+            .unwrap_err();
+        assert!(
+            matches!(err, TypegenError::InvalidRename { from, to: err_to } if from == "scale_typegen::tests::B" && err_to == to.join("::"))
+        );
+    }
+}
+
+#[test]
+fn module_layouts() {
+    mod m {
+        pub mod a {
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct S(pub u8);
+        }
+
+        pub mod b {
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct S(pub bool);
+
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct T {
+                pub a: super::a::S,
+                pub b: S,
+            }
+        }
+    }
+
+    let code = Testgen::new()
+        .with::<m::b::T>()
+        .gen(TypeGeneratorSettings::new().module_layout(ModuleLayout::Flat));
+    let expected_code = quote! {
+        pub mod types {
+            use super::types;
+
+            pub struct AS(pub ::core::primitive::u8,);
+
+            pub struct BS(pub ::core::primitive::bool,);
+
+            pub struct T {
+                pub a: types::AS,
+                pub b: types::BS,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+
+    let settings =
+        TypeGeneratorSettings::new().module_layout(ModuleLayout::CollapseSingleChildModules);
+    let code = Testgen::new().with::<m::b::T>().gen(settings);
+    let expected_code = quote! {
+        pub mod types {
+            use super::types;
+
+            pub mod a {
+                use super::types;
+
+                pub struct S(pub ::core::primitive::u8,);
+            }
+
+            pub mod b {
+                use super::types;
+
+                pub struct S(pub ::core::primitive::bool,);
+
+                pub struct T {
+                    pub a: types::a::S,
+                    pub b: types::b::S,
+                }
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+#[allow(non_camel_case_types)]
+fn original_type_param_names() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct S<T, Balance, types> {
+        a: T,
+        b: Vec<Balance>,
+        c: types,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Wrapper {
+        s: S<u8, u16, u32>,
+    }
+
+    let settings = TypeGeneratorSettings::new().original_type_param_names();
+    let code = Testgen::new().with::<Wrapper>().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub struct S<T, Balance, _2> {
+                pub a: T,
+                pub b: ::std::vec::Vec<Balance>,
+                pub c: _2,
+            }
+
+            pub struct Wrapper {
+                pub s: types::scale_typegen::tests::S<
+                    ::core::primitive::u8,
+                    ::core::primitive::u16,
+                    ::core::primitive::u32
+                >,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn instantiation_aliases() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Header<N> {
+        number: N,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    enum MultiAddress<AccountId, AccountIndex> {
+        Id(AccountId),
+        Index(AccountIndex),
+    }
+
+    type HeaderFor = Header<u64>;
+    // Hints that are keywords or shadow the prelude are not used:
+    type Vec = Header<u16>;
+    #[allow(non_camel_case_types)]
+    type r#type = Header<u8>;
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Block {
+        header: HeaderFor,
+        old_header: Header<u32>,
+        who: MultiAddress<[u8; 32], ()>,
+        vec_header: Vec,
+        type_header: r#type,
+    }
+
+    let settings = TypeGeneratorSettings::new().instantiation_aliases();
+    let code = Testgen::new().with::<Block>().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub struct Block {
+                pub header: types::scale_typegen::tests::Header<::core::primitive::u64>,
+                pub old_header: types::scale_typegen::tests::Header<::core::primitive::u32>,
+                pub who: types::scale_typegen::tests::MultiAddress<[::core::primitive::u8; 32usize], ()>,
+                pub vec_header: types::scale_typegen::tests::Header<::core::primitive::u16>,
+                pub type_header: types::scale_typegen::tests::Header<::core::primitive::u8>,
+            }
+
+            pub struct Header<_0> {
+                pub number: _0,
+            }
+
+            pub enum MultiAddress<_0, _1> {
+                Id(_0,),
+                Index(_1,),
+            }
+
+            pub type HeaderFor = types::scale_typegen::tests::Header<::core::primitive::u64>;
+            pub type HeaderU16 = types::scale_typegen::tests::Header<::core::primitive::u16>;
+            pub type HeaderU32 = types::scale_typegen::tests::Header<::core::primitive::u32>;
+            pub type HeaderU8 = types::scale_typegen::tests::Header<::core::primitive::u8>;
+            pub type MultiAddressU8Array32Unit =
+                types::scale_typegen::tests::MultiAddress<[::core::primitive::u8; 32usize], ()>;
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn monomorphize() {
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Header<N> {
+        number: N,
+        parent: Option<Box<Header<N>>>,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Wrapper<T> {
+        inner: T,
+    }
+
+    #[allow(unused)]
+    #[derive(TypeInfo)]
+    struct Block {
+        header: Header<u32>,
+        old_header: Header<u64>,
+        wrapped: Wrapper<Header<u32>>,
+    }
+
+    let settings = TypeGeneratorSettings::new().monomorphize();
+    let code = Testgen::new().with::<Block>().gen_tests_mod(settings);
+    let expected_code = quote! {
+        pub mod tests {
+            use super::types;
+
+            pub struct Block {
+                pub header: types::scale_typegen::tests::HeaderU32,
+                pub old_header: types::scale_typegen::tests::HeaderU64,
+                pub wrapped: types::scale_typegen::tests::WrapperHeaderU32,
+            }
+
+            pub struct HeaderU32 {
+                pub number: ::core::primitive::u32,
+                pub parent: ::std::boxed::Box<::core::option::Option<types::scale_typegen::tests::HeaderU32> >,
+            }
+
+            pub struct HeaderU64 {
+                pub number: ::core::primitive::u64,
+                pub parent: ::std::boxed::Box<::core::option::Option<types::scale_typegen::tests::HeaderU64> >,
+            }
+
+            pub struct WrapperHeaderU32 {
+                pub inner: types::scale_typegen::tests::HeaderU32,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
+
+#[test]
+fn monomorphize_with_module_layout_and_inferred_derives() {
+    mod m {
+        pub mod inner {
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub enum Either<L, R> {
+                Left(L),
+                Right(R),
+            }
+
+            #[allow(unused)]
+            #[derive(scale_info::TypeInfo)]
+            pub struct Holder {
+                pub a: Either<u8, bool>,
+                pub b: Either<String, u8>,
+            }
+        }
+    }
+
+    let settings = TypeGeneratorSettings::new()
+        .monomorphize()
+        .module_layout(ModuleLayout::Flat)
+        .add_derives_for_all([parse_quote!(Clone), parse_quote!(Copy)])
+        .infer_derives();
+    let code = Testgen::new().with::<m::inner::Holder>().gen(settings);
+    let expected_code = quote! {
+        pub mod types {
+            use super::types;
+
+            #[derive(Clone)]
+            pub enum EitherStrU8 {
+                Left(::std::string::String,),
+                Right(::core::primitive::u8,),
+            }
+
+            #[derive(Clone, Copy)]
+            pub enum EitherU8Bool {
+                Left(::core::primitive::u8,),
+                Right(::core::primitive::bool,),
+            }
+
+            #[derive(Clone)]
+            pub struct Holder {
+                pub a: types::EitherU8Bool,
+                pub b: types::EitherStrU8,
+            }
+        }
+    };
+    assert_eq!(code.to_string(), expected_code.to_string());
+}
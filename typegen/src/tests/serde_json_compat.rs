@@ -0,0 +1,479 @@
+use std::marker::PhantomData;
+
+use parity_scale_codec::{Decode, Encode};
+use pretty_assertions::assert_eq;
+use proc_macro2::TokenStream;
+use scale_bits::Bits;
+use scale_info::{meta_type, PortableRegistry, Registry};
+use serde::{Deserialize, Serialize};
+use syn::parse_quote;
+
+use crate::{typegen::ir::ToTokensWithSettings, TypeGenerator, TypeGeneratorSettings};
+
+mod source {
+    use std::collections::BTreeMap;
+
+    use bitvec::{order::Lsb0, vec::BitVec};
+    use parity_scale_codec::Encode;
+    use scale_info::TypeInfo;
+
+    #[derive(TypeInfo, Encode)]
+    pub struct S {
+        pub a: u8,
+        #[codec(compact)]
+        pub b: u64,
+        pub c: u128,
+        pub e: E,
+        pub unit: Unit,
+        pub newtype: Newtype,
+        pub bits: BitVec<u8, Lsb0>,
+        pub o: Option<u16>,
+        pub m: BTreeMap<u8, bool>,
+        pub p: P<u8>,
+    }
+
+    #[derive(TypeInfo, Encode)]
+    pub enum E {
+        A,
+        B(u8),
+        C {
+            #[codec(compact)]
+            x: u32,
+            bits: BitVec<u8, Lsb0>,
+        },
+        D(BitVec<u8, Lsb0>),
+        R(Result<u8, bool>),
+    }
+
+    #[derive(TypeInfo, Encode)]
+    pub struct Unit;
+
+    #[derive(TypeInfo, Encode)]
+    pub struct Newtype(pub u32);
+
+    #[derive(TypeInfo, Encode)]
+    pub struct P<T>(pub core::marker::PhantomData<T>);
+}
+
+/// The `decoded_bits_type_path` type, which serializes as a sequence of bools like `scale_bits::Bits`.
+#[derive(Encode, Decode, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DecodedBits<Store, Order>(
+    Bits,
+    #[codec(skip)]
+    #[serde(skip)]
+    PhantomData<(Store, Order)>,
+);
+
+fn settings() -> TypeGeneratorSettings {
+    TypeGeneratorSettings::new()
+        .insert_codec_attributes()
+        .compact_type_path(parse_quote!(::parity_scale_codec::Compact))
+        .decoded_bits_type_path(parse_quote!(crate::tests::serde_json_compat::DecodedBits))
+        .add_derives_for_all([
+            parse_quote!(::parity_scale_codec::Encode),
+            parse_quote!(::parity_scale_codec::Decode),
+            parse_quote!(::serde::Serialize),
+            parse_quote!(::serde::Deserialize),
+        ])
+        .serde_json_compat(parse_quote!(::serde))
+}
+
+fn registry() -> (PortableRegistry, u32) {
+    let mut registry = Registry::new();
+    let id = registry.register_type(&meta_type::<source::S>()).id;
+    (registry.into(), id)
+}
+
+/// Emits the given generated code, so it is compiled, and keeps it as a string to compare it with the generator
+/// output.
+macro_rules! compiled {
+    ($generated:item) => {
+        #[allow(unused_imports, dead_code)]
+        $generated
+
+        const COMPILED: &str = stringify!($generated);
+    };
+}
+
+compiled! {
+    pub mod types {
+        use super::types;
+        pub mod __serde_json_compat {
+            use super::types;
+            #[derive(
+                ::parity_scale_codec::Decode,
+                ::parity_scale_codec::Encode,
+                ::serde::Deserialize,
+                ::serde::Serialize
+            )]
+            #[serde(
+                bound(
+                    serialize = "_0 : :: serde :: Serialize, _1 : :: serde :: Serialize",
+                    deserialize = "_0 : :: serde :: Deserialize < 'de >, _1 : :: serde :: Deserialize < 'de >"
+                )
+            )]
+            pub struct BTreeMap<_0, _1>(
+                #[serde(with = "types::__serde_json_compat::newtype")]
+                pub ::std::vec::Vec<(_0, _1)>,
+            );
+            #[derive(
+                ::parity_scale_codec::Decode,
+                ::parity_scale_codec::Encode,
+                ::serde::Deserialize,
+                ::serde::Serialize
+            )]
+            #[serde(tag = "name", content = "values")]
+            #[serde(
+                bound(
+                    serialize = "_0 : :: serde :: Serialize",
+                    deserialize = "_0 : :: serde :: Deserialize < 'de >"
+                )
+            )]
+            pub enum Option<_0> {
+                #[codec(index = 0)]
+                #[serde(with = "types::__serde_json_compat::unit")]
+                None,
+                #[codec(index = 1)]
+                Some(#[serde(with = "types::__serde_json_compat::newtype")] _0),
+            }
+            #[derive(
+                ::parity_scale_codec::Decode,
+                ::parity_scale_codec::Encode,
+                ::serde::Deserialize,
+                ::serde::Serialize
+            )]
+            #[serde(tag = "name", content = "values")]
+            #[serde(
+                bound(
+                    serialize = "_0 : :: serde :: Serialize, _1 : :: serde :: Serialize",
+                    deserialize = "_0 : :: serde :: Deserialize < 'de >, _1 : :: serde :: Deserialize < 'de >"
+                )
+            )]
+            pub enum Result<_0, _1> {
+                #[codec(index = 0)]
+                Ok(#[serde(with = "types::__serde_json_compat::newtype")] _0),
+                #[codec(index = 1)]
+                Err(#[serde(with = "types::__serde_json_compat::newtype")] _1),
+            }
+            pub mod newtype {
+                pub fn serialize<T: ::serde::Serialize, S: ::serde::Serializer>(
+                    value: &T,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&(value,), serializer)
+                }
+                pub fn deserialize<
+                    'de,
+                    T: ::serde::Deserialize<'de>,
+                    D: ::serde::Deserializer<'de>,
+                >(deserializer: D) -> ::core::result::Result<T, D::Error> {
+                    let (value,): (T,) = ::serde::Deserialize::deserialize(deserializer)?;
+                    ::core::result::Result::Ok(value)
+                }
+            }
+            pub mod bits {
+                pub fn serialize<T: ::serde::Serialize, S: ::serde::Serializer>(
+                    value: &T,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    let mut map = ::serde::Serializer::serialize_map(
+                        serializer,
+                        ::core::option::Option::Some(1),
+                    )?;
+                    ::serde::ser::SerializeMap::serialize_entry(
+                        &mut map,
+                        "__bitvec__values__",
+                        value,
+                    )?;
+                    ::serde::ser::SerializeMap::end(map)
+                }
+                pub fn deserialize<
+                    'de,
+                    T: ::serde::Deserialize<'de>,
+                    D: ::serde::Deserializer<'de>,
+                >(deserializer: D) -> ::core::result::Result<T, D::Error> {
+                    let map: ::std::collections::BTreeMap<::std::string::String, T> = ::serde::Deserialize::deserialize(
+                        deserializer,
+                    )?;
+                    values(map)
+                }
+                pub(super) fn values<T, E: ::serde::de::Error>(
+                    mut map: ::std::collections::BTreeMap<::std::string::String, T>,
+                ) -> ::core::result::Result<T, E> {
+                    map.remove("__bitvec__values__")
+                        .ok_or_else(|| E::missing_field("__bitvec__values__"))
+                }
+            }
+            pub mod newtype_bits {
+                struct Bits<'a, T>(&'a T);
+                impl<T: ::serde::Serialize> ::serde::Serialize for Bits<'_, T> {
+                    fn serialize<S: ::serde::Serializer>(
+                        &self,
+                        serializer: S,
+                    ) -> ::core::result::Result<S::Ok, S::Error> {
+                        super::bits::serialize(self.0, serializer)
+                    }
+                }
+                pub fn serialize<T: ::serde::Serialize, S: ::serde::Serializer>(
+                    value: &T,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    super::newtype::serialize(&Bits(value), serializer)
+                }
+                pub fn deserialize<
+                    'de,
+                    T: ::serde::Deserialize<'de>,
+                    D: ::serde::Deserializer<'de>,
+                >(deserializer: D) -> ::core::result::Result<T, D::Error> {
+                    let map: ::std::collections::BTreeMap<::std::string::String, T> = super::newtype::deserialize(
+                        deserializer,
+                    )?;
+                    super::bits::values(map)
+                }
+            }
+            pub mod unit {
+                pub fn serialize<S: ::serde::Serializer>(
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&[(); 0], serializer)
+                }
+                pub fn deserialize<'de, D: ::serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<(), D::Error> {
+                    let []: [(); 0] = ::serde::Deserialize::deserialize(deserializer)?;
+                    ::core::result::Result::Ok(())
+                }
+            }
+            pub mod phantom {
+                pub fn serialize<T, S: ::serde::Serializer>(
+                    _: &::core::marker::PhantomData<T>,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    super::unit::serialize(serializer)
+                }
+                pub fn deserialize<'de, T, D: ::serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<::core::marker::PhantomData<T>, D::Error> {
+                    super::unit::deserialize(deserializer)
+                        .map(|()| ::core::marker::PhantomData)
+                }
+            }
+        }
+        pub mod bitvec {
+            use super::types;
+            pub mod order {
+                use super::types;
+                #[derive(::parity_scale_codec::Decode, ::parity_scale_codec::Encode)]
+                pub struct Lsb0;
+                impl ::serde::Serialize for Lsb0 {
+                    fn serialize<S: ::serde::Serializer>(
+                        &self,
+                        serializer: S,
+                    ) -> ::core::result::Result<S::Ok, S::Error> {
+                        types::__serde_json_compat::unit::serialize(serializer)
+                    }
+                }
+                impl<'de> ::serde::Deserialize<'de> for Lsb0 {
+                    fn deserialize<D: ::serde::Deserializer<'de>>(
+                        deserializer: D,
+                    ) -> ::core::result::Result<Self, D::Error> {
+                        types::__serde_json_compat::unit::deserialize(deserializer)
+                            .map(|()| Lsb0)
+                    }
+                }
+            }
+        }
+        pub mod scale_typegen {
+            use super::types;
+            pub mod tests {
+                use super::types;
+                pub mod serde_json_compat {
+                    use super::types;
+                    pub mod source {
+                        use super::types;
+                        #[derive(
+                            ::parity_scale_codec::Decode,
+                            ::parity_scale_codec::Encode,
+                            ::serde::Deserialize,
+                            ::serde::Serialize
+                        )]
+                        #[serde(tag = "name", content = "values")]
+                        pub enum E {
+                            #[codec(index = 0)]
+                            #[serde(with = "types::__serde_json_compat::unit")]
+                            A,
+                            #[codec(index = 1)]
+                            B(
+                                #[serde(with = "types::__serde_json_compat::newtype")]
+                                ::core::primitive::u8,
+                            ),
+                            #[codec(index = 2)]
+                            C {
+                                #[codec(compact)]
+                                x: ::core::primitive::u32,
+                                #[serde(with = "types::__serde_json_compat::bits")]
+                                bits: crate::tests::serde_json_compat::DecodedBits<
+                                    ::core::primitive::u8,
+                                    types::bitvec::order::Lsb0,
+                                >,
+                            },
+                            #[codec(index = 3)]
+                            D(
+                                #[serde(with = "types::__serde_json_compat::newtype_bits")]
+                                crate::tests::serde_json_compat::DecodedBits<
+                                    ::core::primitive::u8,
+                                    types::bitvec::order::Lsb0,
+                                >,
+                            ),
+                            #[codec(index = 4)]
+                            R(
+                                #[serde(with = "types::__serde_json_compat::newtype")]
+                                types::__serde_json_compat::Result<
+                                    ::core::primitive::u8,
+                                    ::core::primitive::bool,
+                                >,
+                            ),
+                        }
+                        #[derive(
+                            ::parity_scale_codec::Decode,
+                            ::parity_scale_codec::Encode,
+                            ::serde::Deserialize,
+                            ::serde::Serialize
+                        )]
+                        pub struct Newtype(
+                            #[serde(with = "types::__serde_json_compat::newtype")]
+                            pub ::core::primitive::u32,
+                        );
+                        #[derive(
+                            ::parity_scale_codec::Decode,
+                            ::parity_scale_codec::Encode,
+                            ::serde::Deserialize,
+                            ::serde::Serialize
+                        )]
+                        pub struct P<_0>(
+                            #[serde(with = "types::__serde_json_compat::phantom")]
+                            pub ::core::marker::PhantomData<_0>,
+                        );
+                        #[derive(
+                            ::parity_scale_codec::Decode,
+                            ::parity_scale_codec::Encode,
+                            ::serde::Deserialize,
+                            ::serde::Serialize
+                        )]
+                        pub struct S {
+                            pub a: ::core::primitive::u8,
+                            #[codec(compact)]
+                            pub b: ::core::primitive::u64,
+                            pub c: ::core::primitive::u128,
+                            pub e: types::scale_typegen::tests::serde_json_compat::source::E,
+                            pub unit: types::scale_typegen::tests::serde_json_compat::source::Unit,
+                            pub newtype: types::scale_typegen::tests::serde_json_compat::source::Newtype,
+                            #[serde(with = "types::__serde_json_compat::bits")]
+                            pub bits: crate::tests::serde_json_compat::DecodedBits<
+                                ::core::primitive::u8,
+                                types::bitvec::order::Lsb0,
+                            >,
+                            pub o: types::__serde_json_compat::Option<
+                                ::core::primitive::u16,
+                            >,
+                            pub m: types::__serde_json_compat::BTreeMap<
+                                ::core::primitive::u8,
+                                ::core::primitive::bool,
+                            >,
+                            pub p: types::scale_typegen::tests::serde_json_compat::source::P<
+                                ::core::primitive::u8,
+                            >,
+                        }
+                        #[derive(::parity_scale_codec::Decode, ::parity_scale_codec::Encode)]
+                        pub struct Unit;
+                        impl ::serde::Serialize for Unit {
+                            fn serialize<S: ::serde::Serializer>(
+                                &self,
+                                serializer: S,
+                            ) -> ::core::result::Result<S::Ok, S::Error> {
+                                types::__serde_json_compat::unit::serialize(serializer)
+                            }
+                        }
+                        impl<'de> ::serde::Deserialize<'de> for Unit {
+                            fn deserialize<D: ::serde::Deserializer<'de>>(
+                                deserializer: D,
+                            ) -> ::core::result::Result<Self, D::Error> {
+                                types::__serde_json_compat::unit::deserialize(deserializer)
+                                    .map(|()| Unit)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn serializes_like_scale_value() {
+    let (registry, type_id) = registry();
+    let settings = settings();
+    let code = TypeGenerator::new(&registry, &settings)
+        .generate_types_mod()
+        .unwrap()
+        .to_token_stream(&settings);
+    let compiled: TokenStream = COMPILED.parse().unwrap();
+    let pretty = |code| prettyplease::unparse(&syn::parse2(code).unwrap());
+    assert_eq!(pretty(code), pretty(compiled));
+
+    let bits = |bits: &[bool]| bits.iter().copied().collect::<bitvec::vec::BitVec<u8>>();
+    let value = |c, e, o| source::S {
+        a: 1,
+        b: u64::MAX,
+        c,
+        e,
+        unit: source::Unit,
+        newtype: source::Newtype(7),
+        bits: bits(&[true, false, true]),
+        o,
+        m: [(1, true), (2, false)].into_iter().collect(),
+        p: source::P(PhantomData),
+    };
+    let values = [
+        value(u128::from(u64::MAX), source::E::A, None),
+        value(0, source::E::B(3), Some(4)),
+        value(
+            1 << 60,
+            source::E::C {
+                x: 5,
+                bits: bits(&[false, true]),
+            },
+            None,
+        ),
+        value(2, source::E::D(bits(&[])), Some(u16::MAX)),
+        value(3, source::E::R(Ok(6)), None),
+        value(4, source::E::R(Err(true)), None),
+    ];
+
+    type Generated = types::scale_typegen::tests::serde_json_compat::source::S;
+    for value in values {
+        let encoded = value.encode();
+        let scale_value =
+            scale_value::scale::decode_as_type(&mut &encoded[..], type_id, &registry).unwrap();
+        let expected = serde_json::to_value(&scale_value).unwrap();
+
+        let generated = Generated::decode(&mut &encoded[..]).unwrap();
+        let json = serde_json::to_value(&generated).unwrap();
+        assert_eq!(json, expected);
+
+        let deserialized: Generated = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.encode(), encoded);
+    }
+
+    // `u128`s that do not fit into a `serde_json::Value` are still written as numbers.
+    let value = value(u128::MAX, source::E::A, None);
+    let encoded = value.encode();
+    let scale_value =
+        scale_value::scale::decode_as_type(&mut &encoded[..], type_id, &registry).unwrap();
+    let generated = Generated::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(
+        serde_json::to_string(&generated).unwrap(),
+        serde_json::to_string(&scale_value).unwrap()
+    );
+}
@@ -71,13 +71,29 @@ pub(super) fn subxt_settings() -> TypeGeneratorSettings {
     TypeGeneratorSettings {
         types_mod_ident: parse_quote!(root),
         should_gen_docs: true,
+        original_type_param_names: false,
+        instantiation_aliases: false,
+        monomorphize: false,
         derives: subxt_default_derives(),
         substitutes: subxt_default_substitutes(),
+        renames: Default::default(),
+        module_layout: Default::default(),
         decoded_bits_type_path: Some(parse_quote!(::subxt_path::utils::bits::DecodedBits)),
         insert_codec_attributes: true,
+        explicit_codec_impls: None,
+        explicit_encode_as_type_impls: None,
+        explicit_decode_as_type_impls: None,
+        serde_json_compat: None,
+        naming: Default::default(),
         compact_as_type_path: Some(parse_quote!(::subxt_path::ext::codec::CompactAs)),
         compact_type_path: Some(parse_quote!(::subxt_path::ext::codec::Compact)),
         alloc_crate_path: Default::default(),
+        u256_type: None,
+        i256_type: None,
+        prelude_types: Default::default(),
+        roots: vec![],
+        infer_derives: false,
+        external_type_traits: Default::default(),
     }
 }
 /// Derives mirroring the subxt default derives
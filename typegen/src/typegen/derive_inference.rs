@@ -0,0 +1,353 @@
+use std::collections::{BTreeSet, HashMap};
+
+use scale_info::{form::PortableForm, TypeDefPrimitive};
+
+use crate::TypeGeneratorSettings;
+
+use super::{
+    ir::{
+        module_ir::ModuleIR,
+        type_ir::{CompositeFieldIR, CompositeIRKind, TypeIR, TypeIRKind},
+    },
+    settings::{
+        derives::DerivableTrait,
+        substitutes::{path_segments, PathSegments},
+    },
+    type_path::{TypePath, TypePathInner, TypePathType},
+};
+
+/// A derive that was dropped from a generated type, because the type cannot derive it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedDerive {
+    /// Path of the type in the type registry.
+    pub type_path: scale_info::Path<PortableForm>,
+    /// The derive that was dropped, e.g. `Hash`.
+    pub derive: syn::Path,
+    /// Why the derive is not possible.
+    pub reason: DropReason,
+}
+
+/// The reason why a derive is not possible for a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropReason {
+    /// A field of this type does not implement the trait.
+    Field(Box<syn::Type>),
+    /// A supertrait of the trait cannot be derived for this type, e.g. `Eq` for `Ord`.
+    Supertrait(DerivableTrait),
+    /// `Default` cannot be derived for enums without a `#[default]` variant.
+    DefaultOnEnum,
+}
+
+/// Derivable traits of prelude types, keyed by the last two segments of their path.
+/// These hold if the generic parameters of the types implement the traits as well.
+fn prelude_type_traits(path: &[String]) -> Option<&'static [DerivableTrait]> {
+    use DerivableTrait::*;
+    let [.., module, ident] = path else {
+        return None;
+    };
+    let traits: &[DerivableTrait] = match (module.as_str(), ident.as_str()) {
+        ("option", "Option") => &[Copy, Eq, PartialOrd, Ord, Hash, Default],
+        ("result", "Result") => &[Copy, Eq, PartialOrd, Ord, Hash],
+        ("collections", "BTreeMap" | "BTreeSet" | "VecDeque" | "LinkedList") => {
+            &[Eq, PartialOrd, Ord, Hash, Default]
+        }
+        ("collections", "BinaryHeap") => &[Default],
+        ("ops", "Range") => &[Eq, Hash, Default],
+        ("ops", "RangeInclusive") => &[Eq, Hash],
+        ("num", ident) if ident.starts_with("NonZero") => &[Copy, Eq, PartialOrd, Ord, Hash],
+        _ => return None,
+    };
+    Some(traits)
+}
+
+/// Derivable traits of `parity_scale_codec::Compact`.
+const COMPACT_TRAITS: &[DerivableTrait] = &[
+    DerivableTrait::Copy,
+    DerivableTrait::Eq,
+    DerivableTrait::PartialOrd,
+    DerivableTrait::Ord,
+];
+
+/// Drops all derives of [`DerivableTrait`]s from the types in the module that cannot derive them,
+/// and returns a report of the dropped derives.
+///
+/// Whether a type can derive a trait depends on the derives of the other generated types, so this is computed
+/// as a fixed point: We start by assuming that all derives are feasible and drop derives until nothing changes.
+pub(crate) fn drop_infeasible_derives(
+    module: &mut ModuleIR,
+    settings: &TypeGeneratorSettings,
+) -> Vec<DroppedDerive> {
+    let mut types = vec![];
+    collect_types(module, settings, &mut types);
+
+    let mut feasible: HashMap<PathSegments, BTreeSet<DerivableTrait>> = types
+        .iter()
+        .map(|(key, _, type_ir)| {
+            let traits = type_ir
+                .derives
+                .derives()
+                .iter()
+                .filter_map(DerivableTrait::from_derive_path)
+                .collect();
+            (key.clone(), traits)
+        })
+        .collect();
+
+    let inference = Inference { settings };
+    let mut reasons: HashMap<(PathSegments, DerivableTrait), DropReason> = HashMap::new();
+    loop {
+        let mut changed = false;
+        for (key, _, type_ir) in types.iter() {
+            for derivable_trait in feasible[key].clone() {
+                if let Some(reason) =
+                    inference.infeasibility(key, type_ir, derivable_trait, &feasible)
+                {
+                    feasible
+                        .get_mut(key)
+                        .expect("all types are in the map; qed")
+                        .remove(&derivable_trait);
+                    reasons.insert((key.clone(), derivable_trait), reason);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut types = vec![];
+    collect_types_mut(module, settings, &mut types);
+
+    let mut dropped = vec![];
+    for (key, type_path, type_ir) in types {
+        let mut derives = type_ir
+            .derives
+            .derives()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        derives.sort_by_key(|derive| quote::quote!(#derive).to_string());
+        for derive in derives {
+            let Some(derivable_trait) = DerivableTrait::from_derive_path(&derive) else {
+                continue;
+            };
+            if feasible[&key].contains(&derivable_trait) {
+                continue;
+            }
+            type_ir.derives.remove_derive(&derive);
+            dropped.push(DroppedDerive {
+                type_path: type_path.clone(),
+                derive,
+                reason: reasons[&(key.clone(), derivable_trait)].clone(),
+            });
+        }
+    }
+    dropped
+}
+
+/// The key of a generated type is the path it is referred to by other generated types.
+fn type_key(
+    root_mod: &str,
+    type_path: &scale_info::Path<PortableForm>,
+    settings: &TypeGeneratorSettings,
+) -> PathSegments {
+    std::iter::once(root_mod.to_string())
+        .chain(settings.generated_path(&type_path.segments).iter().cloned())
+        .collect()
+}
+
+fn collect_types<'a>(
+    module: &'a ModuleIR,
+    settings: &TypeGeneratorSettings,
+    types: &mut Vec<(PathSegments, &'a scale_info::Path<PortableForm>, &'a TypeIR)>,
+) {
+    let root_mod = module.root_mod.to_string();
+    for (type_path, (_, type_ir)) in module.types.iter() {
+        types.push((type_key(&root_mod, type_path, settings), type_path, type_ir));
+    }
+    for child in module.children.values() {
+        collect_types(child, settings, types);
+    }
+}
+
+fn collect_types_mut<'a>(
+    module: &'a mut ModuleIR,
+    settings: &TypeGeneratorSettings,
+    types: &mut Vec<(
+        PathSegments,
+        &'a scale_info::Path<PortableForm>,
+        &'a mut TypeIR,
+    )>,
+) {
+    let root_mod = module.root_mod.to_string();
+    for (type_path, (_, type_ir)) in module.types.iter_mut() {
+        types.push((type_key(&root_mod, type_path, settings), type_path, type_ir));
+    }
+    for child in module.children.values_mut() {
+        collect_types_mut(child, settings, types);
+    }
+}
+
+struct Inference<'a> {
+    settings: &'a TypeGeneratorSettings,
+}
+
+impl Inference<'_> {
+    /// Returns the reason why the type cannot derive the trait, or `None` if it can,
+    /// given the currently feasible derives of all generated types.
+    fn infeasibility(
+        &self,
+        key: &PathSegments,
+        type_ir: &TypeIR,
+        derivable_trait: DerivableTrait,
+        feasible: &HashMap<PathSegments, BTreeSet<DerivableTrait>>,
+    ) -> Option<DropReason> {
+        for supertrait in derivable_trait.supertraits() {
+            if !feasible[key].contains(supertrait) {
+                return Some(DropReason::Supertrait(*supertrait));
+            }
+        }
+
+        // `Clone` and `PartialEq` are assumed to be implemented by all fields, see `DerivableTrait`.
+        if matches!(
+            derivable_trait,
+            DerivableTrait::Clone | DerivableTrait::PartialEq
+        ) {
+            return None;
+        }
+
+        let mut fields = vec![];
+        match &type_ir.kind {
+            TypeIRKind::Struct(composite) => composite_fields(&composite.kind, &mut fields),
+            TypeIRKind::Enum(enum_ir) => {
+                if derivable_trait == DerivableTrait::Default {
+                    return Some(DropReason::DefaultOnEnum);
+                }
+                for (_, composite) in enum_ir.variants.iter() {
+                    composite_fields(&composite.kind, &mut fields);
+                }
+            }
+        }
+
+        fields
+            .into_iter()
+            .find(|field| {
+                let boxed_copy = field.is_boxed && derivable_trait == DerivableTrait::Copy;
+                boxed_copy || !self.implements(&field.type_path, derivable_trait, feasible)
+            })
+            .map(|field| {
+                let ty = field.type_path.to_syn_type(self.settings);
+                DropReason::Field(Box::new(ty))
+            })
+    }
+
+    /// Returns true if the type implements the trait.
+    fn implements(
+        &self,
+        type_path: &TypePath,
+        derivable_trait: DerivableTrait,
+        feasible: &HashMap<PathSegments, BTreeSet<DerivableTrait>>,
+    ) -> bool {
+        let ty = match type_path.inner() {
+            // Derives add bounds for all type parameters, so they implement the trait where it matters.
+            TypePathInner::Parameter(_) => return true,
+            TypePathInner::Type(ty) => ty,
+        };
+        let all_implement = |type_paths: &[TypePath]| {
+            type_paths
+                .iter()
+                .all(|type_path| self.implements(type_path, derivable_trait, feasible))
+        };
+
+        match ty {
+            TypePathType::Path { path, params } => {
+                let segments = path_segments(path);
+                let implemented = match feasible.get(&segments) {
+                    Some(traits) => traits.contains(&derivable_trait),
+                    None => self.external_type_implements(&segments, derivable_trait),
+                };
+                implemented && all_implement(params)
+            }
+            TypePathType::Vec { of } => {
+                derivable_trait != DerivableTrait::Copy
+                    && self.implements(of, derivable_trait, feasible)
+            }
+            TypePathType::Array { len, of } => {
+                // `Default` is only implemented for arrays with up to 32 elements.
+                (derivable_trait != DerivableTrait::Default || *len <= 32)
+                    && self.implements(of, derivable_trait, feasible)
+            }
+            TypePathType::Tuple { elements } => all_implement(elements),
+            TypePathType::Primitive { def } => {
+                !(*def == TypeDefPrimitive::Str && derivable_trait == DerivableTrait::Copy)
+            }
+            TypePathType::MappedPrimitive { ty, .. } => match ty {
+                // `Default` is only implemented for arrays with up to 32 elements.
+                syn::Type::Array(array) => {
+                    derivable_trait != DerivableTrait::Default
+                        || array_len(array).is_some_and(|len| len <= 32)
+                }
+                syn::Type::Path(path) => {
+                    self.external_type_implements(&path_segments(&path.path), derivable_trait)
+                }
+                _ => false,
+            },
+            TypePathType::Compact {
+                inner,
+                is_field,
+                compact_type_path,
+            } => {
+                // Compact fields are represented by the inner type, with a `#[codec(compact)]` attribute.
+                let wrapper_implements = *is_field
+                    || match self
+                        .settings
+                        .external_type_traits
+                        .get(&path_segments(compact_type_path))
+                    {
+                        Some(traits) => traits.contains(&derivable_trait),
+                        None => COMPACT_TRAITS.contains(&derivable_trait),
+                    };
+                wrapper_implements && self.implements(inner, derivable_trait, feasible)
+            }
+            TypePathType::BitVec {
+                decoded_bits_type_path,
+                ..
+            } => decoded_bits_type_path.as_ref().is_some_and(|path| {
+                self.external_type_implements(&path_segments(path), derivable_trait)
+            }),
+        }
+    }
+
+    /// Types that are not generated implement the traits declared in the settings, or the
+    /// traits of well known prelude types.
+    fn external_type_implements(
+        &self,
+        segments: &PathSegments,
+        derivable_trait: DerivableTrait,
+    ) -> bool {
+        if let Some(traits) = self.settings.external_type_traits.get(segments) {
+            return traits.contains(&derivable_trait);
+        }
+        prelude_type_traits(segments).is_some_and(|traits| traits.contains(&derivable_trait))
+    }
+}
+
+/// The length of an array type like `[u8; 32]`, if it is a literal.
+fn array_len(array: &syn::TypeArray) -> Option<usize> {
+    match &array.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => len.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+fn composite_fields<'a>(kind: &'a CompositeIRKind, fields: &mut Vec<&'a CompositeFieldIR>) {
+    match kind {
+        CompositeIRKind::NoFields => {}
+        CompositeIRKind::Named(named) => fields.extend(named.iter().map(|(_, field)| field)),
+        CompositeIRKind::Unnamed(unnamed) => fields.extend(unnamed.iter()),
+    }
+}
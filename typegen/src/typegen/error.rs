@@ -22,9 +22,29 @@ pub enum TypegenError {
     /// Could not generate a type that contains a bit sequence, because the DecodedBits type path is not set in the settings.
     #[error("Could not generate a type that contains a bit sequence, because the DecodedBits type path is not set in the settings.")]
     DecodedBitsPathNone,
+    /// Could not generate a type that contains a `U256`, because the `U256` type is not set in the settings.
+    #[error("Could not generate a type that contains a U256, because the U256 type is not set in the settings.")]
+    U256TypeNone,
+    /// Could not generate a type that contains an `I256`, because the `I256` type is not set in the settings.
+    #[error("Could not generate a type that contains an I256, because the I256 type is not set in the settings.")]
+    I256TypeNone,
+    /// A type has a single segment path that is neither a builtin prelude type nor configured in the settings.
+    #[error(
+        "Unknown prelude type path `{name}`{}. Add a path for it to the `prelude_types` in the settings.",
+        type_id.map(|id| format!(" of type with ID {id}")).unwrap_or_default()
+    )]
+    UnknownPreludeType {
+        /// Id of the type in the type registry, if the path was resolved for a type
+        type_id: Option<u32>,
+        /// Path of the type, e.g. `HashMap`
+        name: String,
+    },
     /// Could not find type with ID in the type registry.
     #[error("Could not find type with ID {0} in the type registry.")]
     TypeNotFound(u32),
+    /// A root path pattern in the settings does not match any type in the type registry.
+    #[error("The root path pattern `{0}` does not match any type in the type registry.")]
+    RootNotFound(String),
     /// Type substitution error.
     #[error("Type substitution error: {0}")]
     InvalidSubstitute(#[from] TypeSubstitutionError),
@@ -35,6 +55,35 @@ pub enum TypegenError {
     /// Use [`crate::utils::ensure_unique_type_paths`] on your [`scale_info::PortableRegistry`] to deduplicate type paths.
     #[error("There are two types with the the same type path {0} but different structure. Use `scale_typegen::utils::ensure_unique_type_paths` on your `PortableRegistry` before, to avoid this error.")]
     DuplicateTypePath(String),
+    /// The naming policy maps two fields or variants of a type to the same identifier.
+    #[error("The naming policy gives two fields or variants of type {type_path} the same name `{name}`.")]
+    DuplicateName {
+        /// Path of the type in the type registry
+        type_path: String,
+        /// The identifier that is used twice
+        name: String,
+    },
+    /// The naming policy renames a field or variant of a type that specifically derives `EncodeAsType` or `DecodeAsType`.
+    #[error("The naming policy renames `{name}` of type {type_path}, but `{derive}` matches fields and variants by their rust name. Add the derive for all types or use `explicit_encode_as_type_impls`/`explicit_decode_as_type_impls` in the settings instead.")]
+    RenamedWithNameMatchingDerive {
+        /// Path of the type in the type registry
+        type_path: String,
+        /// The name in the type registry that is renamed
+        name: String,
+        /// The derive that matches names
+        derive: String,
+    },
+    /// A rename in the settings has an empty path or a segment that is not a valid identifier.
+    #[error("Invalid rename of `{from}` to `{to}`: both paths need to be non-empty and consist of valid identifiers.")]
+    InvalidRename {
+        /// Path of the type in the type registry
+        from: String,
+        /// Path the type should be generated at
+        to: String,
+    },
+    /// After renaming, two different types would be generated with the same path.
+    #[error("Multiple types would be generated with the type path {0} after renaming. Check the `renames` in the settings.")]
+    DuplicateRenamedTypePath(String),
     /// PortableRegistry entry has incorrect Id.
     #[error("PortableRegistry entry has incorrect type_id. expected type_id: {expected_ty_id}, got: {given_ty_id}.\nDefinition of the type: {ty_def}.\nThis can happen if registry was modified with calls to `::retain()` in older versions of scale-info. Try generating a new metadata to fix this.")]
     RegistryTypeIdsInvalid {
@@ -87,6 +136,9 @@ pub enum TypeSubstitutionErrorKind {
     /// Target ident doesn't correspond to any source type.
     #[error("Cannot find matching param on 'from' type.")]
     NoMatchingFromType,
+    /// Source path pattern is invalid.
+    #[error("Expected a path pattern like 'a::*::Foo' or 'a::**::Foo<A,B>' to mark types to be substituted.")]
+    InvalidPathPattern,
 }
 
 /// Error attempting to do type substitution.
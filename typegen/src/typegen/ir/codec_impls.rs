@@ -0,0 +1,598 @@
+use std::collections::BTreeSet;
+
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use crate::TypeGeneratorSettings;
+
+use super::{
+    type_ir::{CompositeFieldIR, CompositeIRKind, TypeIR, TypeIRKind},
+    ToTokensWithSettings,
+};
+
+/// A field of a composite, together with the binding that refers to it in the generated impls.
+struct Field<'a> {
+    /// A reference to the field value, e.g. `&self.a` or `__binding_0`.
+    binding: TokenStream,
+    /// The name of the field in the type registry, `None` for unnamed fields.
+    name: Option<String>,
+    field: &'a CompositeFieldIR,
+}
+
+impl TypeIR {
+    /// All explicit trait implementations that are configured for this type.
+    pub(crate) fn explicit_impls(&self, settings: &TypeGeneratorSettings) -> TokenStream {
+        let codec_impls = self
+            .explicit_codec_impls
+            .as_ref()
+            .map(|codec| self.codec_impls(codec, settings));
+        let encode_as_type_impl = self
+            .explicit_encode_as_type_impls
+            .as_ref()
+            .map(|scale_encode| self.encode_as_type_impl(scale_encode));
+        let decode_as_type_impl = self
+            .explicit_decode_as_type_impls
+            .as_ref()
+            .map(|scale_decode| self.decode_as_type_impl(scale_decode));
+        quote! {
+            #codec_impls
+            #encode_as_type_impl
+            #decode_as_type_impl
+        }
+    }
+
+    /// Explicit `Encode`, `EncodeLike` and `Decode` implementations for this type, equivalent to what the
+    /// `parity_scale_codec` derive macros would generate (honoring codec indexes, compact fields and phantom markers).
+    pub(crate) fn codec_impls(
+        &self,
+        codec: &syn::Path,
+        settings: &TypeGeneratorSettings,
+    ) -> TokenStream {
+        let ident = self.ident();
+        let type_params = &self.type_params;
+        let encode_bounds = self.codec_bounds(codec, quote!(#codec::Encode), settings);
+        let decode_bounds = self.codec_bounds(codec, quote!(#codec::Decode), settings);
+
+        let (encode_body, decode_body) = match &self.kind {
+            TypeIRKind::Struct(composite) => {
+                let fields = self_fields(&composite.kind);
+                let encode = fields
+                    .iter()
+                    .map(|f| encode_field(codec, f, settings))
+                    .collect::<TokenStream>();
+                let construct = self.construct(
+                    codec,
+                    &quote!(Self),
+                    &ident.to_string(),
+                    &composite.kind,
+                    true,
+                    settings,
+                );
+                (encode, quote!(::core::result::Result::Ok(#construct)))
+            }
+            TypeIRKind::Enum(enum_ir) => {
+                let mut encode_arms = vec![];
+                let mut decode_arms = vec![];
+                for (index, variant) in &enum_ir.variants {
+                    let variant_ident = &variant.name;
+                    let index = Literal::u8_suffixed(*index);
+                    let (pattern, fields) = binding_fields(&variant.kind);
+                    let encode = fields
+                        .iter()
+                        .map(|f| encode_field(codec, f, settings))
+                        .collect::<TokenStream>();
+                    encode_arms.push(quote! {
+                        Self::#variant_ident #pattern => {
+                            #codec::Output::push_byte(__codec_dest, #index);
+                            #encode
+                        }
+                    });
+
+                    let construct = self.construct(
+                        codec,
+                        &quote!(Self::#variant_ident),
+                        &format!("{ident}::{variant_ident}"),
+                        &variant.kind,
+                        false,
+                        settings,
+                    );
+                    decode_arms.push(quote! {
+                        #index => ::core::result::Result::Ok(#construct),
+                    });
+                }
+                if self.type_params.has_unused_type_params() {
+                    encode_arms.push(quote!(Self::__Ignore(_) => {}));
+                }
+
+                let read_variant_err =
+                    format!("Could not decode `{ident}`, failed to read variant byte");
+                let unknown_variant_err =
+                    format!("Could not decode `{ident}`, variant doesn't exist");
+                let encode = quote! {
+                    match self {
+                        #( #encode_arms )*
+                    }
+                };
+                let decode = quote! {
+                    match #codec::Input::read_byte(__codec_input)
+                        .map_err(|e| e.chain(#read_variant_err))?
+                    {
+                        #( #decode_arms )*
+                        _ => ::core::result::Result::Err(#unknown_variant_err.into()),
+                    }
+                };
+                (encode, decode)
+            }
+        };
+
+        quote! {
+            impl #type_params #codec::Encode for #ident #type_params #encode_bounds {
+                fn encode_to<__CodecOutput: #codec::Output + ?::core::marker::Sized>(
+                    &self,
+                    __codec_dest: &mut __CodecOutput,
+                ) {
+                    #encode_body
+                }
+            }
+
+            impl #type_params #codec::EncodeLike for #ident #type_params #encode_bounds {}
+
+            impl #type_params #codec::Decode for #ident #type_params #decode_bounds {
+                fn decode<__CodecInput: #codec::Input>(
+                    __codec_input: &mut __CodecInput,
+                ) -> ::core::result::Result<Self, #codec::Error> {
+                    #decode_body
+                }
+            }
+        }
+    }
+
+    /// Bounds for all type params that are encoded as they are. Compact fields are encoded via `HasCompact` instead,
+    /// so only their types need to implement `HasCompact`.
+    fn codec_bounds(
+        &self,
+        codec: &syn::Path,
+        codec_trait: TokenStream,
+        settings: &TypeGeneratorSettings,
+    ) -> TokenStream {
+        let mut bounds = self.param_bounds(&codec_trait, |field| !field.is_compact);
+        for field in self.fields() {
+            if field.is_compact && !field.type_path.parent_type_params().is_empty() {
+                let ty = field.to_token_stream(settings);
+                bounds.push(quote!(#ty: #codec::HasCompact));
+            }
+        }
+        where_clause(bounds)
+    }
+
+    /// Bounds `param: bound` for the type params that are used in the fields matching the filter, in the order
+    /// of the type params.
+    fn param_bounds(
+        &self,
+        bound: &TokenStream,
+        filter: impl Fn(&CompositeFieldIR) -> bool,
+    ) -> Vec<TokenStream> {
+        let params = self
+            .fields()
+            .into_iter()
+            .filter(|field| filter(field))
+            .flat_map(|field| field.type_path.parent_type_params())
+            .collect::<BTreeSet<_>>();
+        self.type_params
+            .used_params()
+            .filter(|param| params.contains(*param))
+            .map(|param| quote!(#param: #bound))
+            .collect()
+    }
+
+    /// An explicit `EncodeAsType` implementation for this type, equivalent to what the `scale_encode` derive
+    /// macro would generate. Fields and variants are matched by their names in the type registry.
+    pub(crate) fn encode_as_type_impl(&self, scale_encode: &syn::Path) -> TokenStream {
+        let ident = self.ident();
+        let type_params = &self.type_params;
+        let bounds =
+            where_clause(self.param_bounds(&quote!(#scale_encode::EncodeAsType), |_| true));
+        let args = quote!(
+            __encode_as_type_id,
+            __encode_as_type_types,
+            __encode_as_type_out
+        );
+
+        let body = match &self.kind {
+            TypeIRKind::Struct(composite) => {
+                let composite =
+                    encode_as_type_composite(scale_encode, &self_fields(&composite.kind));
+                quote!(#composite.encode_composite_as_type_to(#args))
+            }
+            TypeIRKind::Enum(enum_ir) => {
+                let mut arms = enum_ir
+                    .variants
+                    .iter()
+                    .map(|(_, variant)| {
+                        let variant_ident = &variant.name;
+                        let name = registry_name(variant_ident, &variant.original_name);
+                        let (pattern, fields) = binding_fields(&variant.kind);
+                        let composite = encode_as_type_composite(scale_encode, &fields);
+                        quote! {
+                            Self::#variant_ident #pattern => #scale_encode::Variant {
+                                name: #name,
+                                fields: #composite,
+                            }
+                            .encode_variant_as_type_to(#args),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if self.type_params.has_unused_type_params() {
+                    let err = format!("Cannot encode the `__Ignore` variant of `{ident}`");
+                    arms.push(quote! {
+                        Self::__Ignore(_) => ::core::result::Result::Err(#scale_encode::Error::custom_str(#err)),
+                    });
+                }
+                quote! {
+                    match self {
+                        #( #arms )*
+                    }
+                }
+            }
+        };
+
+        quote! {
+            impl #type_params #scale_encode::EncodeAsType for #ident #type_params #bounds {
+                fn encode_as_type_to<__EncodeAsTypeResolver: #scale_encode::TypeResolver>(
+                    &self,
+                    __encode_as_type_id: __EncodeAsTypeResolver::TypeId,
+                    __encode_as_type_types: &__EncodeAsTypeResolver,
+                    __encode_as_type_out: &mut #scale_encode::Vec<::core::primitive::u8>,
+                ) -> ::core::result::Result<(), #scale_encode::Error> {
+                    #body
+                }
+            }
+        }
+    }
+
+    /// An explicit `DecodeAsType` implementation for this type, equivalent to what the `scale_decode` derive
+    /// macro would generate: a visitor and an `IntoVisitor` implementation that returns it. Fields and variants
+    /// are matched by their names in the type registry.
+    pub(crate) fn decode_as_type_impl(&self, scale_decode: &syn::Path) -> TokenStream {
+        let ident = self.ident();
+        let type_params = &self.type_params;
+        let params = type_params.params();
+        let bounds = where_clause(self.param_bounds(&quote!(#scale_decode::IntoVisitor), |_| true));
+        let result = quote!(::core::result::Result<Self::Value<'scale, 'resolver>, Self::Error>);
+
+        let visit = match &self.kind {
+            TypeIRKind::Struct(composite) => {
+                let construct = self.decode_as_type_construct(
+                    scale_decode,
+                    &quote!(#ident),
+                    &composite.kind,
+                    None,
+                );
+                quote! {
+                    fn visit_composite<'scale, 'resolver>(
+                        self,
+                        value: &mut #scale_decode::visitor::types::Composite<'scale, 'resolver, Self::TypeResolver>,
+                        _type_id: #scale_decode::visitor::TypeIdFor<Self>,
+                    ) -> #result {
+                        let __fields = value.collect::<::core::result::Result<#scale_decode::vec::Vec<_>, _>>()?;
+                        #construct
+                    }
+                }
+            }
+            TypeIRKind::Enum(enum_ir) => {
+                let names = enum_ir
+                    .variants
+                    .iter()
+                    .map(|(_, variant)| registry_name(&variant.name, &variant.original_name))
+                    .collect::<Vec<_>>();
+                let arms = enum_ir
+                    .variants
+                    .iter()
+                    .zip(&names)
+                    .map(|((_, variant), name)| {
+                        let variant_ident = &variant.name;
+                        let construct = self.decode_as_type_construct(
+                            scale_decode,
+                            &quote!(#ident::#variant_ident),
+                            &variant.kind,
+                            Some(name),
+                        );
+                        quote!(#name => { #construct })
+                    });
+                quote! {
+                    fn visit_variant<'scale, 'resolver>(
+                        self,
+                        value: &mut #scale_decode::visitor::types::Variant<'scale, 'resolver, Self::TypeResolver>,
+                        _type_id: #scale_decode::visitor::TypeIdFor<Self>,
+                    ) -> #result {
+                        let __variant_name = value.name();
+                        let __fields = value.fields().collect::<::core::result::Result<#scale_decode::vec::Vec<_>, _>>()?;
+                        match __variant_name {
+                            #( #arms, )*
+                            __other => ::core::result::Result::Err(#scale_decode::Error::new(
+                                #scale_decode::error::ErrorKind::CannotFindVariant {
+                                    got: #scale_decode::ToString::to_string(__other),
+                                    expected: #scale_decode::vec![ #( #names ),* ],
+                                },
+                            )),
+                        }
+                    }
+                }
+            }
+        };
+
+        quote! {
+            const _: () = {
+                pub struct __DecodeAsTypeVisitor<__DecodeAsTypeResolver, #( #params ),*>(
+                    ::core::marker::PhantomData<(__DecodeAsTypeResolver, #( #params ),*)>,
+                );
+
+                impl<__DecodeAsTypeResolver: #scale_decode::TypeResolver, #( #params ),*> #scale_decode::Visitor
+                    for __DecodeAsTypeVisitor<__DecodeAsTypeResolver, #( #params ),*> #bounds
+                {
+                    type Value<'scale, 'resolver> = #ident #type_params;
+                    type Error = #scale_decode::Error;
+                    type TypeResolver = __DecodeAsTypeResolver;
+
+                    #visit
+                }
+
+                impl #type_params #scale_decode::IntoVisitor for #ident #type_params #bounds {
+                    type AnyVisitor<__DecodeAsTypeResolver: #scale_decode::TypeResolver> =
+                        __DecodeAsTypeVisitor<__DecodeAsTypeResolver, #( #params ),*>;
+                    fn into_visitor<__DecodeAsTypeResolver: #scale_decode::TypeResolver>(
+                    ) -> Self::AnyVisitor<__DecodeAsTypeResolver> {
+                        __DecodeAsTypeVisitor(::core::marker::PhantomData)
+                    }
+                }
+            };
+        }
+    }
+
+    /// Constructs `path` from the `__fields` of a struct or of the given variant in a `DecodeAsType` visitor.
+    /// Named fields are looked up by their name, unnamed fields by their position.
+    fn decode_as_type_construct(
+        &self,
+        scale_decode: &syn::Path,
+        path: &TokenStream,
+        kind: &CompositeIRKind,
+        variant: Option<&String>,
+    ) -> TokenStream {
+        let error_kind = quote!(#scale_decode::error::ErrorKind);
+        let at_variant = variant.map(|name| quote!(.at_variant(#name)));
+        let is_struct = variant.is_none();
+        let construct =
+            self.construct_with(path, kind, is_struct, |field, ident, index| match ident {
+                Some(ident) => {
+                    let name = registry_name(ident, &field.original_name);
+                    quote! {
+                        __fields
+                            .iter()
+                            .find(|field| field.name() == ::core::option::Option::Some(#name))
+                            .ok_or_else(|| #scale_decode::Error::new(#error_kind::CannotFindField {
+                                name: #scale_decode::ToString::to_string(#name),
+                            }) #at_variant)?
+                            .decode_as_type()
+                            .map_err(|e| e.at_field(#name) #at_variant)?
+                    }
+                }
+                None => quote! {
+                    __fields[#index].decode_as_type().map_err(|e| e.at_idx(#index) #at_variant)?
+                },
+            });
+        let len_check = match kind {
+            CompositeIRKind::Unnamed(fields) => {
+                let len = fields.len();
+                Some(quote! {
+                    if __fields.len() != #len {
+                        return ::core::result::Result::Err(#scale_decode::Error::new(#error_kind::WrongLength {
+                            actual_len: __fields.len(),
+                            expected_len: #len,
+                        }) #at_variant);
+                    }
+                })
+            }
+            CompositeIRKind::NoFields | CompositeIRKind::Named(_) => None,
+        };
+        quote! {
+            #len_check
+            ::core::result::Result::Ok(#construct)
+        }
+    }
+
+    fn fields(&self) -> Vec<&CompositeFieldIR> {
+        let kinds = match &self.kind {
+            TypeIRKind::Struct(composite) => vec![&composite.kind],
+            TypeIRKind::Enum(enum_ir) => enum_ir.variants.iter().map(|(_, v)| &v.kind).collect(),
+        };
+        kinds
+            .into_iter()
+            .flat_map(|kind| match kind {
+                CompositeIRKind::NoFields => vec![],
+                CompositeIRKind::Named(fields) => fields.iter().map(|(_, f)| f).collect(),
+                CompositeIRKind::Unnamed(fields) => fields.iter().collect(),
+            })
+            .collect()
+    }
+
+    /// Decodes all fields and constructs `path` from them. Only structs carry a phantom marker field.
+    fn construct(
+        &self,
+        codec: &syn::Path,
+        path: &TokenStream,
+        err_prefix: &str,
+        kind: &CompositeIRKind,
+        is_struct: bool,
+        settings: &TypeGeneratorSettings,
+    ) -> TokenStream {
+        self.construct_with(path, kind, is_struct, |field, ident, index| {
+            let err = match ident {
+                Some(ident) => format!("Could not decode `{err_prefix}::{ident}`"),
+                None => format!("Could not decode `{err_prefix}.{index}`"),
+            };
+            decode_field(codec, field, &err, settings)
+        })
+    }
+
+    /// Constructs `path` from the decoded fields, given the tokens that decode a field from the field,
+    /// its identifier (`None` for unnamed fields) and its index.
+    fn construct_with(
+        &self,
+        path: &TokenStream,
+        kind: &CompositeIRKind,
+        is_struct: bool,
+        decode: impl Fn(&CompositeFieldIR, Option<&Ident>, usize) -> TokenStream,
+    ) -> TokenStream {
+        let has_marker = is_struct && self.type_params.has_unused_type_params();
+        let marker = quote!(::core::marker::PhantomData);
+        match kind {
+            CompositeIRKind::NoFields => {
+                if has_marker {
+                    quote!(#path(#marker))
+                } else {
+                    quote!(#path)
+                }
+            }
+            CompositeIRKind::Named(fields) => {
+                let fields = fields.iter().enumerate().map(|(i, (name, field))| {
+                    let decode = decode(field, Some(name), i);
+                    quote!(#name: #decode)
+                });
+                let marker = has_marker.then(|| quote!(__ignore: #marker,));
+                quote!(#path { #( #fields, )* #marker })
+            }
+            CompositeIRKind::Unnamed(fields) => {
+                let fields = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| decode(field, None, i));
+                let marker = has_marker.then(|| quote!(#marker,));
+                quote!(#path( #( #fields, )* #marker ))
+            }
+        }
+    }
+}
+
+/// Fields of a struct, referred to via `self`.
+fn self_fields(kind: &CompositeIRKind) -> Vec<Field<'_>> {
+    match kind {
+        CompositeIRKind::NoFields => vec![],
+        CompositeIRKind::Named(fields) => fields
+            .iter()
+            .map(|(name, field)| Field {
+                binding: quote!(&self.#name),
+                name: Some(registry_name(name, &field.original_name)),
+                field,
+            })
+            .collect(),
+        CompositeIRKind::Unnamed(fields) => fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let index = syn::Index::from(i);
+                Field {
+                    binding: quote!(&self.#index),
+                    name: None,
+                    field,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Fields of an enum variant, together with the pattern that binds them.
+fn binding_fields(kind: &CompositeIRKind) -> (TokenStream, Vec<Field<'_>>) {
+    match kind {
+        CompositeIRKind::NoFields => (quote!(), vec![]),
+        CompositeIRKind::Named(fields) => {
+            let names = fields.iter().map(|(name, _)| name);
+            let fields = fields
+                .iter()
+                .map(|(name, field)| Field {
+                    binding: quote!(#name),
+                    name: Some(registry_name(name, &field.original_name)),
+                    field,
+                })
+                .collect();
+            (quote!({ #( #names, )* }), fields)
+        }
+        CompositeIRKind::Unnamed(fields) => {
+            let bindings = (0..fields.len())
+                .map(|i| format_ident!("__binding_{i}"))
+                .collect::<Vec<Ident>>();
+            let fields = fields
+                .iter()
+                .zip(&bindings)
+                .map(|(field, binding)| Field {
+                    binding: quote!(#binding),
+                    name: None,
+                    field,
+                })
+                .collect();
+            (quote!(( #( #bindings, )* )), fields)
+        }
+    }
+}
+
+fn where_clause(bounds: Vec<TokenStream>) -> TokenStream {
+    if bounds.is_empty() {
+        return quote!();
+    }
+    quote!(where #( #bounds, )*)
+}
+
+/// A `scale_encode::Composite` of the given fields, to encode a struct or the fields of a variant.
+fn encode_as_type_composite(scale_encode: &syn::Path, fields: &[Field]) -> TokenStream {
+    let fields = fields.iter().map(|field| {
+        let binding = &field.binding;
+        let name = match &field.name {
+            Some(name) => quote!(::core::option::Option::Some(#name)),
+            None => quote!(::core::option::Option::None),
+        };
+        quote!((#name, #scale_encode::CompositeField::new(#binding)))
+    });
+    quote!(#scale_encode::Composite::new([ #( #fields, )* ].into_iter()))
+}
+
+fn encode_field(codec: &syn::Path, field: &Field, settings: &TypeGeneratorSettings) -> TokenStream {
+    let binding = &field.binding;
+    if field.field.is_compact {
+        let ty = field.field.to_token_stream(settings);
+        quote! {
+            #codec::Encode::encode_to(
+                &<<#ty as #codec::HasCompact>::Type as #codec::EncodeAsRef<'_, #ty>>::RefType::from(#binding),
+                __codec_dest,
+            );
+        }
+    } else {
+        quote!(#codec::Encode::encode_to(#binding, __codec_dest);)
+    }
+}
+
+fn decode_field(
+    codec: &syn::Path,
+    field: &CompositeFieldIR,
+    err: &str,
+    settings: &TypeGeneratorSettings,
+) -> TokenStream {
+    let ty = field.to_token_stream(settings);
+    if field.is_compact {
+        quote! {
+            <<#ty as #codec::HasCompact>::Type as #codec::Decode>::decode(__codec_input)
+                .map_err(|e| e.chain(#err))?
+                .into()
+        }
+    } else {
+        quote! {
+            <#ty as #codec::Decode>::decode(__codec_input).map_err(|e| e.chain(#err))?
+        }
+    }
+}
+
+/// The name of a field or variant in the type registry.
+fn registry_name(ident: &Ident, original_name: &Option<String>) -> String {
+    original_name
+        .clone()
+        .unwrap_or_else(|| ident.unraw().to_string())
+}
@@ -0,0 +1,74 @@
+use proc_macro2::{Ident, TokenStream};
+
+use super::{
+    module_ir::ModuleIR,
+    type_ir::{CompositeFieldIR, CompositeIR, EnumIR, TypeIR},
+};
+
+/// Hooks into the conversion of [`ModuleIR`]s and [`TypeIR`]s into rust tokens.
+///
+/// Each hook receives the intermediate representation of the item and the tokens that are
+/// generated for it by default, and returns the tokens that should be used instead. All hooks
+/// return the default tokens unchanged, unless overridden. This makes it possible to e.g. inject
+/// attributes on fields or add `impl` blocks after types.
+///
+/// Hooks are called bottom up: the default tokens passed to [`TypeEmitter::emit_struct`] already
+/// contain the output of [`TypeEmitter::emit_field`] for each of the fields of the struct.
+pub trait TypeEmitter {
+    /// Called for every module, including the root module.
+    fn emit_module(&self, module: &ModuleIR, default: TokenStream) -> TokenStream {
+        let _ = module;
+        default
+    }
+
+    /// Called for every struct.
+    fn emit_struct(
+        &self,
+        ty: &TypeIR,
+        composite: &CompositeIR,
+        default: TokenStream,
+    ) -> TokenStream {
+        let _ = (ty, composite);
+        default
+    }
+
+    /// Called for every enum.
+    fn emit_enum(&self, ty: &TypeIR, enum_ir: &EnumIR, default: TokenStream) -> TokenStream {
+        let _ = (ty, enum_ir);
+        default
+    }
+
+    /// Called for every variant of an enum. The `__Ignore` variant holding `PhantomData` for unused
+    /// type parameters is not passed to this hook.
+    fn emit_variant(
+        &self,
+        ty: &TypeIR,
+        index: u8,
+        variant: &CompositeIR,
+        default: TokenStream,
+    ) -> TokenStream {
+        let _ = (ty, index, variant);
+        default
+    }
+
+    /// Called for every field of a struct or enum variant. `variant` is the enclosing variant for fields
+    /// of enum variants and `None` for fields of structs. `name` is `None` for unnamed fields.
+    /// Marker fields holding `PhantomData` for unused type parameters are not passed to this hook.
+    fn emit_field(
+        &self,
+        ty: &TypeIR,
+        variant: Option<&CompositeIR>,
+        name: Option<&Ident>,
+        field: &CompositeFieldIR,
+        default: TokenStream,
+    ) -> TokenStream {
+        let _ = (ty, variant, name, field);
+        default
+    }
+}
+
+/// A [`TypeEmitter`] that does not change any of the generated tokens.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultEmitter;
+
+impl TypeEmitter for DefaultEmitter {}
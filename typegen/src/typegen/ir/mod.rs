@@ -1,7 +1,13 @@
 use crate::TypeGeneratorSettings;
 
+mod codec_impls;
+/// Hooks for customizing how the intermediate representation is turned into rust tokens.
+pub mod emitter;
 /// Intermediate Representation of a rust module.
 pub mod module_ir;
+pub(crate) mod serde_json_compat;
+/// Intermediate Representation of a rust type alias.
+pub mod type_alias_ir;
 /// Intermediate Representation of a rust type.
 pub mod type_ir;
 
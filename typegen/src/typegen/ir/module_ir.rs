@@ -1,7 +1,11 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 
-use crate::TypeGeneratorSettings;
+use crate::{TypeGeneratorSettings, TypegenError};
 
+use super::emitter::{DefaultEmitter, TypeEmitter};
+use super::serde_json_compat::{self, SERDE_JSON_COMPAT_MOD};
+use super::type_alias_ir::TypeAliasIR;
 use super::type_ir::TypeIR;
 use super::ToTokensWithSettings;
 use proc_macro2::Span;
@@ -20,30 +24,13 @@ pub struct ModuleIR {
     pub children: BTreeMap<Ident, ModuleIR>,
     /// Types in this module.
     pub types: BTreeMap<scale_info::Path<PortableForm>, (u32, TypeIR)>,
+    /// Type aliases for concrete instantiations of the generic types, keyed by their name.
+    pub aliases: BTreeMap<Ident, TypeAliasIR>,
 }
 
 impl ToTokensWithSettings for ModuleIR {
     fn to_tokens(&self, tokens: &mut TokenStream, settings: &TypeGeneratorSettings) {
-        let name = &self.name;
-        let root_mod = &self.root_mod;
-        let modules = self
-            .children
-            .values()
-            .map(|ir| ir.to_token_stream(settings));
-        let types = self
-            .types
-            .values()
-            .map(|(_, ir)| ir.to_token_stream(settings))
-            .clone();
-
-        tokens.extend(quote! {
-            pub mod #name {
-                use super::#root_mod;
-
-                #( #modules )*
-                #( #types )*
-            }
-        })
+        tokens.extend(self.to_token_stream_with_emitter(settings, &DefaultEmitter));
     }
 }
 
@@ -55,6 +42,7 @@ impl ModuleIR {
             root_mod,
             children: BTreeMap::new(),
             types: BTreeMap::new(),
+            aliases: BTreeMap::new(),
         }
     }
 
@@ -73,6 +61,11 @@ impl ModuleIR {
         self.types.iter().map(|(k, v)| (k, &v.1))
     }
 
+    /// Returns the type aliases for concrete instantiations of generic types.
+    pub fn aliases(&self) -> impl Iterator<Item = &TypeAliasIR> {
+        self.aliases.values()
+    }
+
     /// Returns the root `mod` used for resolving type paths.
     pub fn root_mod(&self) -> &Ident {
         &self.root_mod
@@ -91,4 +84,113 @@ impl ModuleIR {
             .or_insert_with(|| ModuleIR::new(child_ident, self.root_mod.clone()));
         child.get_or_insert_submodule(&namespace[1..])
     }
+
+    /// Translate this module into rust tokens, passing the tokens of all modules and types
+    /// through the hooks of the given [`TypeEmitter`].
+    pub fn to_token_stream_with_emitter(
+        &self,
+        settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
+    ) -> TokenStream {
+        let name = &self.name;
+        let root_mod = &self.root_mod;
+        let modules = self
+            .children
+            .values()
+            .map(|ir| ir.to_token_stream_with_emitter(settings, emitter));
+        let types = self
+            .types
+            .values()
+            .map(|(_, ir)| ir.to_token_stream_with_emitter(settings, emitter));
+        let aliases = self
+            .aliases
+            .values()
+            .map(|alias| alias.to_token_stream(settings));
+        let serde_adapters = self.serde_adapters(settings);
+
+        let tokens = quote! {
+            pub mod #name {
+                use super::#root_mod;
+
+                #( #modules )*
+                #( #types )*
+                #( #aliases )*
+                #serde_adapters
+            }
+        };
+        emitter.emit_module(self, tokens)
+    }
+
+    /// The serde adapters, if this is the module they are generated into.
+    fn serde_adapters(&self, settings: &TypeGeneratorSettings) -> Option<TokenStream> {
+        let serde = settings.serde_json_compat.as_ref()?;
+        (self.name == SERDE_JSON_COMPAT_MOD).then(|| serde_json_compat::adapters(serde, settings))
+    }
+
+    /// Renders this module and all of its submodules into one formatted source file per module.
+    ///
+    /// The returned map goes from relative file paths to the file contents, e.g. `types/mod.rs`
+    /// and `types/pallet_balances/mod.rs` for a root module named `types`. Each file declares its
+    /// child modules with `pub mod child;`, so the generated code can be included via `mod types;`
+    /// next to the `types` directory.
+    pub fn to_files(
+        &self,
+        settings: &TypeGeneratorSettings,
+    ) -> Result<BTreeMap<PathBuf, String>, TypegenError> {
+        self.to_files_with_emitter(settings, &DefaultEmitter)
+    }
+
+    /// Like [`ModuleIR::to_files`], but passes the tokens of all types through the hooks of the given [`TypeEmitter`].
+    ///
+    /// The tokens passed to [`TypeEmitter::emit_module`] contain the module contents only (no `pub mod name { .. }` around them),
+    /// because every module is placed in a file of its own.
+    pub fn to_files_with_emitter(
+        &self,
+        settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
+    ) -> Result<BTreeMap<PathBuf, String>, TypegenError> {
+        let mut files = BTreeMap::new();
+        self.collect_files(PathBuf::new(), settings, emitter, &mut files)?;
+        Ok(files)
+    }
+
+    fn collect_files(
+        &self,
+        parent_dir: PathBuf,
+        settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
+        files: &mut BTreeMap<PathBuf, String>,
+    ) -> Result<(), TypegenError> {
+        let dir = parent_dir.join(self.name.to_string());
+        let root_mod = &self.root_mod;
+        let modules = self.children.keys();
+        let types = self
+            .types
+            .values()
+            .map(|(_, ir)| ir.to_token_stream_with_emitter(settings, emitter));
+        let aliases = self
+            .aliases
+            .values()
+            .map(|alias| alias.to_token_stream(settings));
+        let serde_adapters = self.serde_adapters(settings);
+
+        let tokens = emitter.emit_module(
+            self,
+            quote! {
+                use super::#root_mod;
+
+                #( pub mod #modules; )*
+                #( #types )*
+                #( #aliases )*
+                #serde_adapters
+            },
+        );
+        let file: syn::File = syn::parse2(tokens)?;
+        files.insert(dir.join("mod.rs"), prettyplease::unparse(&file));
+
+        for child in self.children.values() {
+            child.collect_files(dir.clone(), settings, emitter, files)?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,193 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::TypeGeneratorSettings;
+
+use crate::typegen::settings::derives::Derives;
+
+use super::type_ir::TypeIR;
+
+/// Name of the module in the types module that contains the serde adapters and the prelude types generated for
+/// serde JSON compatibility.
+pub(crate) const SERDE_JSON_COMPAT_MOD: &str = "__serde_json_compat";
+
+/// Key under which `scale_value` serializes the bools of a bit sequence.
+const BITVEC_VALUES_KEY: &str = "__bitvec__values__";
+
+/// The path of one of the generated serde adapters as a string, for use in `#[serde(with = "..")]`.
+pub(super) fn adapter_path(adapter: &str, settings: &TypeGeneratorSettings) -> String {
+    format!(
+        "{}::{SERDE_JSON_COMPAT_MOD}::{adapter}",
+        settings.types_mod_ident
+    )
+}
+
+/// The serde adapters that are generated into the [`SERDE_JSON_COMPAT_MOD`] module:
+/// - `newtype`: (de)serializes the single field of a newtype struct or variant as a sequence `[value]`.
+/// - `bits`: (de)serializes the `decoded_bits_type_path` type as `{ "__bitvec__values__": [bool, ..] }`.
+/// - `newtype_bits`: both of the above, for a newtype around the `decoded_bits_type_path` type.
+/// - `unit`: (de)serializes unit structs and unit variants as `[]`.
+/// - `phantom`: (de)serializes the `PhantomData` marker of a struct without other fields as `[]`.
+pub(super) fn adapters(serde: &syn::Path, settings: &TypeGeneratorSettings) -> TokenStream {
+    let alloc = &settings.alloc_crate_path;
+    let result = quote!(::core::result::Result);
+    let values_map = quote!(#alloc::collections::BTreeMap<#alloc::string::String, T>);
+    quote! {
+        pub mod newtype {
+            pub fn serialize<T: #serde::Serialize, S: #serde::Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> #result<S::Ok, S::Error> {
+                #serde::Serialize::serialize(&(value,), serializer)
+            }
+
+            pub fn deserialize<'de, T: #serde::Deserialize<'de>, D: #serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> #result<T, D::Error> {
+                let (value,): (T,) = #serde::Deserialize::deserialize(deserializer)?;
+                #result::Ok(value)
+            }
+        }
+
+        pub mod bits {
+            pub fn serialize<T: #serde::Serialize, S: #serde::Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> #result<S::Ok, S::Error> {
+                let mut map = #serde::Serializer::serialize_map(serializer, ::core::option::Option::Some(1))?;
+                #serde::ser::SerializeMap::serialize_entry(&mut map, #BITVEC_VALUES_KEY, value)?;
+                #serde::ser::SerializeMap::end(map)
+            }
+
+            pub fn deserialize<'de, T: #serde::Deserialize<'de>, D: #serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> #result<T, D::Error> {
+                let map: #values_map = #serde::Deserialize::deserialize(deserializer)?;
+                values(map)
+            }
+
+            pub(super) fn values<T, E: #serde::de::Error>(mut map: #values_map) -> #result<T, E> {
+                map.remove(#BITVEC_VALUES_KEY)
+                    .ok_or_else(|| E::missing_field(#BITVEC_VALUES_KEY))
+            }
+        }
+
+        pub mod newtype_bits {
+            struct Bits<'a, T>(&'a T);
+
+            impl<T: #serde::Serialize> #serde::Serialize for Bits<'_, T> {
+                fn serialize<S: #serde::Serializer>(&self, serializer: S) -> #result<S::Ok, S::Error> {
+                    super::bits::serialize(self.0, serializer)
+                }
+            }
+
+            pub fn serialize<T: #serde::Serialize, S: #serde::Serializer>(
+                value: &T,
+                serializer: S,
+            ) -> #result<S::Ok, S::Error> {
+                super::newtype::serialize(&Bits(value), serializer)
+            }
+
+            pub fn deserialize<'de, T: #serde::Deserialize<'de>, D: #serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> #result<T, D::Error> {
+                let map: #values_map = super::newtype::deserialize(deserializer)?;
+                super::bits::values(map)
+            }
+        }
+
+        pub mod unit {
+            pub fn serialize<S: #serde::Serializer>(serializer: S) -> #result<S::Ok, S::Error> {
+                #serde::Serialize::serialize(&[(); 0], serializer)
+            }
+
+            pub fn deserialize<'de, D: #serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> #result<(), D::Error> {
+                let []: [(); 0] = #serde::Deserialize::deserialize(deserializer)?;
+                #result::Ok(())
+            }
+        }
+
+        pub mod phantom {
+            pub fn serialize<T, S: #serde::Serializer>(
+                _: &::core::marker::PhantomData<T>,
+                serializer: S,
+            ) -> #result<S::Ok, S::Error> {
+                super::unit::serialize(serializer)
+            }
+
+            pub fn deserialize<'de, T, D: #serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> #result<::core::marker::PhantomData<T>, D::Error> {
+                super::unit::deserialize(deserializer).map(|()| ::core::marker::PhantomData)
+            }
+        }
+    }
+}
+
+/// Serde serializes unit structs as `null`, so their `Serialize` and `Deserialize` derives are replaced by impls
+/// that use the `unit` adapter. Returns the remaining derives and the impls.
+pub(super) fn unit_struct_serde_impls(
+    ident: &Ident,
+    derives: &Derives,
+    serde: &syn::Path,
+    settings: &TypeGeneratorSettings,
+) -> (Derives, TokenStream) {
+    let mut derives = derives.clone();
+    let mut take = |name: &str| {
+        let derive = derives
+            .derives()
+            .iter()
+            .find(|derive| derive.segments.last().is_some_and(|s| s.ident == name))
+            .cloned();
+        derive.is_some_and(|derive| derives.remove_derive(&derive))
+    };
+    let serialize = take("Serialize");
+    let deserialize = take("Deserialize");
+
+    let result = quote!(::core::result::Result);
+    let types_mod_ident = &settings.types_mod_ident;
+    let compat_mod = format_ident!("{SERDE_JSON_COMPAT_MOD}");
+    let adapter = quote!(#types_mod_ident::#compat_mod::unit);
+    let serialize_impl = serialize.then(|| {
+        quote! {
+            impl #serde::Serialize for #ident {
+                fn serialize<S: #serde::Serializer>(&self, serializer: S) -> #result<S::Ok, S::Error> {
+                    #adapter::serialize(serializer)
+                }
+            }
+        }
+    });
+    let deserialize_impl = deserialize.then(|| {
+        quote! {
+            impl<'de> #serde::Deserialize<'de> for #ident {
+                fn deserialize<D: #serde::Deserializer<'de>>(deserializer: D) -> #result<Self, D::Error> {
+                    #adapter::deserialize(deserializer).map(|()| #ident)
+                }
+            }
+        }
+    });
+    (derives, quote!(#serialize_impl #deserialize_impl))
+}
+
+impl TypeIR {
+    /// Serde does not infer bounds for the type parameters in fields with a `#[serde(with = "..")]` adapter, so
+    /// the used type parameters of generic types are bound explicitly.
+    pub(super) fn serde_bound(&self, serde: &syn::Path) -> Option<TokenStream> {
+        let params = self.type_params.used_params().collect::<Vec<_>>();
+        if params.is_empty() {
+            return None;
+        }
+        let bound = |bound: TokenStream| {
+            params
+                .iter()
+                .map(|param| quote!(#param: #bound).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let serialize = bound(quote!(#serde::Serialize));
+        let deserialize = bound(quote!(#serde::Deserialize<'de>));
+        Some(quote!( #[serde(bound(serialize = #serialize, deserialize = #deserialize))] ))
+    }
+}
@@ -0,0 +1,27 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+
+use crate::{
+    typegen::{ir::ToTokensWithSettings, type_path::TypePath},
+    TypeGeneratorSettings,
+};
+
+/// Intermediate Representation of a type alias for a concrete instantiation of a generic type,
+/// e.g. `pub type HeaderFor = types::sp_runtime::generic::header::Header<::core::primitive::u32>;`.
+#[derive(Debug, Clone)]
+pub struct TypeAliasIR {
+    /// Name of the alias.
+    pub name: Ident,
+    /// Id of the instantiation in the type registry.
+    pub type_id: u32,
+    /// The aliased type, with all of its generic parameters filled in.
+    pub type_path: TypePath,
+}
+
+impl ToTokensWithSettings for TypeAliasIR {
+    fn to_tokens(&self, tokens: &mut TokenStream, settings: &TypeGeneratorSettings) {
+        let name = &self.name;
+        let ty = self.type_path.to_syn_type(settings);
+        tokens.extend(quote! { pub type #name = #ty; })
+    }
+}
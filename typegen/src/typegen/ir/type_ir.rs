@@ -1,10 +1,18 @@
+use std::borrow::Cow;
+
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 
 use crate::{
     typegen::{
-        ir::ToTokensWithSettings, settings::derives::Derives, type_params::TypeParameters,
-        type_path::TypePath,
+        ir::{
+            emitter::{DefaultEmitter, TypeEmitter},
+            serde_json_compat::{adapter_path, unit_struct_serde_impls},
+            ToTokensWithSettings,
+        },
+        settings::derives::Derives,
+        type_params::TypeParameters,
+        type_path::{TypePath, TypePathInner, TypePathType},
     },
     TypeGeneratorSettings,
 };
@@ -19,6 +27,13 @@ pub struct TypeIR {
     /// whether or not `#[codec(...)]` attributes should be inserted.
     /// Only makes sense if the derives include `Encode`/`Decode`.
     pub insert_codec_attributes: bool,
+    /// Path to the `parity_scale_codec` crate, if `Encode` and `Decode` should be implemented explicitly
+    /// instead of via derive macros.
+    pub explicit_codec_impls: Option<syn::Path>,
+    /// Path to the `scale_encode` crate, if `EncodeAsType` should be implemented explicitly.
+    pub explicit_encode_as_type_impls: Option<syn::Path>,
+    /// Path to the `scale_decode` crate, if `DecodeAsType` should be implemented explicitly.
+    pub explicit_decode_as_type_impls: Option<syn::Path>,
     /// Is this type an enum or struct.
     pub kind: TypeIRKind,
 }
@@ -33,7 +48,7 @@ pub enum TypeIRKind {
 }
 
 impl TypeIR {
-    fn ident(&self) -> &Ident {
+    pub(crate) fn ident(&self) -> &Ident {
         match &self.kind {
             TypeIRKind::Struct(e) => &e.name,
             TypeIRKind::Enum(e) => &e.name,
@@ -57,12 +72,19 @@ pub struct CompositeIR {
     pub kind: CompositeIRKind,
     /// Docs for the composite.
     pub docs: TokenStream,
+    /// Name of the variant in the type registry, if the naming policy changed it.
+    pub original_name: Option<String>,
 }
 
 impl CompositeIR {
     /// Creates a new `CompositeIR`.
     pub fn new(name: Ident, kind: CompositeIRKind, docs: TokenStream) -> Self {
-        Self { name, kind, docs }
+        Self {
+            name,
+            kind,
+            docs,
+            original_name: None,
+        }
     }
 }
 
@@ -70,9 +92,11 @@ impl CompositeIR {
 #[derive(Debug, Clone)]
 pub struct EnumIR {
     /// Docs for the enum.
-    pub(crate) docs: TokenStream,
-    pub(crate) name: Ident,
-    pub(crate) variants: Vec<(u8, CompositeIR)>,
+    pub docs: TokenStream,
+    /// Enum name.
+    pub name: Ident,
+    /// Variants of the enum, together with their codec index.
+    pub variants: Vec<(u8, CompositeIR)>,
 }
 
 /// Named, Unnamed or NoFields.
@@ -119,6 +143,8 @@ pub struct CompositeFieldIR {
     pub is_compact: bool,
     /// Is this field actually boxed? e.g. `Box<type_path>` instead of just `type_path`.
     pub is_boxed: bool,
+    /// Name of the field in the type registry, if the naming policy changed it.
+    pub original_name: Option<String>,
 }
 
 impl CompositeFieldIR {
@@ -128,6 +154,7 @@ impl CompositeFieldIR {
             type_path,
             is_compact,
             is_boxed,
+            original_name: None,
         }
     }
 
@@ -135,10 +162,68 @@ impl CompositeFieldIR {
     fn compact_attr(&self) -> Option<TokenStream> {
         self.is_compact.then(|| quote!( #[codec(compact)] ))
     }
+
+    /// Returns a `#[serde(with = "..")]` attribute with one of the generated serde adapters, if the field does not
+    /// serialize like a `scale_value::Value` on its own.
+    fn serde_attr(
+        &self,
+        is_newtype: bool,
+        settings: &TypeGeneratorSettings,
+    ) -> Option<TokenStream> {
+        settings.serde_json_compat.as_ref()?;
+        let is_bits = matches!(
+            self.type_path.inner(),
+            TypePathInner::Type(TypePathType::BitVec { .. })
+        );
+        let adapter = match (is_newtype, is_bits) {
+            (false, false) => return None,
+            (true, false) => "newtype",
+            (false, true) => "bits",
+            (true, true) => "newtype_bits",
+        };
+        Some(serde_with(adapter, settings))
+    }
+}
+
+/// Returns a `#[serde(rename = "..")]` attribute for fields and variants that were renamed by the naming policy.
+fn serde_rename(
+    original_name: Option<&String>,
+    settings: &TypeGeneratorSettings,
+) -> Option<TokenStream> {
+    let insert = settings.naming.serde_rename || settings.serde_json_compat.is_some();
+    original_name
+        .filter(|_| insert)
+        .map(|name| quote!( #[serde(rename = #name)] ))
+}
+
+/// Returns a `#[serde(with = "..")]` attribute with one of the generated serde adapters.
+fn serde_with(adapter: &str, settings: &TypeGeneratorSettings) -> TokenStream {
+    let adapter = adapter_path(adapter, settings);
+    quote!( #[serde(with = #adapter)] )
+}
+
+/// Returns a `#[serde(skip)]` attribute for phantom markers, if serde JSON compatibility is enabled.
+fn serde_skip(settings: &TypeGeneratorSettings) -> Option<TokenStream> {
+    settings
+        .serde_json_compat
+        .is_some()
+        .then(|| quote!( #[serde(skip)] ))
 }
 
 impl ToTokensWithSettings for TypeIR {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream, settings: &TypeGeneratorSettings) {
+        tokens.extend(self.to_token_stream_with_emitter(settings, &DefaultEmitter));
+    }
+}
+
+impl TypeIR {
+    /// Translate this type into rust tokens, passing the tokens of the type, its variants and its fields
+    /// through the hooks of the given [`TypeEmitter`].
+    pub fn to_token_stream_with_emitter(
+        &self,
+        settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
+    ) -> TokenStream {
         let derives = &self.derives;
         let type_params = &self.type_params;
         let docs = self.docs();
@@ -146,92 +231,135 @@ impl ToTokensWithSettings for TypeIR {
 
         match &self.kind {
             TypeIRKind::Struct(composite_ir) => {
-                let phantom_data = self.type_params.unused_params_phantom_data();
-                let fields = composite_ir.struct_field_tokens(
-                    phantom_data,
-                    self.insert_codec_attributes,
-                    settings,
-                );
+                let fields = self.struct_field_tokens(composite_ir, settings, emitter);
                 let trailing_semicolon = matches!(
                     composite_ir.kind,
                     CompositeIRKind::NoFields | CompositeIRKind::Unnamed(_)
                 )
                 .then(|| quote!(;));
 
+                let codec_impls = self.explicit_impls(settings);
+                let is_unit = matches!(composite_ir.kind, CompositeIRKind::NoFields)
+                    && !self.type_params.has_unused_type_params();
+                let (derives, serde_impls) = match &settings.serde_json_compat {
+                    Some(serde) if is_unit => {
+                        let (derives, impls) =
+                            unit_struct_serde_impls(ident, derives, serde, settings);
+                        (Cow::Owned(derives), Some(impls))
+                    }
+                    _ => (Cow::Borrowed(derives), None),
+                };
+                let serde_bound = settings
+                    .serde_json_compat
+                    .as_ref()
+                    .and_then(|serde| self.serde_bound(serde));
                 let tokenstream = quote! {
                     #derives
+                    #serde_bound
                     #docs
                     pub struct #ident #type_params #fields #trailing_semicolon
+                    #codec_impls
+                    #serde_impls
                 };
-                tokens.extend(tokenstream);
+                emitter.emit_struct(self, composite_ir, tokenstream)
             }
             TypeIRKind::Enum(enum_ir) => {
                 let mut variants = enum_ir
                     .variants
                     .iter()
                     .map(|(index, composite)| {
-                        let index = proc_macro2::Literal::u8_unsuffixed(*index);
+                        let codec_index = proc_macro2::Literal::u8_unsuffixed(*index);
                         let ident = &composite.name;
                         let variant_docs = &composite.docs;
-                        let fields =
-                            composite.enum_field_tokens(self.insert_codec_attributes, settings);
+                        let fields = self.enum_field_tokens(composite, settings, emitter);
                         let codec_index = self
                             .insert_codec_attributes
-                            .then(|| quote!(#[codec(index = #index)]));
-                        quote! {
+                            .then(|| quote!(#[codec(index = #codec_index)]));
+                        let serde_rename = serde_rename(composite.original_name.as_ref(), settings);
+                        // Serde leaves out the content of unit variants, but `scale_value` has `"values": []`.
+                        let serde_unit = (settings.serde_json_compat.is_some()
+                            && matches!(composite.kind, CompositeIRKind::NoFields))
+                        .then(|| serde_with("unit", settings));
+                        let tokenstream = quote! {
                             #codec_index
+                            #serde_rename
+                            #serde_unit
                             #variant_docs
                             #ident #fields
-                        }
+                        };
+                        emitter.emit_variant(self, *index, composite, tokenstream)
                     })
                     .collect::<Vec<_>>();
 
                 if let Some(phantom) = self.type_params.unused_params_phantom_data() {
                     // Note: not sure if a #[codec(skip)] is appropriate here or not.
                     // let codec_skip = self.insert_codec_attributes.then(|| quote!(#[codec(skip)]));
+                    let serde_skip = serde_skip(settings);
                     variants.push(quote! {
+                        #serde_skip
                         __Ignore(#phantom)
                     })
                 }
 
+                let codec_impls = self.explicit_impls(settings);
+                let serde_attrs = settings.serde_json_compat.as_ref().map(|serde| {
+                    let serde_bound = self.serde_bound(serde);
+                    quote! {
+                        #[serde(tag = "name", content = "values")]
+                        #serde_bound
+                    }
+                });
                 let tokenstream = quote! {
                     #derives
+                    #serde_attrs
                     #docs
                     pub enum #ident #type_params {
                         #( #variants, )*
                     }
+                    #codec_impls
                 };
-                tokens.extend(tokenstream);
+                emitter.emit_enum(self, enum_ir, tokenstream)
             }
         }
     }
-}
 
-impl CompositeIR {
     fn struct_field_tokens(
         &self,
-        phantom_data: Option<syn::TypePath>,
-        insert_codec_attributes: bool,
+        composite: &CompositeIR,
         settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
     ) -> TokenStream {
-        match &self.kind {
+        let insert_codec_attributes = self.insert_codec_attributes;
+        let phantom_data = self.type_params.unused_params_phantom_data();
+        let serde_skip = serde_skip(settings);
+        match &composite.kind {
             CompositeIRKind::NoFields => {
                 if let Some(phantom_data) = phantom_data {
-                    quote! { ( pub #phantom_data ) }
+                    // A skipped single field would make serde serialize `null`, instead of an empty sequence.
+                    let serde_attr = settings
+                        .serde_json_compat
+                        .as_ref()
+                        .map(|_| serde_with("phantom", settings));
+                    quote! { ( #serde_attr pub #phantom_data ) }
                 } else {
-                    quote! {}
+                    quote!()
                 }
             }
             CompositeIRKind::Named(fields) => {
                 let fields = fields.iter().map(|(name, field)| {
                     let compact_attr = field.compact_attr().filter(|_| insert_codec_attributes);
-                    let field = field.to_token_stream(settings);
-                    quote! { #compact_attr pub #name: #field }
+                    let serde_attr = field.serde_attr(false, settings);
+                    let serde_rename = serde_rename(field.original_name.as_ref(), settings);
+                    let field_ty = field.to_token_stream(settings);
+                    let tokenstream =
+                        quote! { #compact_attr #serde_attr #serde_rename pub #name: #field_ty };
+                    emitter.emit_field(self, None, Some(name), field, tokenstream)
                 });
                 let marker = phantom_data.map(|phantom_data| {
                     let codec_skip = insert_codec_attributes.then(|| quote!(#[codec(skip)]));
                     quote!(
                         #codec_skip
+                        #serde_skip
                         pub __ignore: #phantom_data
                     )
                 });
@@ -243,15 +371,19 @@ impl CompositeIR {
                 )
             }
             CompositeIRKind::Unnamed(fields) => {
+                let is_newtype = fields.len() == 1 && phantom_data.is_none();
                 let fields = fields.iter().map(|field| {
                     let compact_attr = field.compact_attr().filter(|_| insert_codec_attributes);
-                    let field = field.to_token_stream(settings);
-                    quote! { #compact_attr pub #field }
+                    let serde_attr = field.serde_attr(is_newtype, settings);
+                    let field_ty = field.to_token_stream(settings);
+                    let tokenstream = quote! { #compact_attr #serde_attr pub #field_ty };
+                    emitter.emit_field(self, None, None, field, tokenstream)
                 });
                 let marker = phantom_data.map(|phantom_data| {
                     let codec_skip = insert_codec_attributes.then(|| quote!(#[codec(skip)]));
                     quote!(
                         #codec_skip
+                        #serde_skip
                         pub #phantom_data
                     )
                 });
@@ -267,24 +399,33 @@ impl CompositeIR {
 
     fn enum_field_tokens(
         &self,
-        insert_codec_attributes: bool,
+        composite: &CompositeIR,
         settings: &TypeGeneratorSettings,
+        emitter: &dyn TypeEmitter,
     ) -> TokenStream {
-        match &self.kind {
-            CompositeIRKind::NoFields => quote! {},
+        let insert_codec_attributes = self.insert_codec_attributes;
+        match &composite.kind {
+            CompositeIRKind::NoFields => quote!(),
             CompositeIRKind::Named(ref fields) => {
                 let fields = fields.iter().map(|(name, field)| {
                     let compact_attr = field.compact_attr().filter(|_| insert_codec_attributes);
-                    let field = field.to_token_stream(settings);
-                    quote! { #compact_attr #name: #field }
+                    let serde_attr = field.serde_attr(false, settings);
+                    let serde_rename = serde_rename(field.original_name.as_ref(), settings);
+                    let field_ty = field.to_token_stream(settings);
+                    let tokenstream =
+                        quote! { #compact_attr #serde_attr #serde_rename #name: #field_ty };
+                    emitter.emit_field(self, Some(composite), Some(name), field, tokenstream)
                 });
                 quote!( { #( #fields, )* } )
             }
             CompositeIRKind::Unnamed(ref fields) => {
+                let is_newtype = fields.len() == 1;
                 let fields = fields.iter().map(|field| {
                     let compact_attr = field.compact_attr().filter(|_| insert_codec_attributes);
-                    let field = field.to_token_stream(settings);
-                    quote! { #compact_attr #field }
+                    let serde_attr = field.serde_attr(is_newtype, settings);
+                    let field_ty = field.to_token_stream(settings);
+                    let tokenstream = quote! { #compact_attr #serde_attr #field_ty };
+                    emitter.emit_field(self, Some(composite), None, field, tokenstream)
                 });
                 quote! { ( #( #fields, )* ) }
             }
@@ -294,7 +435,7 @@ impl CompositeIR {
 
 impl ToTokensWithSettings for CompositeFieldIR {
     fn to_tokens(&self, tokens: &mut TokenStream, settings: &TypeGeneratorSettings) {
-        let ty_path = &self.type_path.to_syn_type(&settings.alloc_crate_path);
+        let ty_path = &self.type_path.to_syn_type(settings);
         if self.is_boxed {
             let alloc_path = &settings.alloc_crate_path;
             tokens.extend(quote! { #alloc_path::boxed::Box<#ty_path> })
@@ -1,4 +1,8 @@
-use std::collections::btree_map::Entry;
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    collections::{btree_map::Entry, BTreeSet, HashSet},
+};
 
 use crate::{
     utils::{sanity_pass, types_equal},
@@ -6,40 +10,63 @@ use crate::{
 };
 
 use self::{
+    derive_inference::{drop_infeasible_derives, DroppedDerive},
     ir::module_ir::ModuleIR,
+    ir::serde_json_compat::SERDE_JSON_COMPAT_MOD,
     ir::type_ir::{CompositeFieldIR, CompositeIR, CompositeIRKind, EnumIR, TypeIR, TypeIRKind},
+    module_layout::layout_paths,
+    monomorphize::{monomorphized_paths, MonomorphizedPaths},
     settings::{
-        derives::{Derives, FlatDerivesRegistry},
-        TypeGeneratorSettings,
+        derives::{collect_type_ids_until, Derives, FlatDerivesRegistry},
+        substitutes::PathSegments,
+        ModuleLayout, TypeGeneratorSettings, TypeRoot,
     },
+    type_aliases::insert_instantiation_aliases,
     type_params::TypeParameters,
     type_path::{TypeParameter, TypePath, TypePathType},
 };
 
 use proc_macro2::{Ident, TokenStream};
-use quote::quote;
-use scale_info::{form::PortableForm, PortableRegistry, Type, TypeDef};
-use syn::parse_quote;
+use quote::{quote, ToTokens};
+use scale_info::{
+    form::PortableForm, PortableRegistry, PortableType, Type, TypeDef, TypeDefPrimitive,
+};
+use syn::{ext::IdentExt, parse_quote};
 
+/// Inference of the derives that are feasible for the generated types.
+pub mod derive_inference;
 /// Custom error types.
 pub mod error;
 /// Intermediate representation of types and modules.
 pub mod ir;
+/// Layout strategies for the module hierarchy of the generated types.
+mod module_layout;
+/// Concrete types for the instantiations of generic types.
+pub mod monomorphize;
 /// Settings passed into the `TypeGenerator`.
 pub mod settings;
+/// Type aliases for concrete instantiations of generic types.
+mod type_aliases;
 /// Logic for dealing with used and unused generic type parameters.
 pub mod type_params;
 /// Type path definition and conversion into tokens.
 pub mod type_path;
+/// Generation of TypeScript type definitions.
+pub mod typescript;
 /// Utility functions to validate that type paths in the user defined
 /// derives and substitutes exist in a type registry.
 pub mod validation;
 
 /// An interface for generating a types module.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct TypeGenerator<'a> {
     type_registry: &'a PortableRegistry,
     settings: &'a TypeGeneratorSettings,
+    /// Computed on first use, see [`TypeGenerator::monomorphized_paths`].
+    monomorphized_paths: OnceCell<MonomorphizedPaths>,
+    /// If false, type paths are resolved without the settings that only exist for rust paths, like
+    /// `compact_type_path`. This is used for the TypeScript output.
+    rust_paths: bool,
 }
 
 impl<'a> TypeGenerator<'a> {
@@ -48,6 +75,19 @@ impl<'a> TypeGenerator<'a> {
         Self {
             type_registry,
             settings,
+            monomorphized_paths: OnceCell::new(),
+            rust_paths: true,
+        }
+    }
+
+    /// A type generator for the same type registry with other settings.
+    fn with_settings<'b>(&self, settings: &'b TypeGeneratorSettings) -> TypeGenerator<'b>
+    where
+        'a: 'b,
+    {
+        TypeGenerator {
+            rust_paths: self.rust_paths,
+            ..TypeGenerator::new(self.type_registry, settings)
         }
     }
 
@@ -68,7 +108,25 @@ impl<'a> TypeGenerator<'a> {
 
     /// Generate a module containing all types defined in the supplied type registry.
     pub fn generate_types_mod(&self) -> Result<ModuleIR, TypegenError> {
+        self.generate_types_mod_with_dropped_derives()
+            .map(|(module, _)| module)
+    }
+
+    /// Generate a module containing all types defined in the supplied type registry, together with a report
+    /// of the derives that were dropped from types that cannot derive them. Derives are only dropped if
+    /// `infer_derives` is set in the settings.
+    pub fn generate_types_mod_with_dropped_derives(
+        &self,
+    ) -> Result<(ModuleIR, Vec<DroppedDerive>), TypegenError> {
         sanity_pass(self.type_registry)?;
+        self.settings.validate_renames()?;
+
+        if self.settings.module_layout != ModuleLayout::Nested {
+            let settings = self.settings_with_module_layout()?;
+            return self
+                .with_settings(&settings)
+                .generate_types_mod_with_dropped_derives();
+        }
 
         let flat_derives_registry = self
             .settings
@@ -81,25 +139,56 @@ impl<'a> TypeGenerator<'a> {
             self.settings.types_mod_ident.clone(),
         );
 
+        // The serde adapters are generated into this module, even if it contains no prelude types.
+        if self.settings.serde_json_compat.is_some() {
+            root_mod.get_or_insert_submodule(&[SERDE_JSON_COMPAT_MOD.to_string()]);
+        }
+
+        let reachable_type_ids = self.reachable_type_ids()?;
+
         for ty in &self.type_registry.types {
-            let path = &ty.ty.path;
-            // Don't generate a type if it was substituted - the target type might
-            // not be in the type registry + our resolution already performs the substitution.
-            if self.settings.substitutes.contains(&path.segments) {
+            if !self.is_generated(ty, &reachable_type_ids) {
                 continue;
             }
 
-            let namespace = path.namespace();
-            // prelude types e.g. Option/Result have no namespace, so we don't generate them
-            if namespace.is_empty() {
-                continue;
-            }
+            // Concrete types of monomorphized instantiations are keyed by their own path, because all instantiations
+            // of a generic type share the path in the registry.
+            let monomorphized_path = self.monomorphized_path(ty.id)?;
+            let path = &match &monomorphized_path {
+                Some(segments) => scale_info::Path {
+                    segments: segments.clone(),
+                },
+                None => ty.ty.path.clone(),
+            };
+            let generated_path = match &monomorphized_path {
+                Some(segments) => Cow::Borrowed(segments.as_slice()),
+                None => self.settings.generated_path(&path.segments),
+            };
+            let namespace = &generated_path[..generated_path.len() - 1];
 
             // if the type is not a builtin type, insert it into the respective module
             let ty_id = ty.id;
-            if let Some(type_ir) = self.create_type_ir(&ty.ty, &flat_derives_registry)? {
+            let type_ir = match &monomorphized_path {
+                Some(segments) => {
+                    self.create_monomorphized_type_ir(&ty.ty, segments, &flat_derives_registry)?
+                }
+                None => self.create_type_ir(&ty.ty, &flat_derives_registry)?,
+            };
+            if let Some(type_ir) = type_ir {
                 // Create the module this type should go into
                 let innermost_module = root_mod.get_or_insert_submodule(namespace);
+                // Renaming can move a type next to another type with the same name.
+                let name_taken = innermost_module
+                    .types
+                    .iter()
+                    .any(|(other_path, (_, other))| {
+                        other_path != path && other.ident() == type_ir.ident()
+                    });
+                if name_taken {
+                    return Err(TypegenError::DuplicateRenamedTypePath(
+                        generated_path.join("::"),
+                    ));
+                }
                 match innermost_module.types.entry(path.clone()) {
                     Entry::Vacant(e) => {
                         e.insert((ty_id, type_ir));
@@ -117,7 +206,141 @@ impl<'a> TypeGenerator<'a> {
             }
         }
 
-        Ok(root_mod)
+        if self.settings.instantiation_aliases && !self.settings.monomorphize {
+            insert_instantiation_aliases(self, &mut root_mod, &reachable_type_ids)?;
+        }
+
+        let dropped_derives = if self.settings.infer_derives {
+            drop_infeasible_derives(&mut root_mod, self.settings)
+        } else {
+            vec![]
+        };
+
+        Ok((root_mod, dropped_derives))
+    }
+
+    /// Returns a copy of the settings, in which the `module_layout` is resolved into `renames` for the types
+    /// generated from the type registry. [`TypeGenerator::generate_types_mod`] does this on its own, but
+    /// type paths resolved outside of it should use a [`TypeGenerator`] with these settings to match
+    /// the laid out types.
+    pub fn settings_with_module_layout(&self) -> Result<TypeGeneratorSettings, TypegenError> {
+        let mut settings = self.settings.clone();
+        if settings.module_layout == ModuleLayout::Nested {
+            return Ok(settings);
+        }
+
+        let reachable_type_ids = self.reachable_type_ids()?;
+        let registry_paths = self
+            .type_registry
+            .types
+            .iter()
+            // prelude types that are generated for serde JSON compatibility keep their own module
+            .filter(|ty| {
+                self.is_generated(ty, &reachable_type_ids) && !ty.ty.path.namespace().is_empty()
+            })
+            .map(|ty| &ty.ty.path.segments)
+            .collect::<BTreeSet<_>>();
+        let generated_paths = registry_paths
+            .iter()
+            .map(|path| settings.generated_path(path).to_vec())
+            .collect();
+
+        let laid_out = layout_paths(&settings.module_layout, &generated_paths);
+        for registry_path in registry_paths {
+            let laid_out_path = &laid_out[&*settings.generated_path(registry_path)];
+            if laid_out_path != registry_path {
+                settings
+                    .renames
+                    .insert(registry_path.clone(), laid_out_path.clone());
+            }
+        }
+        settings.module_layout = ModuleLayout::Nested;
+        Ok(settings)
+    }
+
+    /// The paths of the concrete types that are generated for the instantiations of generic types,
+    /// if `monomorphize` is set in the settings. They are computed once and then reused by this generator.
+    pub fn monomorphized_paths(&self) -> Result<&MonomorphizedPaths, TypegenError> {
+        if let Some(paths) = self.monomorphized_paths.get() {
+            return Ok(paths);
+        }
+        let paths = if self.settings.monomorphize {
+            monomorphized_paths(self)?
+        } else {
+            MonomorphizedPaths::new()
+        };
+        Ok(self.monomorphized_paths.get_or_init(|| paths))
+    }
+
+    /// The path of the concrete type for an instantiation of a generic type, if `monomorphize` is set in the settings.
+    fn monomorphized_path(&self, type_id: u32) -> Result<Option<PathSegments>, TypegenError> {
+        if !self.settings.monomorphize {
+            return Ok(None);
+        }
+        Ok(self.monomorphized_paths()?.get(&type_id).cloned())
+    }
+
+    /// Returns true if a struct or enum is generated for the type.
+    fn is_generated(&self, ty: &PortableType, reachable_type_ids: &Option<HashSet<u32>>) -> bool {
+        if let Some(reachable_type_ids) = reachable_type_ids {
+            if !reachable_type_ids.contains(&ty.id) {
+                return false;
+            }
+        }
+
+        let path = &ty.ty.path;
+        // Don't generate a type if it was substituted - the target type might
+        // not be in the type registry + our resolution already performs the substitution.
+        if self.settings.substitutes.contains(&path.segments) {
+            return false;
+        }
+
+        // prelude types e.g. Option/Result have no namespace, so we don't generate them, unless they are
+        // generated into the serde compat module
+        (!path.namespace().is_empty() || self.settings.generated_path(&path.segments).len() > 1)
+            && matches!(ty.ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_))
+    }
+
+    /// The ids of all types that are reachable from the roots in the settings.
+    /// Returns `None` if no roots are set, because then all types are generated.
+    fn reachable_type_ids(&self) -> Result<Option<HashSet<u32>>, TypegenError> {
+        if self.settings.roots.is_empty() {
+            return Ok(None);
+        }
+
+        let mut reachable_type_ids = HashSet::new();
+        for root in &self.settings.roots {
+            match root {
+                TypeRoot::Id(id) => {
+                    if self.type_registry.resolve(*id).is_none() {
+                        return Err(TypegenError::TypeNotFound(*id));
+                    }
+                    self.collect_reachable_type_ids(*id, &mut reachable_type_ids);
+                }
+                TypeRoot::Path(pattern) => {
+                    let mut matched = false;
+                    for ty in &self.type_registry.types {
+                        if !ty.ty.path.is_empty() && pattern.matches(&ty.ty.path.segments) {
+                            matched = true;
+                            self.collect_reachable_type_ids(ty.id, &mut reachable_type_ids);
+                        }
+                    }
+                    if !matched {
+                        return Err(TypegenError::RootNotFound(pattern.to_string()));
+                    }
+                }
+            }
+        }
+        Ok(Some(reachable_type_ids))
+    }
+
+    /// Collects the id of the type and the ids of all types that are (transitively) contained in it.
+    /// The walk stops at substituted types: their fields are never generated, only their type params
+    /// can be passed on to the substitute.
+    fn collect_reachable_type_ids(&self, id: u32, reachable_type_ids: &mut HashSet<u32>) {
+        collect_type_ids_until(id, self.type_registry, reachable_type_ids, &|ty| {
+            self.settings.substitutes.contains(&ty.path.segments)
+        });
     }
 
     /// Creates an intermediate representation of a type that can later be converted into rust tokens.
@@ -125,19 +348,51 @@ impl<'a> TypeGenerator<'a> {
         &self,
         ty: &Type<PortableForm>,
         flat_derives_registry: &FlatDerivesRegistry,
+    ) -> Result<Option<TypeIR>, TypegenError> {
+        let type_params = if self.settings.original_type_param_names {
+            TypeParameters::from_scale_info_with_original_names(
+                &ty.type_params,
+                &self.settings.types_mod_ident,
+            )
+        } else {
+            TypeParameters::from_scale_info(&ty.type_params)
+        };
+        let generated_path = self.settings.generated_path(&ty.path.segments);
+        self.create_type_ir_with(
+            ty,
+            generated_path.last(),
+            type_params,
+            flat_derives_registry,
+        )
+    }
+
+    /// Creates the intermediate representation of the concrete type for an instantiation of a generic type,
+    /// given the path from [`TypeGenerator::monomorphized_paths`]. The fields have concrete types instead of
+    /// generic parameters.
+    fn create_monomorphized_type_ir(
+        &self,
+        ty: &Type<PortableForm>,
+        monomorphized_path: &[String],
+        flat_derives_registry: &FlatDerivesRegistry,
+    ) -> Result<Option<TypeIR>, TypegenError> {
+        let type_params = TypeParameters::from_scale_info(&[]);
+        let name = monomorphized_path.last();
+        self.create_type_ir_with(ty, name, type_params, flat_derives_registry)
+    }
+
+    fn create_type_ir_with(
+        &self,
+        ty: &Type<PortableForm>,
+        name: Option<&String>,
+        mut type_params: TypeParameters,
+        flat_derives_registry: &FlatDerivesRegistry,
     ) -> Result<Option<TypeIR>, TypegenError> {
         // if the type is some builtin, early return, we are only interested in generating structs and enums.
         if !matches!(ty.type_def, TypeDef::Composite(_) | TypeDef::Variant(_)) {
             return Ok(None);
         }
 
-        let mut type_params = TypeParameters::from_scale_info(&ty.type_params);
-
-        let name = ty
-            .path
-            .ident()
-            .map(|e| syn::parse_str::<Ident>(&e))
-            .expect(
+        let name = name.map(|e| syn::parse_str::<Ident>(e)).expect(
             "Structs and enums should have a name. Checked with namespace.is_empty() above. qed;",
         )?;
 
@@ -152,19 +407,31 @@ impl<'a> TypeGenerator<'a> {
                     could_derive_as_compact = true;
                 }
 
-                TypeIRKind::Struct(CompositeIR { name, kind, docs })
+                ensure_unique_names(ty, kind_names(&kind))?;
+                TypeIRKind::Struct(CompositeIR::new(name, kind, docs))
             }
             TypeDef::Variant(variant) => {
                 let variants = variant
                     .variants
                     .iter()
                     .map(|v| {
-                        let name = syn::parse_str::<Ident>(&v.name)?;
+                        let name = self.settings.naming.variant_ident(&v.name)?;
                         let kind = self.create_composite_ir_kind(&v.fields, &mut type_params)?;
+                        ensure_unique_names(ty, kind_names(&kind))?;
                         let docs = self.docs_from_scale_info(&v.docs);
-                        Ok((v.index, CompositeIR { kind, name, docs }))
+                        let original_name = original_name(&name, &v.name);
+                        Ok((
+                            v.index,
+                            CompositeIR {
+                                kind,
+                                name,
+                                docs,
+                                original_name,
+                            },
+                        ))
                     })
                     .collect::<Result<Vec<(u8, CompositeIR)>, TypegenError>>()?;
+                ensure_unique_names(ty, variants.iter().map(|(_, v)| &v.name))?;
                 TypeIRKind::Enum(EnumIR {
                     name,
                     variants,
@@ -179,12 +446,20 @@ impl<'a> TypeGenerator<'a> {
             self.add_as_compact_derive(&mut derives);
         }
 
-        let type_ir = TypeIR {
+        let mut type_ir = TypeIR {
             kind,
             derives,
             type_params,
-            insert_codec_attributes: self.settings.insert_codec_attributes,
+            insert_codec_attributes: self.insert_codec_attributes(),
+            explicit_codec_impls: self.settings.explicit_codec_impls.clone(),
+            explicit_encode_as_type_impls: self.settings.explicit_encode_as_type_impls.clone(),
+            explicit_decode_as_type_impls: self.settings.explicit_decode_as_type_impls.clone(),
         };
+        replace_name_matching_derives(
+            || ty.path.to_string(),
+            &mut type_ir,
+            flat_derives_registry.default_derives(),
+        )?;
         Ok(Some(type_ir))
     }
 
@@ -218,7 +493,7 @@ impl<'a> TypeGenerator<'a> {
                 .iter()
                 .map(|field| {
                     let field_name = field.name.as_ref().unwrap();
-                    let ident = syn::parse_str::<Ident>(field_name)?;
+                    let ident = self.settings.naming.field_ident(field_name)?;
 
                     let path = self.resolve_field_type_path(
                         field.ty.id,
@@ -236,7 +511,9 @@ impl<'a> TypeGenerator<'a> {
                         type_params.mark_used(param);
                     }
 
-                    Ok((ident, CompositeFieldIR::new(path, is_compact, is_boxed)))
+                    let mut field_ir = CompositeFieldIR::new(path, is_compact, is_boxed);
+                    field_ir.original_name = original_name(&ident, field_name);
+                    Ok((ident, field_ir))
                 })
                 .collect::<Result<Vec<(Ident, CompositeFieldIR)>, TypegenError>>()?;
             Ok(CompositeIRKind::Named(named_fields))
@@ -278,12 +555,28 @@ impl<'a> TypeGenerator<'a> {
         if composite.kind.could_derive_as_compact() {
             self.add_as_compact_derive(&mut derives)
         }
-        TypeIR {
+        let mut type_ir = TypeIR {
             type_params: TypeParameters::from_scale_info(&[]),
             derives,
-            insert_codec_attributes: self.settings.insert_codec_attributes,
+            insert_codec_attributes: self.insert_codec_attributes(),
+            explicit_codec_impls: self.settings.explicit_codec_impls.clone(),
+            explicit_encode_as_type_impls: self.settings.explicit_encode_as_type_impls.clone(),
+            explicit_decode_as_type_impls: self.settings.explicit_decode_as_type_impls.clone(),
             kind: TypeIRKind::Struct(composite.clone()),
-        }
+        };
+        replace_name_matching_derives(
+            || composite.name.to_string(),
+            &mut type_ir,
+            self.settings.derives.default_derives(),
+        )
+        .expect("default derives are always replaced; qed");
+        type_ir
+    }
+
+    /// Codec attributes only compile together with the `Encode`/`Decode` derive macros,
+    /// so they are left out if these traits are implemented explicitly.
+    fn insert_codec_attributes(&self) -> bool {
+        self.settings.insert_codec_attributes && self.settings.explicit_codec_impls.is_none()
     }
 
     /// Adds a AsCompact derive, if a path to AsCompact trait/derive macro set in settings.
@@ -331,6 +624,7 @@ impl<'a> TypeGenerator<'a> {
         parent_type_params: &[TypeParameter],
         original_name: Option<&str>,
     ) -> Result<TypePath, TypegenError> {
+        #[allow(clippy::unnecessary_map_or)] // `Option::is_none_or` is above the MSRV
         if let Some(parent_type_param) = parent_type_params.iter().find(|tp| {
             tp.concrete_type_id == id
                 && original_name.map_or(true, |original_name| tp.original_name == original_name)
@@ -339,10 +633,11 @@ impl<'a> TypeGenerator<'a> {
             return Ok(type_path);
         }
 
+        let mut id = id;
         let mut ty = self.resolve_type(id)?;
 
         if ty.path.ident() == Some("Cow".to_string()) {
-            let inner_ty_id = ty.type_params[0]
+            id = ty.type_params[0]
                 .ty
                 .ok_or_else(|| {
                     TypegenError::InvalidType(
@@ -350,7 +645,7 @@ impl<'a> TypeGenerator<'a> {
                     )
                 })?
                 .id;
-            ty = self.resolve_type(inner_ty_id)?
+            ty = self.resolve_type(id)?
         }
 
         let params: Vec<TypePath> = ty
@@ -363,10 +658,33 @@ impl<'a> TypeGenerator<'a> {
 
         let ty = match &ty.type_def {
             TypeDef::Composite(_) | TypeDef::Variant(_) => {
-                self.type_path_maybe_with_substitutes(&ty.path, &params)
+                self.type_path_for_type_id(id, &ty.path, &params)?
             }
-            TypeDef::Primitive(primitive) => TypePathType::Primitive {
-                def: primitive.clone(),
+            TypeDef::Primitive(primitive) => match primitive {
+                TypeDefPrimitive::U256 | TypeDefPrimitive::I256 if !self.rust_paths => {
+                    TypePathType::Primitive {
+                        def: primitive.clone(),
+                    }
+                }
+                TypeDefPrimitive::U256 => TypePathType::MappedPrimitive {
+                    def: primitive.clone(),
+                    ty: self
+                        .settings
+                        .u256_type
+                        .clone()
+                        .ok_or(TypegenError::U256TypeNone)?,
+                },
+                TypeDefPrimitive::I256 => TypePathType::MappedPrimitive {
+                    def: primitive.clone(),
+                    ty: self
+                        .settings
+                        .i256_type
+                        .clone()
+                        .ok_or(TypegenError::I256TypeNone)?,
+                },
+                _ => TypePathType::Primitive {
+                    def: primitive.clone(),
+                },
             },
             TypeDef::Array(arr) => {
                 let inner_type = self.resolve_type_path_recurse(
@@ -407,6 +725,10 @@ impl<'a> TypeGenerator<'a> {
                     parent_type_params,
                     None,
                 )?;
+                if !self.rust_paths {
+                    // Compact values are represented by their inner value.
+                    return Ok(inner_type);
+                }
 
                 let compact_type_path = self
                     .settings
@@ -422,12 +744,11 @@ impl<'a> TypeGenerator<'a> {
                 }
             }
             TypeDef::BitSequence(bitseq) => {
-                let decoded_bits_type_path = self
-                    .settings
-                    .decoded_bits_type_path
-                    .as_ref()
-                    .ok_or(TypegenError::DecodedBitsPathNone)?
-                    .clone();
+                let decoded_bits_type_path = match &self.settings.decoded_bits_type_path {
+                    Some(path) => Some(path.clone()),
+                    None if self.rust_paths => return Err(TypegenError::DecodedBitsPathNone),
+                    None => None,
+                };
 
                 let bit_order_type = self.resolve_type_path_recurse(
                     bitseq.bit_order_type.id,
@@ -454,25 +775,83 @@ impl<'a> TypeGenerator<'a> {
     }
 
     /// Converts a [`scale_info::Path`] into a [`TypePathType`], replacing all types that should be substituted.
+    /// Returns an error for single segment paths that are not known prelude types.
+    ///
+    /// The concrete types of `monomorphize` are not considered, because they depend on the type id. Use
+    /// [`TypeGenerator::resolve_type_path`] for monomorphized paths.
     pub fn type_path_maybe_with_substitutes(
         &self,
         path: &scale_info::Path<PortableForm>,
         params: &[TypePath],
-    ) -> TypePathType {
-        if let Some(substitute) =
-            self.settings
-                .substitutes
-                .for_path_with_params(&path.segments, params, self.settings)
-        {
-            substitute
-        } else {
-            TypePathType::from_type_def_path(
-                path,
+    ) -> Result<TypePathType, TypegenError> {
+        self.substitute_for_path(path, params)
+            .or_else(|| self.generated_or_prelude_type_path(path, params))
+            .ok_or_else(|| TypegenError::UnknownPreludeType {
+                type_id: None,
+                name: path.to_string(),
+            })
+    }
+
+    /// Like [`TypeGenerator::type_path_maybe_with_substitutes`], but for the type with the given id. Resolves the
+    /// concrete type if `monomorphize` is set.
+    fn type_path_for_type_id(
+        &self,
+        type_id: u32,
+        path: &scale_info::Path<PortableForm>,
+        params: &[TypePath],
+    ) -> Result<TypePathType, TypegenError> {
+        if let Some(substitute) = self.substitute_for_path(path, params) {
+            return Ok(substitute);
+        }
+
+        if let Some(monomorphized) = self.monomorphized_path(type_id)? {
+            return Ok(TypePathType::from_generated_type_path(
+                &monomorphized,
+                self.settings.types_mod_ident.clone(),
+                vec![],
+            ));
+        }
+
+        self.generated_or_prelude_type_path(path, params)
+            .ok_or_else(|| TypegenError::UnknownPreludeType {
+                type_id: Some(type_id),
+                name: path.to_string(),
+            })
+    }
+
+    fn substitute_for_path(
+        &self,
+        path: &scale_info::Path<PortableForm>,
+        params: &[TypePath],
+    ) -> Option<TypePathType> {
+        self.settings
+            .substitutes
+            .for_path_with_params(&path.segments, params, self.settings)
+    }
+
+    /// The path of a generated type, taking `renames` into account, or of a prelude type.
+    /// Returns `None` for unknown prelude types.
+    fn generated_or_prelude_type_path(
+        &self,
+        path: &scale_info::Path<PortableForm>,
+        params: &[TypePath],
+    ) -> Option<TypePathType> {
+        let generated_path = self.settings.generated_path(&path.segments);
+        if *generated_path != *path.segments {
+            return Some(TypePathType::from_generated_type_path(
+                &generated_path,
                 self.settings.types_mod_ident.clone(),
                 params.to_vec(),
-                &self.settings.alloc_crate_path,
-            )
+            ));
         }
+
+        TypePathType::from_type_def_path(
+            path,
+            self.settings.types_mod_ident.clone(),
+            params.to_vec(),
+            &self.settings.alloc_crate_path,
+            &self.settings.prelude_types,
+        )
     }
 
     /// Resolves a type, given some type id.
@@ -484,3 +863,136 @@ impl<'a> TypeGenerator<'a> {
         Ok(ty)
     }
 }
+
+/// The name in the type registry, if it differs from the generated identifier (ignoring a raw identifier prefix).
+fn original_name(ident: &Ident, name: &str) -> Option<String> {
+    // Types deriving `TypeInfo` keep the prefix of raw identifiers in their field names.
+    let unprefixed = name.strip_prefix("r#").unwrap_or(name);
+    (ident.unraw() != unprefixed).then(|| name.to_string())
+}
+
+fn kind_names(kind: &CompositeIRKind) -> Vec<&Ident> {
+    match kind {
+        CompositeIRKind::Named(fields) => fields.iter().map(|(name, _)| name).collect(),
+        CompositeIRKind::NoFields | CompositeIRKind::Unnamed(_) => vec![],
+    }
+}
+
+/// The names in the type registry of all fields and variants that are renamed by the naming policy.
+fn renamed_names(kind: &TypeIRKind) -> Vec<String> {
+    let field_names = |kind: &CompositeIRKind| match kind {
+        CompositeIRKind::Named(fields) => fields
+            .iter()
+            .filter_map(|(_, field)| field.original_name.clone())
+            .collect(),
+        CompositeIRKind::NoFields | CompositeIRKind::Unnamed(_) => vec![],
+    };
+    match kind {
+        TypeIRKind::Struct(composite) => field_names(&composite.kind),
+        TypeIRKind::Enum(enum_ir) => enum_ir
+            .variants
+            .iter()
+            .flat_map(|(_, variant)| {
+                variant
+                    .original_name
+                    .clone()
+                    .into_iter()
+                    .chain(field_names(&variant.kind))
+            })
+            .collect(),
+    }
+}
+
+/// The `EncodeAsType` and `DecodeAsType` derives match fields and variants by their rust names. For types whose
+/// names are changed by the naming policy, derives that are added for all types are replaced by explicit impls,
+/// which use the original names. A derive that is added for the type specifically cannot be replaced.
+fn replace_name_matching_derives(
+    type_path: impl FnOnce() -> String,
+    type_ir: &mut TypeIR,
+    default_derives: &Derives,
+) -> Result<(), TypegenError> {
+    let Some(name) = renamed_names(&type_ir.kind).into_iter().next() else {
+        return Ok(());
+    };
+    let replacements: [(&str, syn::Path, syn::Path, &mut Option<syn::Path>); 2] = [
+        (
+            "EncodeAsType",
+            parse_quote!(encode_as_type),
+            parse_quote!(::scale_encode),
+            &mut type_ir.explicit_encode_as_type_impls,
+        ),
+        (
+            "DecodeAsType",
+            parse_quote!(decode_as_type),
+            parse_quote!(::scale_decode),
+            &mut type_ir.explicit_decode_as_type_impls,
+        ),
+    ];
+    for (trait_name, attribute, default_crate_path, explicit_impls) in replacements {
+        let derives: Vec<syn::Path> = type_ir
+            .derives
+            .derives()
+            .iter()
+            .filter(|derive| {
+                derive
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == trait_name)
+            })
+            .cloned()
+            .collect();
+        for derive in derives {
+            if !default_derives.derives().contains(&derive) {
+                return Err(TypegenError::RenamedWithNameMatchingDerive {
+                    type_path: type_path(),
+                    name,
+                    derive: derive.to_token_stream().to_string().replace(' ', ""),
+                });
+            }
+            // The explicit impls use the same crate as the derive would have used.
+            let crate_path = crate_path_attribute(&type_ir.derives, &attribute)
+                .unwrap_or_else(|| default_crate_path.clone());
+            type_ir.derives.remove_derive(&derive);
+            type_ir.derives.remove_attributes(&attribute);
+            explicit_impls.get_or_insert(crate_path);
+        }
+    }
+    Ok(())
+}
+
+/// The `crate_path` of an attribute like `#[encode_as_type(crate_path = "::subxt::ext::scale_encode")]`.
+fn crate_path_attribute(derives: &Derives, attribute: &syn::Path) -> Option<syn::Path> {
+    derives
+        .attributes()
+        .iter()
+        .filter(|attr| attr.path() == attribute)
+        .find_map(|attr| {
+            let mut crate_path = None;
+            attr.parse_nested_meta(|meta| {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("crate_path") {
+                    crate_path = Some(value.parse()?);
+                }
+                Ok(())
+            })
+            .ok()?;
+            crate_path
+        })
+}
+
+/// Naming conventions can map distinct names in the registry to the same identifier, e.g. `fooBar` and `foo_bar`.
+fn ensure_unique_names<'a>(
+    ty: &Type<PortableForm>,
+    names: impl IntoIterator<Item = &'a Ident>,
+) -> Result<(), TypegenError> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(TypegenError::DuplicateName {
+                type_path: ty.path.to_string(),
+                name: name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
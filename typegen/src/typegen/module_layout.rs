@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::settings::{naming::to_upper_camel_case, substitutes::PathSegments, ModuleLayout};
+
+/// Lays out the paths of the generated types, which are relative to the types module.
+/// Returns the new path for each of the paths.
+pub(crate) fn layout_paths(
+    layout: &ModuleLayout,
+    paths: &BTreeSet<PathSegments>,
+) -> BTreeMap<PathSegments, PathSegments> {
+    match layout {
+        ModuleLayout::Nested => paths
+            .iter()
+            .map(|path| (path.clone(), path.clone()))
+            .collect(),
+        ModuleLayout::StripModules(modules) => strip_modules(modules, paths),
+        ModuleLayout::CollapseSingleChildModules => collapse_single_child_modules(paths),
+        ModuleLayout::Flat => flat(paths),
+    }
+}
+
+fn strip_modules(
+    modules: &[String],
+    paths: &BTreeSet<PathSegments>,
+) -> BTreeMap<PathSegments, PathSegments> {
+    let mut laid_out: BTreeMap<PathSegments, PathSegments> = paths
+        .iter()
+        .map(|path| {
+            let (ident, namespace) = path.split_last().expect("type paths are not empty; qed");
+            let stripped = namespace
+                .iter()
+                .filter(|segment| !modules.contains(segment))
+                .chain([ident])
+                .cloned()
+                .collect();
+            (path.clone(), stripped)
+        })
+        .collect();
+
+    // The original paths are unique, so clashing paths are reverted to them. This can cause
+    // new clashes with other stripped paths, so we repeat until nothing changes.
+    loop {
+        let mut changed = false;
+        for path in clashing_paths(&laid_out) {
+            if laid_out[&path] != path {
+                laid_out.insert(path.clone(), path);
+                changed = true;
+            }
+        }
+        if !changed {
+            return laid_out;
+        }
+    }
+}
+
+/// Returns the original paths of all laid out paths that are used by another type, or that are used as a module
+/// by other types (or the other way around). Modules and types share a namespace in rust.
+fn clashing_paths(laid_out: &BTreeMap<PathSegments, PathSegments>) -> Vec<PathSegments> {
+    let mut counts: HashMap<&[String], usize> = HashMap::new();
+    let mut modules: HashSet<&[String]> = HashSet::new();
+    for path in laid_out.values() {
+        *counts.entry(path).or_default() += 1;
+        for i in 1..path.len() {
+            modules.insert(&path[..i]);
+        }
+    }
+    laid_out
+        .iter()
+        .filter(|(_, path)| {
+            counts[path.as_slice()] > 1
+                || modules.contains(path.as_slice())
+                || (1..path.len()).any(|i| counts.contains_key(&path[..i]))
+        })
+        .map(|(original, _)| original.clone())
+        .collect()
+}
+
+fn collapse_single_child_modules(
+    paths: &BTreeSet<PathSegments>,
+) -> BTreeMap<PathSegments, PathSegments> {
+    let mut children: HashMap<&[String], BTreeSet<&String>> = HashMap::new();
+    let mut has_types: HashSet<&[String]> = HashSet::new();
+    for path in paths {
+        let namespace = &path[..path.len() - 1];
+        has_types.insert(namespace);
+        for i in 0..namespace.len() {
+            children
+                .entry(&namespace[..i])
+                .or_default()
+                .insert(&namespace[i]);
+        }
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            let (ident, namespace) = path.split_last().expect("type paths are not empty; qed");
+            // A module is dropped from the path if its parent contains no types and no other modules.
+            // All paths through the parent go through the dropped module, so the paths stay unique.
+            let collapsed = namespace
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| {
+                    let parent = &namespace[..*i];
+                    has_types.contains(parent) || children[parent].len() > 1
+                })
+                .map(|(_, segment)| segment)
+                .chain([ident])
+                .cloned()
+                .collect();
+            (path.clone(), collapsed)
+        })
+        .collect()
+}
+
+fn flat(paths: &BTreeSet<PathSegments>) -> BTreeMap<PathSegments, PathSegments> {
+    // The number of innermost modules each name is prefixed with, and a numeric suffix
+    // for types whose full paths still clash, e.g. `a_b::C` and `a::b::C`.
+    let mut prefix_lens: BTreeMap<&PathSegments, usize> =
+        paths.iter().map(|path| (path, 0)).collect();
+    let mut suffixes: BTreeMap<&PathSegments, usize> = BTreeMap::new();
+    let name = |path: &PathSegments, prefix_len: usize, suffix: Option<&usize>| {
+        let (ident, namespace) = path.split_last().expect("type paths are not empty; qed");
+        let prefix = namespace[namespace.len() - prefix_len..]
+            .iter()
+            .map(|segment| to_upper_camel_case(segment))
+            .collect::<String>();
+        let suffix = suffix.map(|n| n.to_string()).unwrap_or_default();
+        format!("{prefix}{ident}{suffix}")
+    };
+
+    loop {
+        let mut by_name: BTreeMap<String, Vec<&PathSegments>> = BTreeMap::new();
+        for (path, prefix_len) in prefix_lens.iter() {
+            by_name
+                .entry(name(path, *prefix_len, suffixes.get(path)))
+                .or_default()
+                .push(path);
+        }
+
+        let mut changed = false;
+        for clashing in by_name.values().filter(|paths| paths.len() > 1) {
+            let expandable = clashing
+                .iter()
+                .filter(|path| prefix_lens[*path] < path.len() - 1)
+                .collect::<Vec<_>>();
+            if expandable.is_empty() {
+                for path in clashing.iter().skip(1) {
+                    let suffix = suffixes.entry(path).or_insert(1);
+                    *suffix += 1;
+                }
+            } else {
+                for path in expandable {
+                    *prefix_lens
+                        .get_mut(*path)
+                        .expect("all paths are in the map; qed") += 1;
+                }
+            }
+            changed = true;
+        }
+
+        if !changed {
+            return prefix_lens
+                .iter()
+                .map(|(path, prefix_len)| {
+                    let name = name(path, *prefix_len, suffixes.get(path));
+                    ((*path).clone(), vec![name])
+                })
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::layout_paths;
+    use crate::typegen::settings::ModuleLayout;
+
+    fn paths(paths: &[&str]) -> BTreeSet<Vec<String>> {
+        paths
+            .iter()
+            .map(|path| path.split("::").map(|s| s.to_string()).collect())
+            .collect()
+    }
+
+    fn laid_out(layout: ModuleLayout, input: &[&str]) -> Vec<String> {
+        layout_paths(&layout, &paths(input))
+            .into_values()
+            .map(|path| path.join("::"))
+            .collect()
+    }
+
+    #[test]
+    fn strip_modules() {
+        let layout = ModuleLayout::StripModules(vec!["pallet".into(), "types".into()]);
+        let out = laid_out(
+            layout,
+            &[
+                "pallet_balances::pallet::Call",
+                "pallet_balances::types::AccountData",
+                "pallet_staking::pallet::Call",
+                "pallet_staking::Call",
+                // Would clash with the `crypto` module after stripping `types`.
+                "sp_core::types::crypto",
+                "sp_core::crypto::AccountId32",
+            ],
+        );
+        assert_eq!(
+            out,
+            [
+                "pallet_balances::Call",
+                "pallet_balances::AccountData",
+                "pallet_staking::Call",
+                "pallet_staking::pallet::Call",
+                "sp_core::crypto::AccountId32",
+                "sp_core::types::crypto",
+            ]
+        );
+    }
+
+    #[test]
+    fn collapse_single_child_modules() {
+        let out = laid_out(
+            ModuleLayout::CollapseSingleChildModules,
+            &[
+                "pallet_balances::pallet::Call",
+                "pallet_balances::pallet::Event",
+                "sp_core::crypto::AccountId32",
+                "sp_core::Void",
+                "sp_arithmetic::per_things::inner::Perbill",
+            ],
+        );
+        assert_eq!(
+            out,
+            [
+                "pallet_balances::Call",
+                "pallet_balances::Event",
+                "sp_arithmetic::Perbill",
+                "sp_core::Void",
+                "sp_core::crypto::AccountId32",
+            ]
+        );
+    }
+
+    #[test]
+    fn flat() {
+        let out = laid_out(
+            ModuleLayout::Flat,
+            &[
+                "pallet_balances::pallet::Call",
+                "pallet_staking::pallet::Call",
+                "sp_core::crypto::AccountId32",
+                // The full paths of these two still clash after prefixing them with all modules.
+                "a_b::C",
+                "a::b::C",
+                "x::b::C",
+            ],
+        );
+        assert_eq!(
+            out,
+            [
+                "ABC",
+                "ABC2",
+                "PalletBalancesPalletCall",
+                "PalletStakingPalletCall",
+                "AccountId32",
+                "XBC",
+            ]
+        );
+    }
+}
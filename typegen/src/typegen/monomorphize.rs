@@ -0,0 +1,70 @@
+use std::collections::{HashMap, HashSet};
+
+use scale_info::PortableType;
+
+use crate::TypegenError;
+
+use super::{settings::substitutes::PathSegments, type_aliases::params_name, TypeGenerator};
+
+/// The paths of the concrete types that are generated for the instantiations of generic types,
+/// keyed by the type id of the instantiation. The paths are relative to the types module.
+pub type MonomorphizedPaths = HashMap<u32, PathSegments>;
+
+/// Names the concrete type of each generated instantiation after the generic type and its parameters,
+/// e.g. `HeaderU32` for `Header<u32>`, with a numeric suffix if that name is already taken.
+pub(crate) fn monomorphized_paths(
+    type_gen: &TypeGenerator,
+) -> Result<MonomorphizedPaths, TypegenError> {
+    let mut settings = type_gen.settings().clone();
+    settings.monomorphize = false;
+    let generic_gen = type_gen.with_settings(&settings);
+
+    let reachable_type_ids = type_gen.reachable_type_ids()?;
+    let generated = type_gen
+        .types()
+        .types
+        .iter()
+        .filter(|ty| type_gen.is_generated(ty, &reachable_type_ids))
+        .collect::<Vec<_>>();
+
+    // The concrete types must not clash with the non-generic types, or with modules.
+    let mut taken: HashSet<PathSegments> = HashSet::new();
+    taken.insert(vec![settings.types_mod_ident.to_string()]);
+    for ty in generated.iter() {
+        let path = settings.generated_path(&ty.ty.path.segments);
+        for i in 1..path.len() {
+            taken.insert(path[..i].to_vec());
+        }
+        if !is_generic(ty) {
+            taken.insert(path.to_vec());
+        }
+    }
+
+    let mut paths = MonomorphizedPaths::new();
+    for ty in generated.into_iter().filter(|ty| is_generic(ty)) {
+        let generated_path = settings.generated_path(&ty.ty.path.segments);
+        let (type_name, namespace) = generated_path
+            .split_last()
+            .expect("generated types have a path; qed");
+        let base = format!(
+            "{type_name}{}",
+            params_name(&generic_gen.resolve_type_path(ty.id)?)
+        );
+        let path = std::iter::once(base.clone())
+            .chain((2..).map(|i| format!("{base}{i}")))
+            .map(|name| {
+                let mut path = namespace.to_vec();
+                path.push(name);
+                path
+            })
+            .find(|path| !taken.contains(path))
+            .expect("there are infinitely many candidates; qed");
+        taken.insert(path.clone());
+        paths.insert(ty.id, path);
+    }
+    Ok(paths)
+}
+
+fn is_generic(ty: &PortableType) -> bool {
+    ty.ty.type_params.iter().any(|param| param.ty.is_some())
+}
@@ -1,18 +1,93 @@
 use std::collections::{HashMap, HashSet};
 
 use quote::ToTokens;
-use scale_info::{form::PortableForm, PortableRegistry, Type};
+use scale_info::{form::PortableForm, PortableRegistry, Type, TypeDef};
 
 use crate::{utils::syn_type_path, TypegenError};
 
+use super::path_pattern::PathPattern;
+
 /// A struct containing the derives that we'll be applying to types;
 /// a combination of some common derives for all types, plus type
 /// specific derives.
+///
+/// Type specific derives can either be added for exact type paths, or for all types matched by a [`TypeSelector`].
+/// The derives and attributes for exact type paths (added directly or recursively) are merged with those from
+/// selectors. Where both add the same derive or an attribute with the same path, the one for the exact path wins.
 #[derive(Debug, Clone, Default)]
 pub struct DerivesRegistry {
     default_derives: Derives,
     specific_type_derives: HashMap<syn::TypePath, Derives>,
     recursive_type_derives: HashMap<syn::TypePath, Derives>,
+    selected_type_derives: Vec<SelectedDerives>,
+    specific_type_removals: HashMap<syn::TypePath, HashSet<syn::Path>>,
+    recursive_type_removals: HashMap<syn::TypePath, HashSet<syn::Path>>,
+}
+
+/// Derives for all types matched by a selector.
+#[derive(Debug, Clone)]
+struct SelectedDerives {
+    selector: TypeSelector,
+    derives: Derives,
+    recursive: bool,
+}
+
+/// Selects the types in a type registry that derives and attributes should be applied to.
+#[derive(Debug, Clone)]
+pub enum TypeSelector {
+    /// All types with a path matching the pattern, e.g. `pallet_staking::**`.
+    Path(PathPattern),
+    /// All types that are generated as enums.
+    Enums,
+    /// All types that are generated as structs.
+    Structs,
+    /// All types that have at least one compact encoded field.
+    HasCompactField,
+    /// All types for which the function returns true.
+    Custom(fn(&Type<PortableForm>, &PortableRegistry) -> bool),
+}
+
+impl TypeSelector {
+    /// Returns true if the type is selected. Types with an empty path, like primitives,
+    /// are never generated and thus never selected.
+    pub fn matches(&self, ty: &Type<PortableForm>, types: &PortableRegistry) -> bool {
+        if ty.path.is_empty() {
+            return false;
+        }
+        match self {
+            TypeSelector::Path(pattern) => pattern.matches(&ty.path.segments),
+            TypeSelector::Enums => matches!(ty.type_def, TypeDef::Variant(_)),
+            TypeSelector::Structs => !matches!(ty.type_def, TypeDef::Variant(_)),
+            TypeSelector::HasCompactField => {
+                let is_compact = |id: u32| {
+                    types
+                        .resolve(id)
+                        .is_some_and(|ty| matches!(ty.type_def, TypeDef::Compact(_)))
+                };
+                match &ty.type_def {
+                    TypeDef::Composite(def) => def.fields.iter().any(|f| is_compact(f.ty.id)),
+                    TypeDef::Variant(def) => def
+                        .variants
+                        .iter()
+                        .any(|v| v.fields.iter().any(|f| is_compact(f.ty.id))),
+                    _ => false,
+                }
+            }
+            TypeSelector::Custom(f) => f(ty, types),
+        }
+    }
+}
+
+impl From<PathPattern> for TypeSelector {
+    fn from(pattern: PathPattern) -> Self {
+        TypeSelector::Path(pattern)
+    }
+}
+
+impl From<&str> for TypeSelector {
+    fn from(pattern: &str) -> Self {
+        TypeSelector::Path(PathPattern::new(pattern))
+    }
 }
 
 impl DerivesRegistry {
@@ -67,6 +142,63 @@ impl DerivesRegistry {
         type_derives.attributes.extend(attributes);
     }
 
+    /// Remove derives from a specific generated type, e.g. `Eq` or `Hash` for a type containing a field
+    /// that does not implement these traits. The derives are removed after all other derives have been
+    /// resolved, so this also removes derives that were added for all types or via a [`TypeSelector`].
+    ///
+    /// The `recursive` flag can be set if the derives should also be removed from child types.
+    pub fn remove_derives_for(
+        &mut self,
+        ty: syn::TypePath,
+        derives: impl IntoIterator<Item = syn::Path>,
+        recursive: bool,
+    ) {
+        let type_removals = if recursive {
+            self.recursive_type_removals.entry(ty).or_default()
+        } else {
+            self.specific_type_removals.entry(ty).or_default()
+        };
+        type_removals.extend(derives);
+    }
+
+    /// Insert derives to be applied to all types matched by the selector, e.g. a path pattern
+    /// like `pallet_staking::**` or [`TypeSelector::Enums`].
+    ///
+    /// The `recursive` flag can be set if child types of the matched types should also receive the given derives.
+    pub fn add_derives_for_selected(
+        &mut self,
+        selector: impl Into<TypeSelector>,
+        derives: impl IntoIterator<Item = syn::Path>,
+        recursive: bool,
+    ) {
+        self.selected_type_derives.push(SelectedDerives {
+            selector: selector.into(),
+            derives: derives.into_iter().collect(),
+            recursive,
+        });
+    }
+
+    /// Insert attributes to be applied to all types matched by the selector, e.g. a path pattern
+    /// like `pallet_staking::**` or [`TypeSelector::Enums`].
+    ///
+    /// The `recursive` flag can be set if child types of the matched types should also receive the given attributes.
+    pub fn add_attributes_for_selected(
+        &mut self,
+        selector: impl Into<TypeSelector>,
+        attributes: impl IntoIterator<Item = syn::Attribute>,
+        recursive: bool,
+    ) {
+        let mut derives = Derives::new();
+        for attribute in attributes {
+            derives.insert_attribute(attribute);
+        }
+        self.selected_type_derives.push(SelectedDerives {
+            selector: selector.into(),
+            derives,
+            recursive,
+        });
+    }
+
     /// Returns the derives to be applied to all generated types.
     pub fn default_derives(&self) -> &Derives {
         &self.default_derives
@@ -82,17 +214,24 @@ impl DerivesRegistry {
             default_derives,
             mut specific_type_derives,
             mut recursive_type_derives,
+            selected_type_derives,
+            specific_type_removals,
+            mut recursive_type_removals,
         } = self;
 
-        if recursive_type_derives.is_empty() {
+        if recursive_type_derives.is_empty()
+            && selected_type_derives.is_empty()
+            && recursive_type_removals.is_empty()
+        {
             return Ok(FlatDerivesRegistry {
                 default_derives,
                 specific_type_derives,
+                removed_type_derives: specific_type_removals,
             });
         }
 
         // Build a mapping of type ids to syn paths for all types in the registry:
-        let mut syn_path_for_id: HashMap<u32, syn::TypePath> = types
+        let syn_path_for_id: HashMap<u32, syn::TypePath> = types
             .types
             .iter()
             .filter_map(|t| {
@@ -134,17 +273,71 @@ impl DerivesRegistry {
 
         // Merge all the recursively obtained derives with the existing derives for the types.
         for (id, derived_to_add) in add_derives_for_id {
-            if let Some(path) = syn_path_for_id.remove(&id) {
+            if let Some(path) = syn_path_for_id.get(&id) {
                 specific_type_derives
-                    .entry(path)
+                    .entry(path.clone())
                     .or_default()
                     .extend_from(derived_to_add);
             }
         }
 
+        // Collect the derives of all selectors that match a type, for the type (and its children if recursive):
+        let mut selected_derives_for_id: HashMap<u32, Derives> = HashMap::new();
+        for selected in selected_type_derives.iter() {
+            for ty in types.types.iter() {
+                if !selected.selector.matches(&ty.ty, types) {
+                    continue;
+                }
+                let mut collected_type_ids: HashSet<u32> = HashSet::new();
+                if selected.recursive {
+                    collect_type_ids(ty.id, types, &mut collected_type_ids);
+                } else {
+                    collected_type_ids.insert(ty.id);
+                }
+                for id in collected_type_ids {
+                    selected_derives_for_id
+                        .entry(id)
+                        .or_default()
+                        .extend_from(selected.derives.clone());
+                }
+            }
+        }
+
+        // Merge the selected derives into the derives for exact type paths, which take precedence on conflicts.
+        for (id, selected_derives) in selected_derives_for_id {
+            if let Some(path) = syn_path_for_id.get(&id) {
+                specific_type_derives
+                    .entry(path.clone())
+                    .or_default()
+                    .extend_without_overriding(selected_derives);
+            }
+        }
+
+        // Flatten out the recursive removals in the same way as the recursive derives:
+        let mut removed_type_derives = specific_type_removals;
+        for ty in types.types.iter() {
+            let Some(path) = syn_path_for_id.get(&ty.id) else {
+                continue;
+            };
+            let Some(removals) = recursive_type_removals.remove(path) else {
+                continue;
+            };
+            let mut collected_type_ids: HashSet<u32> = HashSet::new();
+            collect_type_ids(ty.id, types, &mut collected_type_ids);
+            for id in collected_type_ids {
+                if let Some(path) = syn_path_for_id.get(&id) {
+                    removed_type_derives
+                        .entry(path.clone())
+                        .or_default()
+                        .extend(removals.iter().cloned());
+                }
+            }
+        }
+
         Ok(FlatDerivesRegistry {
             default_derives,
             specific_type_derives,
+            removed_type_derives,
         })
     }
 
@@ -189,6 +382,29 @@ impl Derives {
         self.attributes.extend(other.attributes);
     }
 
+    /// Extend this set of `Derives` from another, skipping the derives and attributes that this set already
+    /// contains. Derives are compared by their last path segment, attributes by their path.
+    fn extend_without_overriding(&mut self, other: Derives) {
+        for derive in other.derives {
+            if !self
+                .derives
+                .iter()
+                .any(|existing| same_derive(existing, &derive))
+            {
+                self.derives.insert(derive);
+            }
+        }
+        for attribute in other.attributes {
+            if !self
+                .attributes
+                .iter()
+                .any(|existing| existing.path() == attribute.path())
+            {
+                self.attributes.insert(attribute);
+            }
+        }
+    }
+
     /// Extend the set of derives by providing an iterator of paths to derive macros.
     pub fn extend(&mut self, derives: impl Iterator<Item = syn::Path>) {
         for derive in derives {
@@ -206,6 +422,16 @@ impl Derives {
         self.attributes.insert(attribute);
     }
 
+    /// Remove a single derive. Returns true if the derive was present.
+    pub fn remove_derive(&mut self, derive: &syn::Path) -> bool {
+        self.derives.remove(derive)
+    }
+
+    /// Remove all attributes with the given path, e.g. `encode_as_type` for `#[encode_as_type(..)]`.
+    pub fn remove_attributes(&mut self, path: &syn::Path) {
+        self.attributes.retain(|attribute| attribute.path() != path);
+    }
+
     /// Getter for the derived traits
     pub fn derives(&self) -> &HashSet<syn::Path> {
         &self.derives
@@ -246,6 +472,73 @@ impl ToTokens for Derives {
     }
 }
 
+/// A standard library trait that can only be derived if all fields of a type implement it.
+/// Used to infer which derives are feasible, see [`crate::TypeGeneratorSettings::infer_derives`].
+/// `Clone` and `PartialEq` are assumed to be implemented by all fields, they are only tracked as supertraits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DerivableTrait {
+    /// `Clone`
+    Clone,
+    /// `Copy`
+    Copy,
+    /// `PartialEq`
+    PartialEq,
+    /// `Eq`
+    Eq,
+    /// `PartialOrd`
+    PartialOrd,
+    /// `Ord`
+    Ord,
+    /// `Hash`
+    Hash,
+    /// `Default`
+    Default,
+}
+
+impl DerivableTrait {
+    /// All derivable traits.
+    pub const ALL: [DerivableTrait; 8] = [
+        DerivableTrait::Clone,
+        DerivableTrait::Copy,
+        DerivableTrait::PartialEq,
+        DerivableTrait::Eq,
+        DerivableTrait::PartialOrd,
+        DerivableTrait::Ord,
+        DerivableTrait::Hash,
+        DerivableTrait::Default,
+    ];
+
+    /// Returns the trait that a derive macro path like `Hash` or `::core::hash::Hash` refers to.
+    pub fn from_derive_path(path: &syn::Path) -> Option<Self> {
+        let ident = path.segments.last()?.ident.to_string();
+        let derivable_trait = match ident.as_str() {
+            "Clone" => DerivableTrait::Clone,
+            "Copy" => DerivableTrait::Copy,
+            "PartialEq" => DerivableTrait::PartialEq,
+            "Eq" => DerivableTrait::Eq,
+            "PartialOrd" => DerivableTrait::PartialOrd,
+            "Ord" => DerivableTrait::Ord,
+            "Hash" => DerivableTrait::Hash,
+            "Default" => DerivableTrait::Default,
+            _ => return None,
+        };
+        Some(derivable_trait)
+    }
+
+    /// Supertraits of this trait that need to be derivable as well.
+    pub fn supertraits(&self) -> &'static [DerivableTrait] {
+        match self {
+            DerivableTrait::Copy => &[DerivableTrait::Clone],
+            DerivableTrait::Eq | DerivableTrait::PartialOrd => &[DerivableTrait::PartialEq],
+            DerivableTrait::Ord => &[DerivableTrait::Eq, DerivableTrait::PartialOrd],
+            DerivableTrait::Clone
+            | DerivableTrait::PartialEq
+            | DerivableTrait::Hash
+            | DerivableTrait::Default => &[],
+        }
+    }
+}
+
 /// This is like a DerivesRegistry, but the recursive type derives have been flattened out into specific_type_derives.
 ///
 /// Can be constructed properly using a DerivesRegistry and a PortableRegistry with `DerivesRegistry::flatten_recursive_derives()`.
@@ -253,15 +546,27 @@ impl ToTokens for Derives {
 pub struct FlatDerivesRegistry {
     default_derives: Derives,
     specific_type_derives: HashMap<syn::TypePath, Derives>,
+    removed_type_derives: HashMap<syn::TypePath, HashSet<syn::Path>>,
 }
 
 impl FlatDerivesRegistry {
+    /// Returns the derives to be applied to all generated types.
+    pub fn default_derives(&self) -> &Derives {
+        &self.default_derives
+    }
+
     /// Resolve the derives for a specific type path.
     pub fn resolve(&self, ty: &syn::TypePath) -> Derives {
         let mut resolved_derives = self.default_derives.clone();
         if let Some(specific) = self.specific_type_derives.get(ty) {
             resolved_derives.extend_from(specific.clone());
         }
+        if let Some(removed) = self.removed_type_derives.get(ty) {
+            // Derives are matched by their last segment, so removing `Eq` also removes `::core::cmp::Eq`.
+            resolved_derives
+                .derives
+                .retain(|derive| !removed.iter().any(|removed| same_derive(removed, derive)));
+        }
         resolved_derives
     }
 
@@ -274,7 +579,23 @@ impl FlatDerivesRegistry {
     }
 }
 
-fn collect_type_ids(id: u32, types: &PortableRegistry, collected_types: &mut HashSet<u32>) {
+/// Collects the id of the type and the ids of all types that are (transitively) contained in it.
+pub(crate) fn collect_type_ids(
+    id: u32,
+    types: &PortableRegistry,
+    collected_types: &mut HashSet<u32>,
+) {
+    collect_type_ids_until(id, types, collected_types, &|_| false);
+}
+
+/// Like [`collect_type_ids`], but does not collect the fields of types for which `stop` returns true.
+/// The types passed as type params of such a type are still collected.
+pub(crate) fn collect_type_ids_until(
+    id: u32,
+    types: &PortableRegistry,
+    collected_types: &mut HashSet<u32>,
+    stop: &impl Fn(&Type<PortableForm>) -> bool,
+) {
     // Recursion protection:
     if collected_types.contains(&id) {
         return;
@@ -289,39 +610,48 @@ fn collect_type_ids(id: u32, types: &PortableRegistry, collected_types: &mut Has
     // Collect the types that are passed as type params (Question/Note: Is this necessary? Maybe not...)
     for param in ty.type_params.iter() {
         if let Some(id) = param.ty.map(|e| e.id) {
-            collect_type_ids(id, types, collected_types);
+            collect_type_ids_until(id, types, collected_types, stop);
         }
     }
 
+    if stop(ty) {
+        return;
+    }
+
     // Collect ids depending on the types structure:
     match &ty.type_def {
         scale_info::TypeDef::Composite(def) => {
             for f in def.fields.iter() {
-                collect_type_ids(f.ty.id, types, collected_types);
+                collect_type_ids_until(f.ty.id, types, collected_types, stop);
             }
         }
         scale_info::TypeDef::Variant(def) => {
             for v in def.variants.iter() {
                 for f in v.fields.iter() {
-                    collect_type_ids(f.ty.id, types, collected_types);
+                    collect_type_ids_until(f.ty.id, types, collected_types, stop);
                 }
             }
         }
         scale_info::TypeDef::Sequence(def) => {
-            collect_type_ids(def.type_param.id, types, collected_types);
+            collect_type_ids_until(def.type_param.id, types, collected_types, stop);
         }
         scale_info::TypeDef::Array(def) => {
-            collect_type_ids(def.type_param.id, types, collected_types);
+            collect_type_ids_until(def.type_param.id, types, collected_types, stop);
         }
         scale_info::TypeDef::Tuple(def) => {
             for f in def.fields.iter() {
-                collect_type_ids(f.id, types, collected_types);
+                collect_type_ids_until(f.id, types, collected_types, stop);
             }
         }
         scale_info::TypeDef::Primitive(_) => {}
         scale_info::TypeDef::Compact(def) => {
-            collect_type_ids(def.type_param.id, types, collected_types);
+            collect_type_ids_until(def.type_param.id, types, collected_types, stop);
         }
         scale_info::TypeDef::BitSequence(_) => {}
     }
 }
+
+/// Returns true if the two paths refer to the same derive macro, e.g. `Eq` and `::core::cmp::Eq`.
+fn same_derive(a: &syn::Path, b: &syn::Path) -> bool {
+    a.segments.last().map(|s| &s.ident) == b.segments.last().map(|s| &s.ident)
+}
@@ -1,13 +1,28 @@
-use derives::DerivesRegistry;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use derives::{DerivableTrait, DerivesRegistry};
 use proc_macro2::Ident;
 use quote::{quote, ToTokens};
 use substitutes::TypeSubstitutes;
 use syn::parse_quote;
 
-use self::substitutes::absolute_path;
+use crate::{typegen::ir::serde_json_compat::SERDE_JSON_COMPAT_MOD, TypegenError};
+
+use self::{
+    naming::NamingPolicy,
+    path_pattern::PathPattern,
+    substitutes::{absolute_path, path_segments, PathSegments},
+};
 
 /// Settings for which derives should be applied on types
 pub mod derives;
+/// Settings for how fields and variants are named.
+pub mod naming;
+/// Patterns for matching type paths.
+pub mod path_pattern;
 /// Settings for which types should be substituted by other types.
 pub mod substitutes;
 
@@ -18,10 +33,31 @@ pub struct TypeGeneratorSettings {
     pub types_mod_ident: Ident,
     /// If false, no docs are generated for the types.
     pub should_gen_docs: bool,
+    /// If true, generic type parameters keep their names from the type registry, e.g. `Balance`, unless the name
+    /// is not a valid identifier or clashes with another name. Otherwise they are named by their index, e.g. `_0`.
+    pub original_type_param_names: bool,
+    /// If true, a `pub type` alias is generated next to each generic type for each of its concrete instantiations
+    /// in the type registry. Aliases are named after the type names of fields with that type, like `HeaderFor`,
+    /// or else after the type and its parameters, like `HeaderU32`.
+    pub instantiation_aliases: bool,
+    /// If true, no generic types are generated. Instead, a concrete type is generated for each instantiation of
+    /// a generic type in the type registry, named after the type and its parameters, like `HeaderU32`.
+    /// Generic prelude types like `Option` and substitutes are not affected. Type aliases for
+    /// instantiations are not generated in this mode.
+    pub monomorphize: bool,
     /// Derive traits on generated types.
     pub derives: DerivesRegistry,
     /// User defined overrides for generated types.
     pub substitutes: TypeSubstitutes,
+    /// Generated types that are renamed or moved to another module, keyed by their path in the type registry.
+    /// The target path is relative to the types module, e.g. `common::AccountId` for a type that should be
+    /// generated as `types::common::AccountId`. All references to the type use the new path.
+    ///
+    /// Other settings like derives, substitutes and roots still refer to the type by its path in the registry.
+    pub renames: HashMap<PathSegments, PathSegments>,
+    /// How the module hierarchy of the generated types is laid out, see [`ModuleLayout`].
+    /// The layout is applied to the paths of the types after `renames`.
+    pub module_layout: ModuleLayout,
     /// Two generic parameters are expected on this type:
     /// - Store (e.g. `u8`/`u16`/`u32`/`u64`)
     /// - Order (e.g. LSB, MSB)
@@ -37,10 +73,84 @@ pub struct TypeGeneratorSettings {
     /// If false, no codec attributes like `codec(index=0)` and `codec(compact)` are inserted.
     /// This is a useful option if we do not want to derive Decode and Encode on our types.
     pub insert_codec_attributes: bool,
+    /// Path to the `parity_scale_codec` crate, e.g. `::subxt::ext::codec`. If set, `Encode` and `Decode` are
+    /// implemented explicitly for all generated types, so the codec derive macros are not needed.
+    /// `Encode`/`Decode` should then not be added as derives, and no codec attributes are inserted.
+    pub explicit_codec_impls: Option<syn::Path>,
+    /// Path to the `scale_encode` crate, e.g. `::subxt::ext::scale_encode`. If set, `EncodeAsType` is implemented
+    /// explicitly for all generated types, so `EncodeAsType` should not be added as a derive.
+    pub explicit_encode_as_type_impls: Option<syn::Path>,
+    /// Path to the `scale_decode` crate, e.g. `::subxt::ext::scale_decode`. If set, `DecodeAsType` is implemented
+    /// explicitly for all generated types, so `DecodeAsType` should not be added as a derive.
+    pub explicit_decode_as_type_impls: Option<syn::Path>,
+    /// Path to the `serde` crate, e.g. `::serde`. If set, serde attributes are inserted such that the generated
+    /// types serialize to the same JSON as `scale_value::Value`s of the same type. Serde derives are not added
+    /// automatically, they need to be configured in `derives` as usual.
+    ///
+    /// Enums are adjacently tagged as `{ "name": .., "values": .. }`, newtypes serialize as `[value]`, unit structs
+    /// and variants as `[]` and bit sequences as `{ "__bitvec__values__": .. }` (the `decoded_bits_type_path` type
+    /// needs to serialize as a sequence of bools for this). The serde adapters for this are generated into a
+    /// `__serde_json_compat` module in the types module. Prelude types like `Option` and `BTreeMap` have their own
+    /// serde representation, so they are generated into this module as well, unless they are set in `prelude_types`.
+    ///
+    /// Integers need no adapter (`serde_json` writes `u128`s as numbers, just like `scale_value`), and compact values
+    /// serialize as their inner value with the `serde` feature of `parity_scale_codec`.
+    pub serde_json_compat: Option<syn::Path>,
+    /// How the names of fields and variants are turned into rust identifiers, see [`NamingPolicy`].
+    pub naming: NamingPolicy,
     /// Configure a custom type path for the `alloc` crate, which is the base for generating type paths like
     /// `alloc::string::String`, `alloc::vec::Vec` and `alloc::boxed::Box`. The default is `AllocCratePath::Std` which
     /// uses the types from the `std` library instead.
     pub alloc_crate_path: AllocCratePath,
+    /// Type used to represent `U256` primitives, e.g. `[u8; 32]` or `::primitive_types::U256`.
+    /// There is no rust primitive for `U256`, so generating a type that contains one fails if this is not set.
+    pub u256_type: Option<syn::Type>,
+    /// Type used to represent `I256` primitives, e.g. `[u8; 32]`.
+    /// There is no rust primitive for `I256`, so generating a type that contains one fails if this is not set.
+    pub i256_type: Option<syn::Type>,
+    /// Paths for prelude types, i.e. types with a single segment path like `HashMap` or `Duration`.
+    /// A number of prelude types (e.g. `Option`, `Result`, `BTreeMap`) are known by default.
+    /// Entries in this map take precedence over these builtin prelude types.
+    pub prelude_types: HashMap<String, syn::Path>,
+    /// Types that the generated types are reachable from. If this is empty, all types in the registry are generated.
+    /// Otherwise only the roots and the types they (transitively) contain are generated.
+    pub roots: Vec<TypeRoot>,
+    /// If true, derives of `Copy`, `Eq`, `PartialOrd`, `Ord`, `Hash` and `Default` are dropped from all types
+    /// that cannot derive them, because some (transitively contained) field does not implement the trait.
+    pub infer_derives: bool,
+    /// The derivable traits that types not generated by us implement, e.g. substitutes or the `decoded_bits_type_path`.
+    /// Keyed by the path the type is referred to in generated code. When inferring derives, such types are assumed
+    /// to implement none of the traits, unless they are listed here or are well known prelude types like `Option`.
+    pub external_type_traits: HashMap<PathSegments, HashSet<DerivableTrait>>,
+}
+
+/// A type that the generated types are reachable from, see [`TypeGeneratorSettings::roots`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeRoot {
+    /// The type with this id in the type registry.
+    Id(u32),
+    /// All types with a path that matches this pattern.
+    Path(PathPattern),
+}
+
+/// Strategies for laying out the modules of the generated types. All strategies result in unique paths.
+///
+/// The layout is resolved into [`TypeGeneratorSettings::renames`] for the types in a type registry, see
+/// [`crate::TypeGenerator::settings_with_module_layout`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ModuleLayout {
+    /// Reproduces the full namespace of the types, e.g. `types::pallet_balances::pallet::Call`. This is the default.
+    #[default]
+    Nested,
+    /// Removes modules with these names from the paths, e.g. `pallet` to generate `types::pallet_balances::Call`.
+    /// Types keep their full path if removing the modules would make it clash with another path.
+    StripModules(Vec<String>),
+    /// Collapses modules that contain no types and a single child module into that child, keeping the name
+    /// of the outer module, e.g. `types::pallet_balances::Call` if `pallet_balances` only contains `pallet`.
+    CollapseSingleChildModules,
+    /// Generates all types in the types module. Types with the same name are disambiguated by prefixing
+    /// them with their innermost modules, e.g. `types::PalletBalancesPalletCall`.
+    Flat,
 }
 
 /// Information about how to construct the type paths for types that need allocation, e.g.
@@ -71,13 +181,29 @@ impl Default for TypeGeneratorSettings {
         Self {
             types_mod_ident: parse_quote!(types),
             should_gen_docs: true,
+            original_type_param_names: false,
+            instantiation_aliases: false,
+            monomorphize: false,
             substitutes: TypeSubstitutes::new(),
+            renames: HashMap::new(),
+            module_layout: ModuleLayout::Nested,
             derives: DerivesRegistry::new(),
             decoded_bits_type_path: None,
             compact_as_type_path: None,
             compact_type_path: None,
             insert_codec_attributes: false,
+            explicit_codec_impls: None,
+            explicit_encode_as_type_impls: None,
+            explicit_decode_as_type_impls: None,
+            serde_json_compat: None,
+            naming: NamingPolicy::default(),
             alloc_crate_path: Default::default(),
+            u256_type: None,
+            i256_type: None,
+            prelude_types: HashMap::new(),
+            roots: vec![],
+            infer_derives: false,
+            external_type_traits: HashMap::new(),
         }
     }
 }
@@ -103,6 +229,47 @@ impl TypeGeneratorSettings {
         self
     }
 
+    /// Renames the generated type with the path `from` in the type registry to `to`, which is relative to the
+    /// types module. See [`TypeGeneratorSettings::renames`].
+    pub fn rename_type(mut self, from: syn::Path, to: syn::Path) -> Self {
+        self.renames
+            .insert(path_segments(&from), path_segments(&to));
+        self
+    }
+
+    /// Keep the names of generic type parameters from the type registry. See the `original_type_param_names` field.
+    pub fn original_type_param_names(mut self) -> Self {
+        self.original_type_param_names = true;
+        self
+    }
+
+    /// Generate type aliases for concrete instantiations of generic types. See the `instantiation_aliases` field.
+    pub fn instantiation_aliases(mut self) -> Self {
+        self.instantiation_aliases = true;
+        self
+    }
+
+    /// Generate concrete types instead of generic types. See the `monomorphize` field.
+    pub fn monomorphize(mut self) -> Self {
+        self.monomorphize = true;
+        self
+    }
+
+    /// Sets the `module_layout` field.
+    pub fn module_layout(mut self, module_layout: ModuleLayout) -> Self {
+        self.module_layout = module_layout;
+        self
+    }
+
+    /// Adds a rule, that all types with a path matching the `from` pattern should be replaced with the path `to`.
+    /// See [`TypeSubstitutes::insert_pattern`].
+    pub fn substitute_pattern(mut self, from: &str, to: syn::Path) -> Self {
+        self.substitutes
+            .insert_pattern(from, absolute_path(to).unwrap())
+            .unwrap();
+        self
+    }
+
     /// Sets the `compact_as_type_path` field.
     pub fn compact_as_type_path(mut self, path: syn::Path) -> Self {
         self.compact_as_type_path = Some(path);
@@ -121,6 +288,57 @@ impl TypeGeneratorSettings {
         self
     }
 
+    /// Sets the `u256_type` field.
+    pub fn u256_type(mut self, ty: syn::Type) -> Self {
+        self.u256_type = Some(ty);
+        self
+    }
+
+    /// Sets the `i256_type` field.
+    pub fn i256_type(mut self, ty: syn::Type) -> Self {
+        self.i256_type = Some(ty);
+        self
+    }
+
+    /// Adds a prelude type, such that types with the single segment path `name` are referred to via `path`.
+    pub fn prelude_type(mut self, name: &str, path: syn::Path) -> Self {
+        self.prelude_types.insert(name.to_string(), path);
+        self
+    }
+
+    /// Adds the type with this id in the type registry as a root. See the `roots` field.
+    pub fn root_type_id(mut self, type_id: u32) -> Self {
+        self.roots.push(TypeRoot::Id(type_id));
+        self
+    }
+
+    /// Adds all types with a path matching the pattern as roots, e.g. `pallet_balances::pallet::Call`
+    /// or `pallet_staking::**`. See the `roots` field and [`PathPattern`] for the pattern syntax.
+    pub fn root_path(mut self, pattern: &str) -> Self {
+        self.roots.push(TypeRoot::Path(PathPattern::new(pattern)));
+        self
+    }
+
+    /// Sets the `infer_derives` field.
+    pub fn infer_derives(mut self) -> Self {
+        self.infer_derives = true;
+        self
+    }
+
+    /// Declares which derivable traits the type at `path` implements (given its generic parameters implement them).
+    /// See the `external_type_traits` field.
+    pub fn external_type_traits(
+        mut self,
+        path: syn::Path,
+        traits: impl IntoIterator<Item = DerivableTrait>,
+    ) -> Self {
+        self.external_type_traits
+            .entry(path_segments(&path))
+            .or_default()
+            .extend(traits);
+        self
+    }
+
     /// Sets the `should_gen_docs` field.
     pub fn should_gen_docs(mut self, should_gen_docs: bool) -> Self {
         self.should_gen_docs = should_gen_docs;
@@ -133,6 +351,36 @@ impl TypeGeneratorSettings {
         self
     }
 
+    /// Sets the `explicit_codec_impls` field.
+    pub fn explicit_codec_impls(mut self, codec_crate_path: syn::Path) -> Self {
+        self.explicit_codec_impls = Some(codec_crate_path);
+        self
+    }
+
+    /// Sets the `explicit_encode_as_type_impls` field.
+    pub fn explicit_encode_as_type_impls(mut self, scale_encode_crate_path: syn::Path) -> Self {
+        self.explicit_encode_as_type_impls = Some(scale_encode_crate_path);
+        self
+    }
+
+    /// Sets the `explicit_decode_as_type_impls` field.
+    pub fn explicit_decode_as_type_impls(mut self, scale_decode_crate_path: syn::Path) -> Self {
+        self.explicit_decode_as_type_impls = Some(scale_decode_crate_path);
+        self
+    }
+
+    /// Sets the `serde_json_compat` field.
+    pub fn serde_json_compat(mut self, serde_crate_path: syn::Path) -> Self {
+        self.serde_json_compat = Some(serde_crate_path);
+        self
+    }
+
+    /// Sets the `naming` field.
+    pub fn naming(mut self, naming: NamingPolicy) -> Self {
+        self.naming = naming;
+        self
+    }
+
     /// Adds some derives for all types.
     pub fn add_derives_for_all(
         mut self,
@@ -141,4 +389,36 @@ impl TypeGeneratorSettings {
         self.derives.add_derives_for_all(derive_paths);
         self
     }
+
+    /// Checks that all renames have non-empty paths consisting of valid identifiers.
+    pub(crate) fn validate_renames(&self) -> Result<(), TypegenError> {
+        let is_valid = |path: &[String]| {
+            !path.is_empty() && path.iter().all(|s| syn::parse_str::<Ident>(s).is_ok())
+        };
+        for (from, to) in &self.renames {
+            if !is_valid(from) || !is_valid(to) {
+                return Err(TypegenError::InvalidRename {
+                    from: from.join("::"),
+                    to: to.join("::"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The path of a generated type relative to the types module. This is its path in the type registry,
+    /// unless it is renamed or a prelude type that is generated for serde JSON compatibility.
+    pub(crate) fn generated_path<'a>(&'a self, registry_path: &'a [String]) -> Cow<'a, [String]> {
+        if let Some(renamed) = self.renames.get(registry_path) {
+            return Cow::Borrowed(renamed);
+        }
+        match registry_path {
+            [name]
+                if self.serde_json_compat.is_some() && !self.prelude_types.contains_key(name) =>
+            {
+                Cow::Owned(vec![SERDE_JSON_COMPAT_MOD.to_string(), name.clone()])
+            }
+            _ => Cow::Borrowed(registry_path),
+        }
+    }
 }
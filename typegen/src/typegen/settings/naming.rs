@@ -0,0 +1,172 @@
+use proc_macro2::{Ident, Span};
+
+/// Decides how the names of fields and variants in the type registry are turned into rust identifiers.
+///
+/// Names that differ from the original name after conversion get a `#[serde(rename = "..")]` attribute
+/// if `serde_rename` is set (or serde JSON compatibility is enabled), so they still serialize with their original name.
+/// The SCALE encoding does not depend on names: fields are encoded in order and variants by their index.
+/// The `EncodeAsType` and `DecodeAsType` derives match fields and variants by their rust name, so for types whose
+/// names are changed, these derives are replaced by explicit impls that use the original names. This is only possible
+/// for derives added for all types: generating a type that specifically derives these traits fails if its names change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamingPolicy {
+    /// How names that are rust keywords, like `type` or `move`, are escaped.
+    pub keyword_escape: KeywordEscape,
+    /// The case that field names are converted to.
+    pub field_case: NameCase,
+    /// The case that variant names are converted to.
+    pub variant_case: NameCase,
+    /// If true, `#[serde(rename = "..")]` attributes are inserted for renamed fields and variants.
+    pub serde_rename: bool,
+}
+
+/// How names that are rust keywords are escaped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum KeywordEscape {
+    /// Use raw identifiers, e.g. `r#type`. This is the default.
+    ///
+    /// `self`, `Self`, `super` and `crate` cannot be raw identifiers, they get a `_` suffix instead.
+    #[default]
+    Raw,
+    /// Append a suffix, e.g. `type_` for a suffix of `_`.
+    Suffix(String),
+}
+
+/// The case convention of generated names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameCase {
+    /// Keep the name from the type registry. This is the default.
+    #[default]
+    Preserve,
+    /// Convert to `snake_case`.
+    Snake,
+    /// Convert to `UpperCamelCase`.
+    UpperCamel,
+}
+
+/// Keywords of the 2021 edition, including reserved ones.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords that cannot be used as raw identifiers.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+impl NamingPolicy {
+    /// Creates the identifier for a field with the given name.
+    pub fn field_ident(&self, name: &str) -> Result<Ident, syn::Error> {
+        self.ident(name, self.field_case)
+    }
+
+    /// Creates the identifier for a variant with the given name.
+    pub fn variant_ident(&self, name: &str) -> Result<Ident, syn::Error> {
+        self.ident(name, self.variant_case)
+    }
+
+    fn ident(&self, name: &str, case: NameCase) -> Result<Ident, syn::Error> {
+        // Types deriving `TypeInfo` keep the prefix of raw identifiers in their field names.
+        let name = name.strip_prefix("r#").unwrap_or(name);
+        let name = match case {
+            NameCase::Preserve => name.to_string(),
+            NameCase::Snake => to_snake_case(name),
+            NameCase::UpperCamel => to_upper_camel_case(name),
+        };
+        if !KEYWORDS.contains(&name.as_str()) {
+            return syn::parse_str(&name);
+        }
+        match &self.keyword_escape {
+            KeywordEscape::Raw if !NON_RAW_KEYWORDS.contains(&name.as_str()) => {
+                Ok(Ident::new_raw(&name, Span::call_site()))
+            }
+            KeywordEscape::Raw => syn::parse_str(&format!("{name}_")),
+            KeywordEscape::Suffix(suffix) => syn::parse_str(&format!("{name}{suffix}")),
+        }
+    }
+}
+
+/// Splits a name into words at `_`s and case boundaries, e.g. `HTTPServer_id` into `HTTP`, `Server` and `id`.
+/// Leading underscores are returned separately, because they usually carry meaning.
+fn words(name: &str) -> (&str, Vec<String>) {
+    let trimmed = name.trim_start_matches('_');
+    let leading = &name[..name.len() - trimmed.len()];
+
+    let mut words = vec![];
+    for part in trimmed.split('_').filter(|part| !part.is_empty()) {
+        let chars = part.chars().collect::<Vec<_>>();
+        let mut word = String::new();
+        for (i, c) in chars.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let boundary = c.is_uppercase()
+                && prev.is_some_and(|prev| {
+                    // `fooBar`, `foo2Bar` or the last capital of an acronym, like the `S` in `HTTPServer`.
+                    !prev.is_uppercase() || next.is_some_and(|next| next.is_lowercase())
+                });
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(*c);
+        }
+        words.push(word);
+    }
+    (leading, words)
+}
+
+fn to_snake_case(name: &str) -> String {
+    let (leading, words) = words(name);
+    let words = words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+    format!("{leading}{}", words.join("_"))
+}
+
+pub(crate) fn to_upper_camel_case(name: &str) -> String {
+    let (leading, words) = words(name);
+    let words = words
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().map(|c| c.to_uppercase().to_string());
+            format!(
+                "{}{}",
+                first.unwrap_or_default(),
+                chars.as_str().to_lowercase()
+            )
+        })
+        .collect::<String>();
+    format!("{leading}{words}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_snake_case, to_upper_camel_case};
+
+    #[test]
+    fn case_conversion() {
+        let cases = [
+            ("foo", "foo", "Foo"),
+            ("foo_bar", "foo_bar", "FooBar"),
+            ("fooBar", "foo_bar", "FooBar"),
+            ("FooBar", "foo_bar", "FooBar"),
+            ("HTTPServer", "http_server", "HttpServer"),
+            ("FOO_BAR", "foo_bar", "FooBar"),
+            ("foo2Bar", "foo2_bar", "Foo2Bar"),
+            ("v2", "v2", "V2"),
+            ("_private", "_private", "_Private"),
+            ("foo__bar", "foo_bar", "FooBar"),
+        ];
+        for (name, snake, camel) in cases {
+            assert_eq!(to_snake_case(name), snake, "snake case of {name}");
+            assert_eq!(
+                to_upper_camel_case(name),
+                camel,
+                "upper camel case of {name}"
+            );
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::Display;
+
+/// A pattern that type paths can be matched against, e.g. `sp_arithmetic::per_things::Per*` or `pallet_staking::**`.
+///
+/// Patterns are matched segment by segment:
+/// - `*` within a segment matches any sequence of characters in that segment, e.g. `Per*` matches `Perbill`.
+/// - A `**` segment matches any number of segments (including none).
+/// - Any other segment has to be equal to the segment of the path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PathPattern {
+    segments: Vec<String>,
+}
+
+impl PathPattern {
+    /// Creates a new pattern from a `::` separated path like `pallet_*::pallet::Call`.
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split("::")
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        Self { segments }
+    }
+
+    /// The segments of the pattern.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// Returns true if the pattern contains no wildcards, so it matches exactly one path.
+    pub fn is_exact(&self) -> bool {
+        !self.segments.iter().any(|s| s.contains('*'))
+    }
+
+    /// Returns true if the path segments match this pattern.
+    pub fn matches(&self, path: &[String]) -> bool {
+        matches_segments(&self.segments, path)
+    }
+}
+
+impl From<&str> for PathPattern {
+    fn from(pattern: &str) -> Self {
+        PathPattern::new(pattern)
+    }
+}
+
+impl Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.segments.join("::"))
+    }
+}
+
+fn matches_segments(pattern: &[String], path: &[String]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((first, rest)), _) if first == "**" => {
+            // Either the `**` matches no further segment, or it consumes the next one.
+            matches_segments(rest, path)
+                || (!path.is_empty() && matches_segments(pattern, &path[1..]))
+        }
+        (Some((first, rest)), Some((segment, path_rest))) => {
+            matches_segment(first, segment) && matches_segments(rest, path_rest)
+        }
+        _ => false,
+    }
+}
+
+/// Matches a single segment, where `*` matches any sequence of characters.
+fn matches_segment(pattern: &str, segment: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = segment.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else {
+        // No `*` in the pattern, so the segment has to match exactly.
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(idx) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[idx + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PathPattern;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split("::").map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn path_pattern_matching() {
+        let cases = [
+            ("a::b::C", "a::b::C", true),
+            ("a::b::C", "a::b::D", false),
+            ("a::b", "a::b::C", false),
+            ("a::*::C", "a::b::C", true),
+            ("a::*::C", "a::C", false),
+            ("a::**::C", "a::C", true),
+            ("a::**::C", "a::b::c::C", true),
+            ("a::**", "a::b::C", true),
+            ("**::C", "a::b::C", true),
+            ("**::C", "a::b::D", false),
+            ("a::b::Per*", "a::b::Perbill", true),
+            ("a::b::Per*", "a::b::Percent", true),
+            ("a::b::Per*", "a::b::FixedU64", false),
+            (
+                "pallet_*::pallet::Call",
+                "pallet_balances::pallet::Call",
+                true,
+            ),
+            ("*_vec::Bounded*Vec", "bounded_vec::BoundedVec", true),
+            ("a::*x*y", "a::xy", true),
+            ("a::*x*y", "a::yx", false),
+        ];
+        for (pattern, p, expected) in cases {
+            assert_eq!(
+                PathPattern::new(pattern).matches(&path(p)),
+                expected,
+                "{pattern} matching {p}"
+            );
+        }
+    }
+}
@@ -6,6 +6,7 @@ use syn::{parse_quote, spanned::Spanned as _, PathSegment};
 use crate::{
     typegen::{
         error::{TypeSubstitutionError, TypeSubstitutionErrorKind},
+        settings::path_pattern::PathPattern,
         type_path::{TypePath, TypePathType},
     },
     TypeGeneratorSettings,
@@ -19,9 +20,14 @@ fn error(span: Span, kind: TypeSubstitutionErrorKind) -> TypeSubstitutionError {
 
 /// A map of type substitutes. We match on the paths to generated types in order
 /// to figure out when to swap said type with some provided substitute.
+///
+/// Substitutes can be registered for exact paths or for [`PathPattern`]s. If several substitutes match a path,
+/// a substitute for the exact path always takes precedence over pattern substitutes. Among the matching
+/// pattern substitutes, the one inserted last wins.
 #[derive(Debug, Clone)]
 pub struct TypeSubstitutes {
     substitutes: HashMap<PathSegments, Substitute>,
+    pattern_substitutes: Vec<(PathPattern, Substitute)>,
 }
 
 /// We use this `Vec<String>` as a common denominator, since we need a consistent key for both
@@ -63,6 +69,7 @@ impl TypeSubstitutes {
     pub fn new() -> Self {
         Self {
             substitutes: HashMap::new(),
+            pattern_substitutes: Vec::new(),
         }
     }
 
@@ -101,6 +108,46 @@ impl TypeSubstitutes {
         Ok(())
     }
 
+    /// Insert a substitution for all types with a path matching the pattern, e.g. `sp_arithmetic::per_things::Per*`
+    /// or `**::bounded_vec::BoundedVec<T>`. See [`PathPattern`] for the pattern syntax. Generic params can be
+    /// mapped to the target in the same way as for [`TypeSubstitutes::insert`].
+    ///
+    /// Overwrites any other pattern substitution with the same pattern.
+    pub fn insert_pattern(
+        &mut self,
+        source: &str,
+        target: AbsolutePath,
+    ) -> Result<(), TypeSubstitutionError> {
+        let source = source.trim();
+        let (pattern, generics) = match source.find('<') {
+            Some(idx) => source.split_at(idx),
+            None => (source, ""),
+        };
+        let pattern = PathPattern::new(pattern);
+        let Some(ident) = pattern.segments().last() else {
+            return Err(error(Span::call_site(), EmptySubstitutePath));
+        };
+
+        // Only the generics of the source are relevant for the param mapping, so we check
+        // them against a placeholder path, because the pattern itself is not a valid `syn::Path`.
+        let placeholder = if ident.contains('*') {
+            "__Pattern"
+        } else {
+            ident
+        };
+        let src_path: syn::Path = syn::parse_str(&format!("{placeholder}{generics}"))
+            .map_err(|e| error(e.span(), InvalidPathPattern))?;
+        let param_mapping = Self::parse_path_param_mapping(&src_path, &target.0)?;
+
+        let substitute = Substitute {
+            path: target.0,
+            param_mapping,
+        };
+        self.pattern_substitutes.retain(|(p, _)| p != &pattern);
+        self.pattern_substitutes.push((pattern, substitute));
+        Ok(())
+    }
+
     /// Given a source and target path, parse the type params to work out the mapping from
     /// source to target, and output the source => substitution mapping that we work out from this.
     fn parse_path_substitution(
@@ -203,7 +250,22 @@ impl TypeSubstitutes {
 
     /// Given a source type path, return whether a substitute exists for it.
     pub fn contains(&self, path: &PathSegments) -> bool {
-        !path.is_empty() && self.substitutes.contains_key(path)
+        self.get(path).is_some()
+    }
+
+    /// Returns the substitute for the source type path. Substitutes for the exact path take precedence over
+    /// pattern substitutes, and later pattern substitutes take precedence over earlier ones.
+    pub fn get(&self, path: &PathSegments) -> Option<&Substitute> {
+        if path.is_empty() {
+            return None;
+        }
+        self.substitutes.get(path).or_else(|| {
+            self.pattern_substitutes
+                .iter()
+                .rev()
+                .find(|(pattern, _)| pattern.matches(path))
+                .map(|(_, sub)| sub)
+        })
     }
 
     /// Given a source type path and the resolved, supplied type parameters,
@@ -254,15 +316,19 @@ impl TypeSubstitutes {
             }
         }
 
-        self.substitutes
-            .get(path)
+        self.get(path)
             .map(|sub| replace_params(sub.path.clone(), params, &sub.param_mapping, settings))
     }
 
-    /// Returns an iterator over all substitutes.
+    /// Returns an iterator over all substitutes for exact paths.
     pub fn iter(&self) -> impl Iterator<Item = (&PathSegments, &Substitute)> {
         self.substitutes.iter()
     }
+
+    /// Returns an iterator over all pattern substitutes, in the order they were inserted.
+    pub fn iter_patterns(&self) -> impl Iterator<Item = (&PathPattern, &Substitute)> {
+        self.pattern_substitutes.iter().map(|(p, sub)| (p, sub))
+    }
 }
 
 /// Dig through a `syn::TypePath` (this is provided by the user in a type substitution definition as the "to" type) and
@@ -295,7 +361,7 @@ fn replace_path_params_recursively<I: Borrow<syn::Ident>, P: Borrow<TypePath>>(
             };
             if let Some(ident) = get_ident_from_type_path(path) {
                 if let Some((_, replacement)) = params.iter().find(|(i, _)| ident == i.borrow()) {
-                    *ty = replacement.borrow().to_syn_type(&settings.alloc_crate_path);
+                    *ty = replacement.borrow().to_syn_type(settings);
                     continue;
                 }
             }
@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use proc_macro2::{Ident, Span};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef};
+
+use crate::TypegenError;
+
+use super::{
+    ir::{module_ir::ModuleIR, type_alias_ir::TypeAliasIR},
+    type_path::{TypePath, TypePathInner, TypePathType},
+    TypeGenerator,
+};
+
+/// Inserts a type alias for each concrete instantiation of the generated generic types into the module
+/// of the generic type.
+pub(crate) fn insert_instantiation_aliases(
+    type_gen: &TypeGenerator,
+    root_mod: &mut ModuleIR,
+    reachable_type_ids: &Option<HashSet<u32>>,
+) -> Result<(), TypegenError> {
+    let hints = type_name_hints(type_gen.types());
+    for ty in type_gen.types().types.iter() {
+        let has_concrete_params = ty.ty.type_params.iter().any(|param| param.ty.is_some());
+        if !has_concrete_params || !type_gen.is_generated(ty, reachable_type_ids) {
+            continue;
+        }
+
+        let generated_path = type_gen.settings().generated_path(&ty.ty.path.segments);
+        let (type_name, namespace) = generated_path
+            .split_last()
+            .expect("generated types have a path; qed");
+        let type_path = type_gen.resolve_type_path(ty.id)?;
+
+        let hint = hints.get(&ty.id).and_then(|hints| {
+            hints
+                .iter()
+                .filter(|(name, _)| *name != type_name)
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+                .map(|(name, _)| name.clone())
+        });
+        let fallback = format!("{type_name}{}", params_name(&type_path));
+        let module = root_mod.get_or_insert_submodule(namespace);
+        let name = hint
+            .into_iter()
+            .chain([fallback.clone()])
+            .chain((2..).map(|i| format!("{fallback}{i}")))
+            .find(|name| !is_taken(module, name))
+            .expect("there are infinitely many candidates; qed");
+
+        let name = Ident::new(&name, Span::call_site());
+        let alias = TypeAliasIR {
+            name: name.clone(),
+            type_id: ty.id,
+            type_path,
+        };
+        module.aliases.insert(name, alias);
+    }
+    Ok(())
+}
+
+/// Counts the type names of all fields in the registry by the type id of the field.
+/// The type names are reduced to their last segment, e.g. `HeaderFor` for `frame_system::HeaderFor<T>`.
+fn type_name_hints(types: &PortableRegistry) -> HashMap<u32, BTreeMap<String, usize>> {
+    let mut hints: HashMap<u32, BTreeMap<String, usize>> = HashMap::new();
+    for ty in types.types.iter() {
+        let fields: Vec<&Field<PortableForm>> = match &ty.ty.type_def {
+            TypeDef::Composite(composite) => composite.fields.iter().collect(),
+            TypeDef::Variant(variant) => variant
+                .variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter())
+                .collect(),
+            _ => continue,
+        };
+        for field in fields {
+            let Some(name) = field.type_name.as_deref().and_then(hint_name) else {
+                continue;
+            };
+            // Type names like `T` refer to the type parameters of the containing type.
+            let is_param = ty.ty.type_params.iter().any(|param| param.name == name);
+            // `Box`es are transparent in the registry, so the field has the type of the boxed value.
+            if is_param || name == "Box" {
+                continue;
+            }
+            *hints
+                .entry(field.ty.id)
+                .or_default()
+                .entry(name)
+                .or_default() += 1;
+        }
+    }
+    hints
+}
+
+fn hint_name(type_name: &str) -> Option<String> {
+    let without_generics = type_name.split('<').next()?;
+    let name = without_generics.rsplit("::").next()?.trim();
+    syn::parse_str::<Ident>(name).ok()?;
+    Some(name.to_string())
+}
+
+/// Names of the Rust prelude and of primitive types, which aliases should not shadow.
+const RESERVED_NAMES: &[&str] = &[
+    "AsMut",
+    "AsRef",
+    "Box",
+    "Clone",
+    "Copy",
+    "Default",
+    "DoubleEndedIterator",
+    "Drop",
+    "Eq",
+    "Err",
+    "ExactSizeIterator",
+    "Extend",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "From",
+    "FromIterator",
+    "Into",
+    "IntoIterator",
+    "Iterator",
+    "None",
+    "Ok",
+    "Option",
+    "Ord",
+    "PartialEq",
+    "PartialOrd",
+    "Result",
+    "Send",
+    "Sized",
+    "Some",
+    "String",
+    "Sync",
+    "ToOwned",
+    "ToString",
+    "TryFrom",
+    "TryInto",
+    "Unpin",
+    "Vec",
+    "bool",
+    "char",
+    "f32",
+    "f64",
+    "i128",
+    "i16",
+    "i32",
+    "i64",
+    "i8",
+    "isize",
+    "str",
+    "u128",
+    "u16",
+    "u32",
+    "u64",
+    "u8",
+    "usize",
+];
+
+fn is_taken(module: &ModuleIR, name: &str) -> bool {
+    // Keywords and raw identifiers are no valid alias names.
+    let is_plain_ident = !name.starts_with("r#") && syn::parse_str::<Ident>(name).is_ok();
+    !is_plain_ident
+        || RESERVED_NAMES.contains(&name)
+        || module.root_mod == name
+        || module.types.values().any(|(_, ty)| ty.ident() == name)
+        || module.children.keys().any(|child| child == name)
+        || module.aliases.keys().any(|alias| alias == name)
+}
+
+/// A readable name for the generic parameters of a type, e.g. `U32BlakeTwo256` for `Header<u32, BlakeTwo256>`.
+pub(super) fn params_name(type_path: &TypePath) -> String {
+    match type_path.inner() {
+        TypePathInner::Type(TypePathType::Path { params, .. }) => {
+            params.iter().map(readable_name).collect()
+        }
+        _ => String::new(),
+    }
+}
+
+fn readable_name(type_path: &TypePath) -> String {
+    let ty = match type_path.inner() {
+        TypePathInner::Parameter(param) => return param.name.to_string(),
+        TypePathInner::Type(ty) => ty,
+    };
+    match ty {
+        TypePathType::Path { path, params } => {
+            let ident = path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+                .unwrap_or_default();
+            let params = params.iter().map(readable_name).collect::<String>();
+            format!("{ident}{params}")
+        }
+        TypePathType::Vec { of } => format!("Vec{}", readable_name(of)),
+        TypePathType::Array { len, of } => format!("{}Array{len}", readable_name(of)),
+        TypePathType::Tuple { elements } if elements.is_empty() => "Unit".to_string(),
+        TypePathType::Tuple { elements } => {
+            let elements = elements.iter().map(readable_name).collect::<String>();
+            format!("Tuple{elements}")
+        }
+        TypePathType::Primitive { def } | TypePathType::MappedPrimitive { def, .. } => {
+            format!("{def:?}")
+        }
+        TypePathType::Compact { inner, .. } => format!("Compact{}", readable_name(inner)),
+        TypePathType::BitVec { .. } => "BitVec".to_string(),
+    }
+}
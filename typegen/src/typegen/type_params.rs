@@ -3,6 +3,7 @@
 // see LICENSE for license details.
 
 use super::TypeParameter;
+use proc_macro2::Ident;
 use quote::{format_ident, quote};
 use scale_info::form::PortableForm;
 use std::collections::BTreeSet;
@@ -21,17 +22,40 @@ pub struct TypeParameters {
 impl TypeParameters {
     /// Create a new [`TypeParameters`] instance.
     pub fn from_scale_info(params: &[scale_info::TypeParameter<PortableForm>]) -> Self {
+        Self::from_scale_info_with_names(params, |i, _| format_ident!("_{}", i))
+    }
+
+    /// Create a new [`TypeParameters`] instance, where the parameters keep their names from the type registry,
+    /// e.g. `T` or `Balance`. A parameter is named by its index like `_0` instead, if its name is not a valid
+    /// identifier, starts with `_`, is used by another parameter of the type or is the `types_mod_ident`
+    /// (which would shadow the paths to other generated types).
+    pub fn from_scale_info_with_original_names(
+        params: &[scale_info::TypeParameter<PortableForm>],
+        types_mod_ident: &Ident,
+    ) -> Self {
+        let is_unique = |name: &str| params.iter().filter(|tp| tp.name == name).count() == 1;
+        Self::from_scale_info_with_names(params, |i, name| {
+            syn::parse_str::<Ident>(name)
+                .ok()
+                .filter(|ident| {
+                    !name.starts_with('_') && ident != types_mod_ident && is_unique(name)
+                })
+                .unwrap_or_else(|| format_ident!("_{}", i))
+        })
+    }
+
+    fn from_scale_info_with_names(
+        params: &[scale_info::TypeParameter<PortableForm>],
+        name: impl Fn(usize, &str) -> Ident,
+    ) -> Self {
         let params = params
             .iter()
             .enumerate()
             .filter_map(|(i, tp)| {
-                tp.ty.as_ref().map(|ty| {
-                    let tp_name = format_ident!("_{}", i);
-                    TypeParameter {
-                        concrete_type_id: ty.id,
-                        original_name: tp.name.clone(),
-                        name: tp_name,
-                    }
+                tp.ty.as_ref().map(|ty| TypeParameter {
+                    concrete_type_id: ty.id,
+                    original_name: tp.name.clone(),
+                    name: name(i, &tp.name),
                 })
             })
             .collect::<Vec<_>>();
@@ -64,6 +88,11 @@ impl TypeParameters {
         &self.params
     }
 
+    /// Returns the type parameters that are used in the fields of the type.
+    pub fn used_params(&self) -> impl Iterator<Item = &TypeParameter> {
+        self.params.iter().filter(|p| !self.unused.contains(p))
+    }
+
     /// Returns true if there are any unused type params
     pub fn has_unused_type_params(&self) -> bool {
         !self.unused.is_empty()
@@ -5,7 +5,7 @@
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, ToTokens};
 use scale_info::{form::PortableForm, Path, TypeDefPrimitive};
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use syn::parse_quote;
 
 use crate::TypeGeneratorSettings;
@@ -30,7 +30,7 @@ pub enum TypePathInner {
 
 impl ToTokensWithSettings for TypePath {
     fn to_tokens(&self, tokens: &mut TokenStream, settings: &TypeGeneratorSettings) {
-        let syn_type = self.to_syn_type(&settings.alloc_crate_path);
+        let syn_type = self.to_syn_type(settings);
         syn_type.to_tokens(tokens)
     }
 }
@@ -56,10 +56,15 @@ impl TypePath {
         }))
     }
 
-    pub(crate) fn to_syn_type(&self, alloc_crate_path: &AllocCratePath) -> syn::Type {
+    /// Returns whether this is a generic type parameter or a concrete type.
+    pub fn inner(&self) -> &TypePathInner {
+        &self.0
+    }
+
+    pub(crate) fn to_syn_type(&self, settings: &TypeGeneratorSettings) -> syn::Type {
         match &self.0 {
             TypePathInner::Parameter(ty_param) => syn::Type::Path(parse_quote! { #ty_param }),
-            TypePathInner::Type(ty) => ty.to_syn_type(alloc_crate_path),
+            TypePathInner::Type(ty) => ty.to_syn_type(settings),
         }
     }
 
@@ -154,11 +159,19 @@ pub enum TypePathType {
         /// Types that make up this tuple
         elements: Vec<TypePath>,
     },
-    /// Primitive type
+    /// Primitive type. `U256` and `I256` have no rust equivalent, they are resolved to a [`TypePathType::MappedPrimitive`]
+    /// for rust code. If they are rendered as rust code anyway, they become a `compile_error!`.
     Primitive {
         /// A primitive Rust type.
         def: TypeDefPrimitive,
     },
+    /// A primitive without a rust equivalent (`U256` or `I256`), represented by a type from the settings.
+    MappedPrimitive {
+        /// The primitive that is represented.
+        def: TypeDefPrimitive,
+        /// The type used in place of the primitive, e.g. `[u8; 32]`.
+        ty: syn::Type,
+    },
     /// A compact encoded type
     Compact {
         /// The type that is being compact encoded
@@ -175,60 +188,54 @@ pub enum TypePathType {
         /// Store type
         bit_store_type: Box<TypePath>,
         /// A user defined wrapper type around scale_bits::Bits. Should be generic over the `order` and `store` types.
-        decoded_bits_type_path: syn::Path,
+        /// This is only `None` if the type path is not resolved for rust code, e.g. for the TypeScript output.
+        decoded_bits_type_path: Option<syn::Path>,
     },
 }
 
 impl TypePathType {
     /// Constructs a [`TypePathType`] from some context information.
+    ///
+    /// Single segment paths are looked up in `prelude_types` first and then in the builtin prelude types.
+    /// Returns `None` if the path is a single segment path that is not a known prelude type.
     pub fn from_type_def_path(
         path: &Path<PortableForm>,
         root_mod_ident: Ident,
         params: Vec<TypePath>,
         alloc_crate_path: &AllocCratePath,
-    ) -> Self {
+        prelude_types: &HashMap<String, syn::Path>,
+    ) -> Option<Self> {
         let path_segments = &*path.segments;
 
         let path: syn::Path = match path_segments {
             [] => panic!("Type has no ident"),
-            [ident] => {
-                // paths to prelude types
-                match ident.as_str() {
-                    "Option" => parse_quote!(::core::option::Option),
-                    "Result" => parse_quote!(::core::result::Result),
-                    "Cow" => parse_quote!(#alloc_crate_path::borrow::Cow),
-                    "BTreeMap" => parse_quote!(#alloc_crate_path::collections::BTreeMap),
-                    "BTreeSet" => parse_quote!(#alloc_crate_path::collections::BTreeSet),
-                    "BinaryHeap" => parse_quote!(#alloc_crate_path::collections::BinaryHeap),
-                    "VecDeque" => parse_quote!(#alloc_crate_path::collections::VecDeque),
-                    "LinkedList" => parse_quote!(#alloc_crate_path::collections::LinkedList),
-                    "Range" => parse_quote!(::core::ops::Range),
-                    "RangeInclusive" => parse_quote!(::core::ops::RangeInclusive),
-                    "NonZeroI8" => parse_quote!(::core::num::NonZeroI8),
-                    "NonZeroU8" => parse_quote!(::core::num::NonZeroU8),
-                    "NonZeroI16" => parse_quote!(::core::num::NonZeroI16),
-                    "NonZeroU16" => parse_quote!(::core::num::NonZeroU16),
-                    "NonZeroI32" => parse_quote!(::core::num::NonZeroI32),
-                    "NonZeroU32" => parse_quote!(::core::num::NonZeroU32),
-                    "NonZeroI64" => parse_quote!(::core::num::NonZeroI64),
-                    "NonZeroU64" => parse_quote!(::core::num::NonZeroU64),
-                    "NonZeroI128" => parse_quote!(::core::num::NonZeroI128),
-                    "NonZeroU128" => parse_quote!(::core::num::NonZeroU128),
-                    "NonZeroIsize" => parse_quote!(::core::num::NonZeroIsize),
-                    "NonZeroUsize" => parse_quote!(::core::num::NonZeroUsize),
-                    ident => panic!("Unknown prelude type '{ident}'"),
-                }
-            }
+            [ident] => match prelude_types.get(ident) {
+                Some(path) => path.clone(),
+                None => builtin_prelude_type_path(ident, alloc_crate_path)?,
+            },
             _ => {
-                // paths to generated types in the root types module
-                let mut ty_path = path_segments
-                    .iter()
-                    .map(|s| syn::PathSegment::from(format_ident!("{}", s)))
-                    .collect::<syn::punctuated::Punctuated<syn::PathSegment, syn::Token![::]>>();
-                ty_path.insert(0, syn::PathSegment::from(root_mod_ident));
-                parse_quote!( #ty_path )
+                return Some(Self::from_generated_type_path(
+                    path_segments,
+                    root_mod_ident,
+                    params,
+                ))
             }
         };
+        Some(Self::Path { path, params })
+    }
+
+    /// Constructs a [`TypePathType`] for a generated type, given its path relative to the root types module.
+    pub fn from_generated_type_path(
+        segments: &[String],
+        root_mod_ident: Ident,
+        params: Vec<TypePath>,
+    ) -> Self {
+        let mut ty_path = segments
+            .iter()
+            .map(|s| syn::PathSegment::from(format_ident!("{}", s)))
+            .collect::<syn::punctuated::Punctuated<syn::PathSegment, syn::Token![::]>>();
+        ty_path.insert(0, syn::PathSegment::from(root_mod_ident));
+        let path = parse_quote!( #ty_path );
         Self::Path { path, params }
     }
 
@@ -256,6 +263,7 @@ impl TypePathType {
                 }
             }
             TypePathType::Primitive { .. } => (),
+            TypePathType::MappedPrimitive { .. } => (),
             TypePathType::Compact { inner, .. } => inner.parent_type_params_recurse(acc),
             TypePathType::BitVec {
                 bit_order_type,
@@ -283,33 +291,34 @@ impl TypePathType {
         )
     }
 
-    fn to_syn_type(&self, alloc_crate_path: &AllocCratePath) -> syn::Type {
+    fn to_syn_type(&self, settings: &TypeGeneratorSettings) -> syn::Type {
+        let alloc_crate_path = &settings.alloc_crate_path;
         match &self {
             TypePathType::Path { path, params } => {
                 let path = if params.is_empty() {
                     parse_quote! { #path }
                 } else {
-                    let params = params.iter().map(|e| e.to_syn_type(alloc_crate_path));
+                    let params = params.iter().map(|e| e.to_syn_type(settings));
                     parse_quote! { #path< #( #params ),* > }
                 };
                 syn::Type::Path(path)
             }
             TypePathType::Vec { of } => {
-                let of = of.to_syn_type(alloc_crate_path);
+                let of = of.to_syn_type(settings);
                 let type_path = parse_quote! { #alloc_crate_path::vec::Vec<#of> };
                 syn::Type::Path(type_path)
             }
             TypePathType::Array { len, of } => {
-                let of = of.to_syn_type(alloc_crate_path);
+                let of = of.to_syn_type(settings);
                 let array = parse_quote! { [#of; #len] };
                 syn::Type::Array(array)
             }
             TypePathType::Tuple { elements } => {
-                let elements = elements.iter().map(|e| e.to_syn_type(alloc_crate_path));
+                let elements = elements.iter().map(|e| e.to_syn_type(settings));
                 let tuple = parse_quote! { (#( # elements, )* ) };
                 syn::Type::Tuple(tuple)
             }
-            TypePathType::Primitive { def } => syn::Type::Path(match def {
+            TypePathType::Primitive { def } => match def {
                 TypeDefPrimitive::Bool => parse_quote!(::core::primitive::bool),
                 TypeDefPrimitive::Char => parse_quote!(::core::primitive::char),
                 TypeDefPrimitive::Str => parse_quote!(#alloc_crate_path::string::String),
@@ -318,20 +327,25 @@ impl TypePathType {
                 TypeDefPrimitive::U32 => parse_quote!(::core::primitive::u32),
                 TypeDefPrimitive::U64 => parse_quote!(::core::primitive::u64),
                 TypeDefPrimitive::U128 => parse_quote!(::core::primitive::u128),
-                TypeDefPrimitive::U256 => unimplemented!("not a rust primitive"),
                 TypeDefPrimitive::I8 => parse_quote!(::core::primitive::i8),
                 TypeDefPrimitive::I16 => parse_quote!(::core::primitive::i16),
                 TypeDefPrimitive::I32 => parse_quote!(::core::primitive::i32),
                 TypeDefPrimitive::I64 => parse_quote!(::core::primitive::i64),
                 TypeDefPrimitive::I128 => parse_quote!(::core::primitive::i128),
-                TypeDefPrimitive::I256 => unimplemented!("not a rust primitive"),
-            }),
+                TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+                    let message = format!(
+                        "`{def:?}` is no rust primitive, resolve it to a `MappedPrimitive`"
+                    );
+                    parse_quote!(::core::compile_error!(#message))
+                }
+            },
+            TypePathType::MappedPrimitive { ty, .. } => ty.clone(),
             TypePathType::Compact {
                 inner,
                 is_field,
                 compact_type_path,
             } => {
-                let inner = inner.to_syn_type(alloc_crate_path);
+                let inner = inner.to_syn_type(settings);
                 let path = if *is_field {
                     // compact fields can use the inner compact type directly and be annotated with
                     // the `compact` attribute e.g. `#[codec(compact)] my_compact_field: u128`
@@ -346,8 +360,13 @@ impl TypePathType {
                 bit_store_type,
                 decoded_bits_type_path,
             } => {
-                let bit_order_type = bit_order_type.to_syn_type(alloc_crate_path);
-                let bit_store_type = bit_store_type.to_syn_type(alloc_crate_path);
+                let Some(decoded_bits_type_path) = decoded_bits_type_path else {
+                    return parse_quote!(::core::compile_error!(
+                        "bit sequences need a `decoded_bits_type_path`"
+                    ));
+                };
+                let bit_order_type = bit_order_type.to_syn_type(settings);
+                let bit_store_type = bit_store_type.to_syn_type(settings);
                 let type_path =
                     parse_quote! { #decoded_bits_type_path<#bit_store_type, #bit_order_type> };
                 syn::Type::Path(type_path)
@@ -356,6 +375,36 @@ impl TypePathType {
     }
 }
 
+/// Paths to the prelude types that are known without any configuration.
+fn builtin_prelude_type_path(ident: &str, alloc_crate_path: &AllocCratePath) -> Option<syn::Path> {
+    let path = match ident {
+        "Option" => parse_quote!(::core::option::Option),
+        "Result" => parse_quote!(::core::result::Result),
+        "Cow" => parse_quote!(#alloc_crate_path::borrow::Cow),
+        "BTreeMap" => parse_quote!(#alloc_crate_path::collections::BTreeMap),
+        "BTreeSet" => parse_quote!(#alloc_crate_path::collections::BTreeSet),
+        "BinaryHeap" => parse_quote!(#alloc_crate_path::collections::BinaryHeap),
+        "VecDeque" => parse_quote!(#alloc_crate_path::collections::VecDeque),
+        "LinkedList" => parse_quote!(#alloc_crate_path::collections::LinkedList),
+        "Range" => parse_quote!(::core::ops::Range),
+        "RangeInclusive" => parse_quote!(::core::ops::RangeInclusive),
+        "NonZeroI8" => parse_quote!(::core::num::NonZeroI8),
+        "NonZeroU8" => parse_quote!(::core::num::NonZeroU8),
+        "NonZeroI16" => parse_quote!(::core::num::NonZeroI16),
+        "NonZeroU16" => parse_quote!(::core::num::NonZeroU16),
+        "NonZeroI32" => parse_quote!(::core::num::NonZeroI32),
+        "NonZeroU32" => parse_quote!(::core::num::NonZeroU32),
+        "NonZeroI64" => parse_quote!(::core::num::NonZeroI64),
+        "NonZeroU64" => parse_quote!(::core::num::NonZeroU64),
+        "NonZeroI128" => parse_quote!(::core::num::NonZeroI128),
+        "NonZeroU128" => parse_quote!(::core::num::NonZeroU128),
+        "NonZeroIsize" => parse_quote!(::core::num::NonZeroIsize),
+        "NonZeroUsize" => parse_quote!(::core::num::NonZeroUsize),
+        _ => return None,
+    };
+    Some(path)
+}
+
 /// A generic type parameter
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct TypeParameter {
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use quote::ToTokens;
+use scale_info::TypeDefPrimitive;
+use syn::ext::IdentExt;
+
+use crate::TypegenError;
+
+use super::{
+    ir::{
+        module_ir::ModuleIR,
+        type_ir::{CompositeIRKind, TypeIR, TypeIRKind},
+    },
+    settings::substitutes::{path_segments, PathSegments},
+    type_path::{TypePath, TypePathInner, TypePathType},
+    TypeGenerator,
+};
+
+/// Definitions of the prelude types in their scale-value JSON representation. These are placed
+/// in front of the generated namespaces, such that they can be referred to from all generated types.
+const TYPESCRIPT_PRELUDE: &str = r#"export type Option<T> = { name: "None"; values: [] } | { name: "Some"; values: [T] };
+export type Result<T, E> = { name: "Ok"; values: [T] } | { name: "Err"; values: [E] };
+export type BTreeMap<K, V> = [[K, V][]];
+export type BTreeSet<T> = [T[]];
+export type BinaryHeap<T> = [T[]];
+export type Range<T> = { start: T; end: T };
+export type RangeInclusive<T> = { start: T; end: T };
+export type Duration = [number, number];
+export type BitSequence = { __bitvec__values__: boolean[] };
+"#;
+
+/// Prelude types that have a definition in [`TYPESCRIPT_PRELUDE`]. All `NonZero*` types are handled separately.
+const TYPESCRIPT_PRELUDE_TYPES: &[&str] = &[
+    "Option",
+    "Result",
+    "BTreeMap",
+    "BTreeSet",
+    "BinaryHeap",
+    "Range",
+    "RangeInclusive",
+    "Duration",
+];
+
+/// Settings for generating TypeScript type definitions via [`TypeGenerator::generate_typescript`].
+#[derive(Debug, Clone, Default)]
+pub struct TypeScriptSettings {
+    /// TypeScript types for rust types that are not generated, keyed by their rust path.
+    /// This is mainly needed for the targets of type substitutes, e.g. `::subxt::utils::AccountId32`.
+    /// Types without an entry here are represented as `unknown`.
+    pub external_types: HashMap<PathSegments, String>,
+}
+
+impl TypeScriptSettings {
+    /// Creates a new `TypeScriptSettings`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a TypeScript type to use for the rust type at `path`.
+    pub fn external_type(mut self, path: syn::Path, ts_type: &str) -> Self {
+        self.external_types
+            .insert(path_segments(&path), ts_type.to_string());
+        self
+    }
+}
+
+impl TypeGenerator<'_> {
+    /// Generate TypeScript type definitions for all types defined in the supplied type registry.
+    ///
+    /// The types match the JSON representation of `scale_value::Value`s of the same type. Types are
+    /// resolved in the same way as for [`TypeGenerator::generate_types_mod`], and are placed
+    /// in nested namespaces that mirror the generated rust modules.
+    ///
+    /// Settings that only affect rust type paths, like `compact_type_path`, do not need to be set.
+    pub fn generate_typescript(
+        &self,
+        ts_settings: &TypeScriptSettings,
+    ) -> Result<String, TypegenError> {
+        let ts_type_gen = TypeGenerator {
+            rust_paths: false,
+            ..TypeGenerator::new(self.type_registry, self.settings)
+        };
+        let module = ts_type_gen.generate_types_mod()?;
+        let ts_gen = TypeScriptGen {
+            root_mod: module.root_mod().to_string(),
+            ts_settings,
+        };
+        let mut out = TYPESCRIPT_PRELUDE.to_string();
+        out.push('\n');
+        ts_gen.write_module(&mut out, &module, 0);
+        Ok(out)
+    }
+}
+
+struct TypeScriptGen<'a> {
+    root_mod: String,
+    ts_settings: &'a TypeScriptSettings,
+}
+
+impl TypeScriptGen<'_> {
+    fn write_module(&self, out: &mut String, module: &ModuleIR, depth: usize) {
+        let indent = "    ".repeat(depth);
+        writeln!(out, "{indent}export namespace {} {{", module.ident()).unwrap();
+        for child in module.children.values() {
+            self.write_module(out, child, depth + 1);
+        }
+        for (_, ty) in module.types.values() {
+            self.write_type(out, ty, depth + 1);
+        }
+        writeln!(out, "{indent}}}").unwrap();
+    }
+
+    fn write_type(&self, out: &mut String, ty: &TypeIR, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let params = ty.type_params.params();
+        let generics = if params.is_empty() {
+            String::new()
+        } else {
+            let params = params
+                .iter()
+                .map(|p| p.to_token_stream().to_string())
+                .collect::<Vec<_>>();
+            format!("<{}>", params.join(", "))
+        };
+
+        match &ty.kind {
+            TypeIRKind::Struct(composite) => match &composite.kind {
+                CompositeIRKind::Named(fields) => {
+                    writeln!(
+                        out,
+                        "{indent}export interface {}{generics} {{",
+                        composite.name
+                    )
+                    .unwrap();
+                    for (name, field) in fields {
+                        writeln!(
+                            out,
+                            "{indent}    {}: {};",
+                            field_name(name, &field.original_name),
+                            self.type_path(&field.type_path)
+                        )
+                        .unwrap();
+                    }
+                    writeln!(out, "{indent}}}").unwrap();
+                }
+                kind => {
+                    let def = self.composite(kind);
+                    writeln!(
+                        out,
+                        "{indent}export type {}{generics} = {def};",
+                        composite.name
+                    )
+                    .unwrap();
+                }
+            },
+            TypeIRKind::Enum(enum_ir) => {
+                if enum_ir.variants.is_empty() {
+                    writeln!(
+                        out,
+                        "{indent}export type {}{generics} = never;",
+                        enum_ir.name
+                    )
+                    .unwrap();
+                    return;
+                }
+                writeln!(out, "{indent}export type {}{generics} =", enum_ir.name).unwrap();
+                let last = enum_ir.variants.len() - 1;
+                for (i, (_, variant)) in enum_ir.variants.iter().enumerate() {
+                    let end = if i == last { ";" } else { "" };
+                    writeln!(
+                        out,
+                        "{indent}    | {{ name: \"{}\"; values: {} }}{end}",
+                        field_name(&variant.name, &variant.original_name),
+                        self.composite(&variant.kind)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+
+    /// Named composites are objects, unnamed composites are arrays.
+    fn composite(&self, kind: &CompositeIRKind) -> String {
+        match kind {
+            CompositeIRKind::NoFields => "[]".to_string(),
+            CompositeIRKind::Named(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, field)| {
+                        format!(
+                            "{}: {}",
+                            field_name(name, &field.original_name),
+                            self.type_path(&field.type_path)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                format!("{{ {} }}", fields.join("; "))
+            }
+            CompositeIRKind::Unnamed(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|field| self.type_path(&field.type_path))
+                    .collect::<Vec<_>>();
+                format!("[{}]", fields.join(", "))
+            }
+        }
+    }
+
+    fn type_path(&self, type_path: &TypePath) -> String {
+        match type_path.inner() {
+            TypePathInner::Parameter(param) => param.to_token_stream().to_string(),
+            TypePathInner::Type(ty) => self.type_path_type(ty),
+        }
+    }
+
+    fn type_path_type(&self, ty: &TypePathType) -> String {
+        match ty {
+            TypePathType::Path { path, params } => {
+                let params = if params.is_empty() {
+                    String::new()
+                } else {
+                    let params = params.iter().map(|p| self.type_path(p)).collect::<Vec<_>>();
+                    format!("<{}>", params.join(", "))
+                };
+                let segments = path_segments(path);
+                let is_generated = path.leading_colon.is_none()
+                    && segments.first().is_some_and(|s| *s == self.root_mod);
+                if is_generated {
+                    return format!("{}{params}", segments.join("."));
+                }
+                if let Some(ts_type) = self.ts_settings.external_types.get(&segments) {
+                    return ts_type.clone();
+                }
+                match segments.last().map(|s| s.as_str()) {
+                    Some(ident) if TYPESCRIPT_PRELUDE_TYPES.contains(&ident) => {
+                        format!("{ident}{params}")
+                    }
+                    Some(ident) if ident.starts_with("NonZero") => "[number]".to_string(),
+                    _ => "unknown".to_string(),
+                }
+            }
+            TypePathType::Vec { of } | TypePathType::Array { of, .. } => {
+                format!("{}[]", self.type_path(of))
+            }
+            TypePathType::Tuple { elements } => {
+                let elements = elements
+                    .iter()
+                    .map(|e| self.type_path(e))
+                    .collect::<Vec<_>>();
+                format!("[{}]", elements.join(", "))
+            }
+            TypePathType::Primitive { def } | TypePathType::MappedPrimitive { def, .. } => {
+                primitive(def).to_string()
+            }
+            TypePathType::Compact { inner, .. } => self.type_path(inner),
+            TypePathType::BitVec { .. } => "BitSequence".to_string(),
+        }
+    }
+}
+
+/// Field and variant names are used as they are in the metadata, instead of the rust identifiers from the naming policy.
+fn field_name(ident: &syn::Ident, original_name: &Option<String>) -> String {
+    match original_name {
+        Some(name) => name.strip_prefix("r#").unwrap_or(name).to_string(),
+        None => ident.unraw().to_string(),
+    }
+}
+
+fn primitive(def: &TypeDefPrimitive) -> &'static str {
+    match def {
+        TypeDefPrimitive::Bool => "boolean",
+        TypeDefPrimitive::Char | TypeDefPrimitive::Str => "string",
+        TypeDefPrimitive::U8
+        | TypeDefPrimitive::U16
+        | TypeDefPrimitive::U32
+        | TypeDefPrimitive::U64
+        | TypeDefPrimitive::U128
+        | TypeDefPrimitive::I8
+        | TypeDefPrimitive::I16
+        | TypeDefPrimitive::I32
+        | TypeDefPrimitive::I64
+        | TypeDefPrimitive::I128 => "number",
+        // 256 bit integers are represented as their 32 little endian bytes.
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => "number[]",
+    }
+}
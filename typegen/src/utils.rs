@@ -4,6 +4,10 @@ use std::collections::{HashMap, HashSet};
 
 use crate::TypegenError;
 
+mod diff;
+
+pub use diff::{diff_registries, AmbiguousDiff, Compatibility, RegistryDiff, TypeChange, TypeDiff};
+
 /// Converts a [`scale_info::Type`] into a [`syn::TypePath`].
 pub fn syn_type_path(ty: &Type<PortableForm>) -> Result<syn::TypePath, TypegenError> {
     let joined_path = ty.path.segments.join("::");
@@ -74,6 +78,7 @@ pub fn ensure_unique_type_paths(types: &mut PortableRegistry) -> Result<(), Type
 
     for groups_with_same_path in groups_that_need_renaming {
         let mut n = 1;
+        #[allow(clippy::explicit_counter_loop)]
         for group_with_same_shape in groups_with_same_path {
             for ty_id in group_with_same_shape {
                 let ty = types
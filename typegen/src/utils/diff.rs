@@ -0,0 +1,906 @@
+use std::collections::{BTreeMap, HashSet};
+
+use scale_info::{form::PortableForm, Field, PortableRegistry, Type, TypeDef};
+
+use crate::TypegenError;
+
+use super::sanity_pass;
+
+/// The differences between two type registries, e.g. the metadata before and after a runtime upgrade.
+///
+/// Types are matched by their path. Types without a namespace (i.e. prelude types like `Option`) are not compared directly,
+/// but show up as field changes of the types that contain them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistryDiff {
+    /// Ids of types in the new registry, whose path is not present in the old registry.
+    pub added: Vec<u32>,
+    /// Ids of types in the old registry, whose path is not present in the new registry.
+    pub removed: Vec<u32>,
+    /// Types that are present in both registries but differ.
+    pub changed: Vec<TypeDiff>,
+    /// Types whose path is shared by several instances (e.g. of a generic type) in one of the registries, where
+    /// the changed instances cannot be matched up unambiguously.
+    pub ambiguous: Vec<AmbiguousDiff>,
+}
+
+/// Instances of types with the same path that changed, but cannot be matched up between the registries.
+/// Instances that are present in both registries are not listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmbiguousDiff {
+    /// Path segments of the types.
+    pub path: Vec<String>,
+    /// Ids of the instances in the old registry that are not present in the new registry.
+    pub old_type_ids: Vec<u32>,
+    /// Ids of the instances in the new registry that are not present in the old registry.
+    pub new_type_ids: Vec<u32>,
+}
+
+/// The differences of a type that is present in both registries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDiff {
+    /// Path segments of the type.
+    pub path: Vec<String>,
+    /// Id of the type in the old registry.
+    pub old_type_id: u32,
+    /// Id of the type in the new registry.
+    pub new_type_id: u32,
+    /// Structural changes of the type.
+    pub changes: Vec<TypeChange>,
+    /// Whether values encoded as the old type can still be decoded as the new type.
+    pub compatibility: Compatibility,
+}
+
+/// A structural change of a type.
+///
+/// Fields of variants are referred to by the variant name, fields of structs have no variant.
+/// Unnamed fields are referred to by their index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeChange {
+    /// The kind of the type changed, e.g. from a struct to an enum.
+    KindChanged,
+    /// The generic parameters of the type changed.
+    GenericsChanged {
+        /// Names of the old generic parameters.
+        old: Vec<String>,
+        /// Names of the new generic parameters.
+        new: Vec<String>,
+    },
+    /// The concrete type of a generic parameter changed, e.g. from `Wrap<u8>` to `Wrap<u64>`.
+    GenericArgumentRetyped {
+        /// The name of the generic parameter.
+        name: String,
+    },
+    /// A field was added.
+    FieldAdded {
+        /// The variant the field belongs to.
+        variant: Option<String>,
+        /// The field name or index.
+        field: String,
+    },
+    /// A field was removed.
+    FieldRemoved {
+        /// The variant the field belongs to.
+        variant: Option<String>,
+        /// The field name or index.
+        field: String,
+    },
+    /// The type of a field changed.
+    FieldRetyped {
+        /// The variant the field belongs to.
+        variant: Option<String>,
+        /// The field name or index.
+        field: String,
+    },
+    /// Fields that are present before and after changed their order.
+    FieldsReordered {
+        /// The variant the fields belong to.
+        variant: Option<String>,
+    },
+    /// A variant was added.
+    VariantAdded {
+        /// The variant name.
+        name: String,
+        /// The index of the variant.
+        index: u8,
+    },
+    /// A variant was removed.
+    VariantRemoved {
+        /// The variant name.
+        name: String,
+        /// The index of the variant.
+        index: u8,
+    },
+    /// A variant has a different index.
+    VariantReindexed {
+        /// The variant name.
+        name: String,
+        /// The old index of the variant.
+        old_index: u8,
+        /// The new index of the variant.
+        new_index: u8,
+    },
+    /// A type without fields or variants (e.g. a sequence or a primitive) changed.
+    ShapeChanged,
+}
+
+/// Whether the SCALE encoding of a type is still compatible after a change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    /// The encoding did not change, e.g. when only type or field names changed.
+    Compatible,
+    /// Old values can be decoded as the new type, but not the other way around, e.g. when variants were added.
+    BackwardsCompatible,
+    /// Old values cannot be decoded as the new type.
+    Incompatible,
+}
+
+/// Compares the types of two registries by their path and reports the types that were added, removed or changed.
+pub fn diff_registries(
+    old: &PortableRegistry,
+    new: &PortableRegistry,
+) -> Result<RegistryDiff, TypegenError> {
+    sanity_pass(old)?;
+    sanity_pass(new)?;
+
+    let mut by_path = BTreeMap::<&[String], (Vec<u32>, Vec<u32>)>::new();
+    for ty in old.types.iter().filter(|ty| has_namespace(&ty.ty)) {
+        by_path
+            .entry(&ty.ty.path.segments)
+            .or_default()
+            .0
+            .push(ty.id);
+    }
+    for ty in new.types.iter().filter(|ty| has_namespace(&ty.ty)) {
+        by_path
+            .entry(&ty.ty.path.segments)
+            .or_default()
+            .1
+            .push(ty.id);
+    }
+
+    let mut diff = RegistryDiff::default();
+    let mut structural_eq = StructuralEq::new(old, new);
+    for (path, (mut old_ids, mut new_ids)) in by_path {
+        // Types can share a path if they are different instances of a generic type.
+        // Instances that are present in both registries are unchanged.
+        old_ids.retain(|&old_id| {
+            let new_idx = new_ids
+                .iter()
+                .position(|&new_id| structural_eq.equal(old_id, new_id));
+            if let Some(new_idx) = new_idx {
+                new_ids.remove(new_idx);
+            }
+            new_idx.is_none()
+        });
+
+        match (&old_ids[..], &new_ids[..]) {
+            ([], _) | (_, []) => {
+                diff.removed.extend(old_ids);
+                diff.added.extend(new_ids);
+            }
+            ([old_id], [new_id]) => {
+                let changes = type_changes(&mut structural_eq, *old_id, *new_id);
+                diff.changed.push(TypeDiff {
+                    path: path.to_vec(),
+                    old_type_id: *old_id,
+                    new_type_id: *new_id,
+                    changes,
+                    compatibility: compatibility(old, *old_id, new, *new_id),
+                });
+            }
+            _ => diff.ambiguous.push(AmbiguousDiff {
+                path: path.to_vec(),
+                old_type_ids: old_ids,
+                new_type_ids: new_ids,
+            }),
+        }
+    }
+    Ok(diff)
+}
+
+fn has_namespace(ty: &Type<PortableForm>) -> bool {
+    !ty.path.namespace().is_empty()
+}
+
+fn generic_names(types: &PortableRegistry, type_id: u32) -> Vec<String> {
+    let ty = types
+        .resolve(type_id)
+        .expect("type should exist in registry");
+    ty.type_params.iter().map(|p| p.name.clone()).collect()
+}
+
+fn type_changes(structural_eq: &mut StructuralEq, old_id: u32, new_id: u32) -> Vec<TypeChange> {
+    let (old, new) = (structural_eq.old, structural_eq.new);
+    let mut changes = vec![];
+    let old_ty = old.resolve(old_id).expect("type should exist in registry");
+    let new_ty = new.resolve(new_id).expect("type should exist in registry");
+
+    let old_generics = generic_names(old, old_id);
+    let new_generics = generic_names(new, new_id);
+    if old_generics != new_generics {
+        changes.push(TypeChange::GenericsChanged {
+            old: old_generics,
+            new: new_generics,
+        });
+    } else {
+        for (old_param, new_param) in old_ty.type_params.iter().zip(&new_ty.type_params) {
+            if !structural_eq.equal_opt(old_param.ty.map(|t| t.id), new_param.ty.map(|t| t.id)) {
+                changes.push(TypeChange::GenericArgumentRetyped {
+                    name: old_param.name.clone(),
+                });
+            }
+        }
+    }
+
+    let mut fields = FieldsDiff { structural_eq };
+    match (&old_ty.type_def, &new_ty.type_def) {
+        (TypeDef::Composite(a), TypeDef::Composite(b)) => {
+            fields.changes(None, &a.fields, &b.fields, &mut changes);
+        }
+        (TypeDef::Variant(a), TypeDef::Variant(b)) => {
+            for old_variant in &a.variants {
+                let Some(new_variant) = b.variants.iter().find(|v| v.name == old_variant.name)
+                else {
+                    changes.push(TypeChange::VariantRemoved {
+                        name: old_variant.name.clone(),
+                        index: old_variant.index,
+                    });
+                    continue;
+                };
+                if old_variant.index != new_variant.index {
+                    changes.push(TypeChange::VariantReindexed {
+                        name: old_variant.name.clone(),
+                        old_index: old_variant.index,
+                        new_index: new_variant.index,
+                    });
+                }
+                fields.changes(
+                    Some(&old_variant.name),
+                    &old_variant.fields,
+                    &new_variant.fields,
+                    &mut changes,
+                );
+            }
+            for new_variant in &b.variants {
+                if !a.variants.iter().any(|v| v.name == new_variant.name) {
+                    changes.push(TypeChange::VariantAdded {
+                        name: new_variant.name.clone(),
+                        index: new_variant.index,
+                    });
+                }
+            }
+        }
+        (a, b) if std::mem::discriminant(a) != std::mem::discriminant(b) => {
+            changes.push(TypeChange::KindChanged);
+        }
+        _ => {
+            if !fields.structural_eq.equal(old_id, new_id) {
+                changes.push(TypeChange::ShapeChanged);
+            }
+        }
+    }
+    changes
+}
+
+struct FieldsDiff<'a, 'b> {
+    structural_eq: &'b mut StructuralEq<'a>,
+}
+
+impl FieldsDiff<'_, '_> {
+    fn changes(
+        &mut self,
+        variant: Option<&String>,
+        old_fields: &[Field<PortableForm>],
+        new_fields: &[Field<PortableForm>],
+        changes: &mut Vec<TypeChange>,
+    ) {
+        let variant = || variant.cloned();
+        let old_names = field_names(old_fields);
+        let new_names = field_names(new_fields);
+
+        for (old_field, name) in old_fields.iter().zip(&old_names) {
+            let Some(new_idx) = new_names.iter().position(|n| n == name) else {
+                changes.push(TypeChange::FieldRemoved {
+                    variant: variant(),
+                    field: name.clone(),
+                });
+                continue;
+            };
+            let new_field = &new_fields[new_idx];
+            if !self.structural_eq.equal(old_field.ty.id, new_field.ty.id) {
+                changes.push(TypeChange::FieldRetyped {
+                    variant: variant(),
+                    field: name.clone(),
+                });
+            }
+        }
+        for name in &new_names {
+            if !old_names.contains(name) {
+                changes.push(TypeChange::FieldAdded {
+                    variant: variant(),
+                    field: name.clone(),
+                });
+            }
+        }
+
+        let retained_old = old_names.iter().filter(|n| new_names.contains(n));
+        let retained_new = new_names.iter().filter(|n| old_names.contains(n));
+        if !retained_old.eq(retained_new) {
+            changes.push(TypeChange::FieldsReordered { variant: variant() });
+        }
+    }
+}
+
+fn field_names(fields: &[Field<PortableForm>]) -> Vec<String> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| f.name.clone().unwrap_or_else(|| i.to_string()))
+        .collect()
+}
+
+/// Compares two types from different registries by their structure: the paths of the types, the names of
+/// fields, variants and generic parameters, variant indexes and (recursively) the concrete types of all fields
+/// and generic parameters. Unlike [`super::types_equal`], generic parameters are not matched up by name or
+/// position, so e.g. `Wrap<u8>` and `Wrap<u64>` are different. Docs and type names of fields are ignored.
+struct StructuralEq<'a> {
+    old: &'a PortableRegistry,
+    new: &'a PortableRegistry,
+    /// Pairs of types that are known to be equal.
+    equal: HashSet<(u32, u32)>,
+    /// Pairs of types that are compared in the current comparison. Recursive types are assumed to be equal
+    /// when they are encountered again.
+    visited: HashSet<(u32, u32)>,
+}
+
+impl<'a> StructuralEq<'a> {
+    fn new(old: &'a PortableRegistry, new: &'a PortableRegistry) -> Self {
+        Self {
+            old,
+            new,
+            equal: HashSet::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    fn equal(&mut self, old_id: u32, new_id: u32) -> bool {
+        let equal = self.equal_inner(old_id, new_id);
+        let visited = std::mem::take(&mut self.visited);
+        // Comparisons short circuit, so all visited pairs are equal if the types are equal.
+        if equal {
+            self.equal.extend(visited);
+        }
+        equal
+    }
+
+    fn equal_opt(&mut self, old: Option<u32>, new: Option<u32>) -> bool {
+        match (old, new) {
+            (None, None) => true,
+            (Some(old), Some(new)) => self.equal(old, new),
+            _ => false,
+        }
+    }
+
+    fn equal_inner(&mut self, old_id: u32, new_id: u32) -> bool {
+        if self.equal.contains(&(old_id, new_id)) || !self.visited.insert((old_id, new_id)) {
+            return true;
+        }
+        let old_ty = self
+            .old
+            .resolve(old_id)
+            .expect("type should exist in registry");
+        let new_ty = self
+            .new
+            .resolve(new_id)
+            .expect("type should exist in registry");
+
+        if old_ty.path != new_ty.path || old_ty.type_params.len() != new_ty.type_params.len() {
+            return false;
+        }
+        let type_params_equal = old_ty
+            .type_params
+            .iter()
+            .zip(&new_ty.type_params)
+            .all(|(a, b)| {
+                a.name == b.name
+                    && match (a.ty, b.ty) {
+                        (None, None) => true,
+                        (Some(a), Some(b)) => self.equal_inner(a.id, b.id),
+                        _ => false,
+                    }
+            });
+        if !type_params_equal {
+            return false;
+        }
+
+        match (&old_ty.type_def, &new_ty.type_def) {
+            (TypeDef::Composite(a), TypeDef::Composite(b)) => self.fields(&a.fields, &b.fields),
+            (TypeDef::Variant(a), TypeDef::Variant(b)) => {
+                a.variants.len() == b.variants.len()
+                    && a.variants.iter().zip(&b.variants).all(|(a, b)| {
+                        a.name == b.name && a.index == b.index && self.fields(&a.fields, &b.fields)
+                    })
+            }
+            (TypeDef::Sequence(a), TypeDef::Sequence(b)) => {
+                self.equal_inner(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::Array(a), TypeDef::Array(b)) => {
+                a.len == b.len && self.equal_inner(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::Tuple(a), TypeDef::Tuple(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields
+                        .iter()
+                        .zip(&b.fields)
+                        .all(|(a, b)| self.equal_inner(a.id, b.id))
+            }
+            (TypeDef::Primitive(a), TypeDef::Primitive(b)) => a == b,
+            (TypeDef::Compact(a), TypeDef::Compact(b)) => {
+                self.equal_inner(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::BitSequence(a), TypeDef::BitSequence(b)) => {
+                self.equal_inner(a.bit_order_type.id, b.bit_order_type.id)
+                    && self.equal_inner(a.bit_store_type.id, b.bit_store_type.id)
+            }
+            _ => false,
+        }
+    }
+
+    fn fields(
+        &mut self,
+        old_fields: &[Field<PortableForm>],
+        new_fields: &[Field<PortableForm>],
+    ) -> bool {
+        old_fields.len() == new_fields.len()
+            && old_fields
+                .iter()
+                .zip(new_fields)
+                .all(|(a, b)| a.name == b.name && self.equal_inner(a.ty.id, b.ty.id))
+    }
+}
+
+/// Compares the encoding of two types. Names of types, fields and variants are irrelevant here,
+/// only the order of fields and the indexes of variants matter.
+fn compatibility(
+    old: &PortableRegistry,
+    old_id: u32,
+    new: &PortableRegistry,
+    new_id: u32,
+) -> Compatibility {
+    CompatibilityCheck {
+        old,
+        new,
+        visited: HashSet::new(),
+    }
+    .check(old_id, new_id)
+}
+
+struct CompatibilityCheck<'a> {
+    old: &'a PortableRegistry,
+    new: &'a PortableRegistry,
+    visited: HashSet<(u32, u32)>,
+}
+
+impl CompatibilityCheck<'_> {
+    fn check(&mut self, old_id: u32, new_id: u32) -> Compatibility {
+        // Recursive types are compatible as far as we've checked them already.
+        if !self.visited.insert((old_id, new_id)) {
+            return Compatibility::Compatible;
+        }
+        let old_ty = self
+            .old
+            .resolve(old_id)
+            .expect("type should exist in registry");
+        let new_ty = self
+            .new
+            .resolve(new_id)
+            .expect("type should exist in registry");
+
+        match (&old_ty.type_def, &new_ty.type_def) {
+            (TypeDef::Composite(a), TypeDef::Composite(b)) => self.fields(&a.fields, &b.fields),
+            (TypeDef::Variant(a), TypeDef::Variant(b)) => {
+                let mut compatibility = Compatibility::Compatible;
+                for old_variant in &a.variants {
+                    // A removed or reindexed variant breaks old values, even if another variant takes its index.
+                    let Some(new_variant) = b.variants.iter().find(|v| v.name == old_variant.name)
+                    else {
+                        return Compatibility::Incompatible;
+                    };
+                    if new_variant.index != old_variant.index {
+                        return Compatibility::Incompatible;
+                    }
+                    compatibility =
+                        compatibility.max(self.fields(&old_variant.fields, &new_variant.fields));
+                }
+                if b.variants.len() > a.variants.len() {
+                    compatibility = compatibility.max(Compatibility::BackwardsCompatible);
+                }
+                compatibility
+            }
+            (TypeDef::Sequence(a), TypeDef::Sequence(b)) => {
+                self.check(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::Array(a), TypeDef::Array(b)) if a.len == b.len => {
+                self.check(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::Tuple(a), TypeDef::Tuple(b)) if a.fields.len() == b.fields.len() => a
+                .fields
+                .iter()
+                .zip(&b.fields)
+                .map(|(a, b)| self.check(a.id, b.id))
+                .max()
+                .unwrap_or(Compatibility::Compatible),
+            (TypeDef::Primitive(a), TypeDef::Primitive(b)) if a == b => Compatibility::Compatible,
+            (TypeDef::Compact(a), TypeDef::Compact(b)) => {
+                self.check(a.type_param.id, b.type_param.id)
+            }
+            (TypeDef::BitSequence(a), TypeDef::BitSequence(b)) => self
+                .check(a.bit_order_type.id, b.bit_order_type.id)
+                .max(self.check(a.bit_store_type.id, b.bit_store_type.id)),
+            _ => Compatibility::Incompatible,
+        }
+    }
+
+    fn fields(
+        &mut self,
+        old_fields: &[Field<PortableForm>],
+        new_fields: &[Field<PortableForm>],
+    ) -> Compatibility {
+        if old_fields.len() != new_fields.len() {
+            return Compatibility::Incompatible;
+        }
+        old_fields
+            .iter()
+            .zip(new_fields)
+            .map(|(a, b)| self.check(a.ty.id, b.ty.id))
+            .max()
+            .unwrap_or(Compatibility::Compatible)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    use super::*;
+
+    /// Registers `T` and removes `module` from all type paths, such that types from the
+    /// `old` and `new` test modules share their paths.
+    fn registry_without_module<T: TypeInfo + 'static>(module: &str) -> PortableRegistry {
+        let mut registry = scale_info::Registry::new();
+        registry.register_type(&scale_info::meta_type::<T>());
+        let mut registry = PortableRegistry::from(registry);
+        for ty in registry.types.iter_mut() {
+            ty.ty.path.segments.retain(|s| s != module);
+        }
+        registry
+    }
+
+    fn type_id(registry: &PortableRegistry, ident: &str) -> u32 {
+        registry
+            .types
+            .iter()
+            .find(|ty| ty.ty.path.ident().as_deref() == Some(ident))
+            .unwrap()
+            .id
+    }
+
+    #[allow(unused)]
+    mod old {
+        use scale_info::TypeInfo;
+
+        #[derive(TypeInfo)]
+        pub struct Root {
+            pub unchanged: Unchanged,
+            pub call: Call,
+            pub account: Account,
+            pub removed: Removed,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Unchanged(pub u8);
+
+        #[derive(TypeInfo)]
+        pub enum Call {
+            #[codec(index = 0)]
+            Transfer { amount: u64 },
+            #[codec(index = 1)]
+            Burn(u32),
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub id: [u8; 4],
+            pub balance: u64,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Removed;
+    }
+
+    #[allow(unused)]
+    mod new {
+        use scale_info::TypeInfo;
+
+        #[derive(TypeInfo)]
+        pub struct Root {
+            pub unchanged: Unchanged,
+            pub call: Call,
+            pub account: Account,
+            pub added: Added,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Unchanged(pub u8);
+
+        #[derive(TypeInfo)]
+        pub enum Call {
+            #[codec(index = 0)]
+            Transfer { amount: u64 },
+            #[codec(index = 1)]
+            Burn(u32),
+            #[codec(index = 2)]
+            Mint(u32),
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Account {
+            pub balance: u128,
+            pub id: [u8; 4],
+            pub nonce: u32,
+        }
+
+        #[derive(TypeInfo)]
+        pub struct Added;
+    }
+
+    #[test]
+    fn diff_registries_test() {
+        let old = registry_without_module::<old::Root>("old");
+        let new = registry_without_module::<new::Root>("new");
+        let diff = diff_registries(&old, &new).unwrap();
+
+        assert_eq!(diff.added, vec![type_id(&new, "Added")]);
+        assert_eq!(diff.removed, vec![type_id(&old, "Removed")]);
+
+        let changed = diff
+            .changed
+            .iter()
+            .map(|d| (d.path.last().unwrap().as_str(), d))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            changed.keys().copied().collect::<Vec<_>>(),
+            ["Account", "Call", "Root"]
+        );
+
+        let call = changed["Call"];
+        assert_eq!(
+            call.changes,
+            vec![TypeChange::VariantAdded {
+                name: "Mint".into(),
+                index: 2
+            }]
+        );
+        assert_eq!(call.compatibility, Compatibility::BackwardsCompatible);
+
+        let account = changed["Account"];
+        assert_eq!(
+            account.changes,
+            vec![
+                TypeChange::FieldRetyped {
+                    variant: None,
+                    field: "balance".into()
+                },
+                TypeChange::FieldAdded {
+                    variant: None,
+                    field: "nonce".into()
+                },
+                TypeChange::FieldsReordered { variant: None },
+            ]
+        );
+        assert_eq!(account.compatibility, Compatibility::Incompatible);
+
+        let root = changed["Root"];
+        assert_eq!(
+            root.changes,
+            vec![
+                TypeChange::FieldRetyped {
+                    variant: None,
+                    field: "call".into()
+                },
+                TypeChange::FieldRetyped {
+                    variant: None,
+                    field: "account".into()
+                },
+                TypeChange::FieldRemoved {
+                    variant: None,
+                    field: "removed".into()
+                },
+                TypeChange::FieldAdded {
+                    variant: None,
+                    field: "added".into()
+                },
+            ]
+        );
+        assert_eq!(root.compatibility, Compatibility::Incompatible);
+    }
+
+    #[test]
+    fn retyped_generic_arguments_are_changes() {
+        #[allow(unused)]
+        mod old {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Root {
+                pub x: Option<u32>,
+                pub v: Vec<u8>,
+                pub w: Wrap<u8>,
+            }
+
+            #[derive(scale_info::TypeInfo)]
+            pub struct Wrap<T>(pub T);
+        }
+
+        #[allow(unused)]
+        mod new {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Root {
+                pub x: Option<u128>,
+                pub v: Vec<u16>,
+                pub w: Wrap<u64>,
+            }
+
+            #[derive(scale_info::TypeInfo)]
+            pub struct Wrap<T>(pub T);
+        }
+
+        let old = registry_without_module::<old::Root>("old");
+        let new = registry_without_module::<new::Root>("new");
+        let diff = diff_registries(&old, &new).unwrap();
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert!(diff.ambiguous.is_empty());
+        let changed = diff
+            .changed
+            .iter()
+            .map(|d| (d.path.last().unwrap().as_str(), d))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            changed.keys().copied().collect::<Vec<_>>(),
+            ["Root", "Wrap"]
+        );
+
+        let root = changed["Root"];
+        assert_eq!(
+            root.changes,
+            ["x", "v", "w"]
+                .into_iter()
+                .map(|field| TypeChange::FieldRetyped {
+                    variant: None,
+                    field: field.into()
+                })
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(root.compatibility, Compatibility::Incompatible);
+
+        let wrap = changed["Wrap"];
+        assert_eq!(
+            wrap.changes,
+            vec![
+                TypeChange::GenericArgumentRetyped { name: "T".into() },
+                TypeChange::FieldRetyped {
+                    variant: None,
+                    field: "0".into()
+                },
+            ]
+        );
+        assert_eq!(wrap.compatibility, Compatibility::Incompatible);
+    }
+
+    #[test]
+    fn changed_instances_of_generic_types_are_ambiguous() {
+        #[allow(unused)]
+        mod old {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Root {
+                pub a: Wrap<u8>,
+                pub b: Wrap<u16>,
+                pub c: Wrap<bool>,
+            }
+
+            #[derive(scale_info::TypeInfo)]
+            pub struct Wrap<T>(pub T);
+        }
+
+        #[allow(unused)]
+        mod new {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Root {
+                pub a: Wrap<u32>,
+                pub b: Wrap<u64>,
+                pub c: Wrap<bool>,
+            }
+
+            #[derive(scale_info::TypeInfo)]
+            pub struct Wrap<T>(pub T);
+        }
+
+        let old = registry_without_module::<old::Root>("old");
+        let new = registry_without_module::<new::Root>("new");
+        let diff = diff_registries(&old, &new).unwrap();
+
+        // The instances of `Wrap` in the order of the fields of `Root`.
+        let wrap_ids = |registry: &PortableRegistry| {
+            let root = registry.resolve(type_id(registry, "Root")).unwrap();
+            let TypeDef::Composite(root) = &root.type_def else {
+                panic!("Root should be a struct");
+            };
+            root.fields.iter().map(|f| f.ty.id).collect::<Vec<_>>()
+        };
+        let old_wraps = wrap_ids(&old);
+        let new_wraps = wrap_ids(&new);
+
+        // `Wrap<bool>` is unchanged, the other instances cannot be matched up.
+        assert_eq!(diff.ambiguous.len(), 1);
+        let ambiguous = &diff.ambiguous[0];
+        assert_eq!(ambiguous.path.last().unwrap(), "Wrap");
+        assert_eq!(ambiguous.old_type_ids, old_wraps[..2]);
+        assert_eq!(ambiguous.new_type_ids, new_wraps[..2]);
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].path.last().unwrap(), "Root");
+    }
+
+    #[test]
+    fn reindexed_variants_are_incompatible() {
+        #[allow(unused)]
+        mod old {
+            #[derive(scale_info::TypeInfo)]
+            pub enum E {
+                #[codec(index = 0)]
+                A,
+                #[codec(index = 1)]
+                B,
+            }
+        }
+
+        #[allow(unused)]
+        mod new {
+            #[derive(scale_info::TypeInfo)]
+            pub enum E {
+                #[codec(index = 1)]
+                A,
+                #[codec(index = 0)]
+                C,
+            }
+        }
+
+        let old = registry_without_module::<old::E>("old");
+        let new = registry_without_module::<new::E>("new");
+        let diff = diff_registries(&old, &new).unwrap();
+
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![
+                TypeChange::VariantReindexed {
+                    name: "A".into(),
+                    old_index: 0,
+                    new_index: 1
+                },
+                TypeChange::VariantRemoved {
+                    name: "B".into(),
+                    index: 1
+                },
+                TypeChange::VariantAdded {
+                    name: "C".into(),
+                    index: 0
+                },
+            ]
+        );
+        // Old values of `A` would be decoded as `C`:
+        assert_eq!(diff.changed[0].compatibility, Compatibility::Incompatible);
+    }
+}